@@ -8,7 +8,15 @@ export enum Code {
 	ProjectsGitAuth = 'errors.projects.git.auth',
 	DefaultTargetNotFound = 'errors.projects.default_target.not_found',
 	CommitSigningFailed = 'errors.commit.signing_failed',
-	ProjectMissing = 'errors.projects.missing'
+	ProjectMissing = 'errors.projects.missing',
+	ProjectCheckoutWouldOverwrite = 'errors.projects.checkout.would_overwrite',
+	ProjectGitSizeLimit = 'errors.projects.git.size_limit',
+	ProjectDirtyWorktree = 'errors.projects.worktree.dirty',
+	ProjectGitTimeout = 'errors.projects.git.timeout',
+	ProjectRefExists = 'errors.projects.ref.exists',
+	ProjectGitLocked = 'errors.projects.git.locked',
+	ProjectGitServerRefused = 'errors.projects.git.server_refused',
+	Cancelled = 'errors.cancelled'
 }
 
 export function isUserErrorCode(something: unknown): something is Code {
@@ -339,15 +339,27 @@ export class BranchController {
 		}
 	}
 
-	async pushBranch(branchId: string, withForce: boolean): Promise<BranchPushResult | undefined> {
+	async pushBranch(branchId: string, withForce: boolean): Promise<SeriesPushResult[] | undefined> {
 		try {
-			const pushResult = await invoke<BranchPushResult | undefined>('push_stack', {
+			const pushResult = await invoke<SeriesPushResult[]>('push_stack', {
 				projectId: this.projectId,
 				branchId,
 				withForce
 			});
 			this.posthog.capture('Push Successful');
 			await this.vbranchService.refresh();
+			const failed = pushResult.filter(
+				(result): result is Extract<SeriesPushResult, { status: 'error' }> =>
+					result.status === 'error'
+			);
+			if (failed.length > 0) {
+				showToast({
+					title: 'Some branches could not be pushed',
+					message: failed.map((result) => `- ${result.name}`).join('\n'),
+					error: failed.map((result) => `${result.name}: ${result.error.message}`).join('\n'),
+					style: 'warning'
+				});
+			}
 			return pushResult;
 		} catch (err: any) {
 			console.error(err);
@@ -547,7 +559,8 @@ export class BranchController {
 		}
 	}
 }
-export interface BranchPushResult {
-	refname: string;
-	remote: string;
-}
+/** The result of pushing one series of a stack. */
+export type SeriesPushResult =
+	| { status: 'pushed'; name: string; remoteMessages: string[] }
+	| { status: 'upToDate'; name: string }
+	| { status: 'error'; name: string; error: { code: string; message: string } };
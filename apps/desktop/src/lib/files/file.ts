@@ -1,5 +1,6 @@
 import { RemoteHunk } from '$lib/hunks/hunk';
 import { Hunk, type HunkLock } from '$lib/hunks/hunk';
+import type { ModeChange } from '@gitbutler/ui/file/types';
 import { isDefined } from '@gitbutler/ui/utils/typeguards';
 import { Transform, Type } from 'class-transformer';
 import 'reflect-metadata';
@@ -16,6 +17,8 @@ export class RemoteFile {
 	hunks!: RemoteHunk[];
 	binary!: boolean;
 	large!: boolean;
+	// The old and new mode if the executable bit of the file changed.
+	modeChange?: ModeChange | null;
 
 	get id(): string {
 		return 'remote:' + this.path;
@@ -68,6 +71,8 @@ export class LocalFile {
 	content!: string;
 	binary!: boolean;
 	large!: boolean;
+	// The old and new mode if the executable bit of the file changed.
+	modeChange?: ModeChange | null;
 
 	get filename(): string {
 		const parts = this.path.split('/');
@@ -4,7 +4,7 @@ import { createSelectNth } from '$lib/state/customSelectors';
 import { ReduxTag } from '$lib/state/tags';
 import { createEntityAdapter, type EntityState } from '@reduxjs/toolkit';
 import type { PostHogWrapper } from '$lib/analytics/posthog';
-import type { BranchPushResult } from '$lib/branches/branchController';
+import type { SeriesPushResult } from '$lib/branches/branchController';
 import type { Commit, StackBranch, UpstreamCommit } from '$lib/branches/v3';
 import type { CommitKey } from '$lib/commits/commit';
 import type { TreeChange } from '$lib/hunks/change';
@@ -374,7 +374,7 @@ function injectEndpoints(api: ClientState['backendApi']) {
 				}
 			}),
 			pushStack: build.mutation<
-				BranchPushResult,
+				SeriesPushResult[],
 				{ projectId: string; stackId: string; withForce: boolean }
 			>({
 				query: ({ projectId, stackId, withForce }) => ({
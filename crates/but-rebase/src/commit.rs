@@ -93,6 +93,10 @@ pub(crate) fn update_committer(
 }
 
 /// Sign the given `buffer` using configuration from `repo`, just like Git would.
+///
+/// The program configured in `gpg.program`, or `gpg.ssh.program` if `gpg.format` is `ssh`, is used
+/// for signing if set. If it can't be found or fails, [`Code::CommitSigningFailed`] is attached
+/// to the error along with its output.
 pub fn sign_buffer(repo: &gix::Repository, buffer: &[u8]) -> anyhow::Result<BString> {
     // TODO: support gpg.ssh.defaultKeyCommand to get the signing key if this value doesn't exist
     let config = repo.config_snapshot();
@@ -121,8 +125,7 @@ pub fn sign_buffer(repo: &gix::Repository, buffer: &[u8]) -> anyhow::Result<BStr
                 |program| Cow::Owned(program.into_owned().into()),
             );
 
-        let cmd =
-            prepare_with_shell(gpg_program.into_owned()).args(["-Y", "sign", "-n", "git", "-f"]);
+        let cmd = prepare_with_shell(gpg_program.as_ref()).args(["-Y", "sign", "-n", "git", "-f"]);
 
         // Write the key to a temp file. This is needs to be created in the
         // same scope where its used; IE: in the command, otherwise the
@@ -152,11 +155,29 @@ pub fn sign_buffer(repo: &gix::Repository, buffer: &[u8]) -> anyhow::Result<BStr
             cmd.arg(signing_key.into_owned())
                 .arg(buffer_file_to_sign_path.to_path_buf())
         };
-        let output = into_command(signing_cmd)
+        let output = match into_command(signing_cmd)
             .stderr(Stdio::piped())
             .stdout(Stdio::piped())
             .stdin(Stdio::null())
-            .output()?;
+            .output()
+        {
+            Ok(output) => output,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                return Err(anyhow!(
+                    "Could not find '{}'. Please make sure it is in your `PATH` or configure the full path using `gpg.ssh.program` in the Git configuration",
+                    gpg_program.display()
+                )
+                .context(Code::CommitSigningFailed));
+            }
+            Err(err) => {
+                return Err(err)
+                    .context(format!(
+                        "Could not execute SSH signing program '{}'",
+                        gpg_program.display()
+                    ))
+                    .context(Code::CommitSigningFailed);
+            }
+        };
 
         if output.status.success() {
             // read signed_storage path plus .sig
@@ -168,7 +189,8 @@ pub fn sign_buffer(repo: &gix::Repository, buffer: &[u8]) -> anyhow::Result<BStr
             let stderr = BString::new(output.stderr);
             let stdout = BString::new(output.stdout);
             let std_both = format!("{} {}", stdout, stderr);
-            bail!("Failed to sign SSH: {}", std_both);
+            Err(anyhow!("Failed to sign SSH: {}", std_both.trim())
+                .context(Code::CommitSigningFailed))
         }
     } else {
         let gpg_program = config
@@ -192,13 +214,16 @@ pub fn sign_buffer(repo: &gix::Repository, buffer: &[u8]) -> anyhow::Result<BStr
         let mut child = match cmd.spawn() {
             Ok(child) => child,
             Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
-                bail!(
+                return Err(anyhow!(
                     "Could not find '{}'. Please make sure it is in your `PATH` or configure the full path using `gpg.program` in the Git configuration",
                     gpg_program.display()
                 )
+                .context(Code::CommitSigningFailed));
             }
             Err(err) => {
-                return Err(err).context(format!("Could not execute GPG program using {:?}", cmd));
+                return Err(err)
+                    .context(format!("Could not execute GPG program using {:?}", cmd))
+                    .context(Code::CommitSigningFailed);
             }
         };
         child.stdin.take().expect("configured").write_all(buffer)?;
@@ -212,7 +237,8 @@ pub fn sign_buffer(repo: &gix::Repository, buffer: &[u8]) -> anyhow::Result<BStr
             let stderr = BString::new(output.stderr);
             let stdout = BString::new(output.stdout);
             let std_both = format!("{} {}", stdout, stderr);
-            bail!("Failed to sign GPG: {}", std_both);
+            Err(anyhow!("Failed to sign GPG: {}", std_both.trim())
+                .context(Code::CommitSigningFailed))
         }
     }
 }
@@ -11,6 +11,8 @@ pub struct AppSettings {
     pub context_lines: u32,
     /// Whether the user has passed the onboarding flow.
     pub onboarding_complete: bool,
+    /// The amount of seconds for which the refs advertised by a remote are reused for previews, 0 to always list them.
+    pub remote_refs_cache_ttl_seconds: u64,
     /// Telemetry settings
     pub telemetry: app_settings::TelemetrySettings,
     /// Client ID for the GitHub OAuth application.
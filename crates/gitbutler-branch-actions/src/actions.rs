@@ -524,7 +524,7 @@ pub fn fetch_from_remotes(ctx: &CommandContext, askpass: Option<String>) -> Resu
     let fetch_errors: Vec<_> = remotes
         .iter()
         .filter_map(|remote| {
-            ctx.fetch(remote, askpass.clone())
+            ctx.fetch(remote, vec![], askpass.clone())
                 .err()
                 .map(|err| err.to_string())
         })
@@ -56,6 +56,9 @@ pub fn get_base_branch_data(ctx: &CommandContext) -> Result<BaseBranch> {
 
 fn go_back_to_integration(ctx: &CommandContext, default_target: &Target) -> Result<BaseBranch> {
     let repo = ctx.repo();
+    repo.require_clean().context(Marker::ProjectConflict)?;
+    // Untracked files are fine for `require_clean()`, but could be overwritten by the forced
+    // checkout below.
     let statuses = repo
         .statuses(Some(
             git2::StatusOptions::new()
@@ -6,6 +6,7 @@ use std::ffi::OsStr;
 /// Conflicts are removed as they are resolved, the conflicts file is removed when there are no more conflicts
 /// or when the merge is complete.
 use std::{
+    ffi::OsString,
     io::Write,
     path::{Path, PathBuf},
 };
@@ -13,7 +14,7 @@ use std::{
 use anyhow::{anyhow, bail, Context, Result};
 use bstr::ByteSlice;
 use gitbutler_command_context::CommandContext;
-use gitbutler_error::error::Marker;
+use gitbutler_error::error::{Code, Marker};
 
 pub(crate) fn mark<P: AsRef<Path>, A: AsRef<[P]>>(
     ctx: &CommandContext,
@@ -93,6 +94,186 @@ pub fn resolve<P: AsRef<Path>>(ctx: &CommandContext, path_to_resolve: P) -> Resu
     Ok(())
 }
 
+/// The versions of a conflicted file written to disk, so an external merge tool can be launched with them.
+///
+/// The files of all versions are deleted when this instance is dropped.
+#[derive(Debug)]
+pub struct ConflictFiles {
+    /// The version of the common ancestor, unless the file was added on both sides.
+    pub base: Option<PathBuf>,
+    /// Our version, unless we deleted the file.
+    pub local: Option<PathBuf>,
+    /// Their version, unless they deleted the file.
+    pub remote: Option<PathBuf>,
+    /// The file in the worktree, where the result of the merge is expected.
+    pub worktree: PathBuf,
+}
+
+impl Drop for ConflictFiles {
+    fn drop(&mut self) {
+        for file in [&self.base, &self.local, &self.remote]
+            .into_iter()
+            .flatten()
+        {
+            if let Err(err) = remove_file_ignore_missing(file) {
+                tracing::warn!(?err, ?file, "failed to remove version of conflicted file");
+            }
+        }
+    }
+}
+
+/// Write the base, local and remote versions of the conflicted file at `path` in the index of `repo`
+/// into `dir`, named like `<stem>_BASE.<extension>`, for use with an external merge tool.
+/// The result should be passed to [`ingest()`].
+pub fn extract_versions(repo: &git2::Repository, path: &Path, dir: &Path) -> Result<ConflictFiles> {
+    let conflict = find_conflict(repo, path)?;
+
+    let mut files = ConflictFiles {
+        base: None,
+        local: None,
+        remote: None,
+        worktree: repo
+            .workdir()
+            .context("conflicts can only be resolved in a worktree")?
+            .join(path),
+    };
+    let write_version = |entry: Option<git2::IndexEntry>, label: &str| -> Result<Option<PathBuf>> {
+        let Some(entry) = entry else {
+            return Ok(None);
+        };
+        let file = dir.join(version_file_name(path, label));
+        std::fs::write(&file, repo.find_blob(entry.id)?.content())?;
+        Ok(Some(file))
+    };
+    // Files that were already written are removed by `files` on error.
+    files.base = write_version(conflict.ancestor, "BASE")?;
+    files.local = write_version(conflict.our, "LOCAL")?;
+    files.remote = write_version(conflict.their, "REMOTE")?;
+    Ok(files)
+}
+
+/// Return the content of the common ancestor of the conflicted file at `path` in the index of `repo`,
+/// for display next to both sides, or `None` if there is no common ancestor as the file was added on both sides.
+pub fn base_content(repo: &git2::Repository, path: &Path) -> Result<Option<Vec<u8>>> {
+    let Some(base) = find_conflict(repo, path)?.ancestor else {
+        return Ok(None);
+    };
+    Ok(Some(repo.find_blob(base.id)?.content().to_owned()))
+}
+
+/// Return the conflict of `path` in the index of `repo`, or fail with [`Code::Validation`] if it isn't conflicted.
+fn find_conflict(repo: &git2::Repository, path: &Path) -> Result<git2::IndexConflict> {
+    let path_bytes = path.as_os_str().as_encoded_bytes();
+    repo.index()?
+        .conflicts()?
+        .filter_map(Result::ok)
+        .find(|conflict| {
+            [&conflict.ancestor, &conflict.our, &conflict.their]
+                .into_iter()
+                .flatten()
+                .any(|entry| entry.path == path_bytes)
+        })
+        .ok_or_else(|| anyhow!("'{}' isn't conflicted", path.display()).context(Code::Validation))
+}
+
+/// Write the `resolved` content of the conflicted file at `path` back into the worktree, and mark
+/// it as resolved in the index and in our conflict state.
+pub fn ingest(ctx: &CommandContext, path: &Path, resolved: &Path) -> Result<()> {
+    let repo = ctx.repo();
+    let worktree_path = repo
+        .workdir()
+        .context("conflicts can only be resolved in a worktree")?
+        .join(path);
+    if resolved != worktree_path {
+        std::fs::copy(resolved, &worktree_path)?;
+    }
+
+    let mut index = repo.index()?;
+    // Adding the path replaces all of its conflicting entries.
+    index.add_path(path)?;
+    index.write()?;
+    if is_conflicting(ctx, Some(path))? {
+        resolve(ctx, path)?;
+    }
+    Ok(())
+}
+
+/// Options for [`auto_resolve()`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AutoResolveOptions {
+    /// If `true`, lines that only differ in the amount of whitespace are considered equal, so
+    /// both sides changing the whitespace of the same line differently doesn't conflict.
+    pub whitespace: bool,
+    /// If `true`, regions changed on both sides are resolved by keeping the lines of both sides,
+    /// ours first, which suits files both sides append to, like changelogs.
+    pub union: bool,
+}
+
+/// Merge each conflicted file in the index of the project again, and if that succeeds without
+/// conflicts, write the result into the worktree and mark the file as resolved like [`ingest()`] does.
+/// Regions changed on only one side are always taken from that side, `options` controls what else may be resolved.
+///
+/// Files with genuine conflicts are left untouched, as are files that were added or deleted on one side.
+/// Return the paths of the resolved files, sorted.
+pub fn auto_resolve(ctx: &CommandContext, options: AutoResolveOptions) -> Result<Vec<PathBuf>> {
+    let repo = ctx.repo();
+    let workdir = repo
+        .workdir()
+        .context("conflicts can only be resolved in a worktree")?;
+    let mut index = repo.index()?;
+    let conflicts: Vec<_> = index.conflicts()?.collect::<Result<_, _>>()?;
+
+    let mut merge_opts = git2::MergeFileOptions::new();
+    merge_opts.ignore_whitespace_change(options.whitespace);
+    if options.union {
+        merge_opts.favor(git2::FileFavor::Union);
+    }
+    let mut resolved = Vec::new();
+    for conflict in conflicts {
+        let (Some(ancestor), Some(our), Some(their)) =
+            (conflict.ancestor, conflict.our, conflict.their)
+        else {
+            continue;
+        };
+        let merged = repo.merge_file_from_index(&ancestor, &our, &their, Some(&mut merge_opts))?;
+        if !merged.is_automergeable() {
+            continue;
+        }
+        let path = our.path.to_path()?.to_owned();
+        std::fs::write(workdir.join(&path), merged.content())?;
+        resolved.push(path);
+    }
+    if resolved.is_empty() {
+        return Ok(resolved);
+    }
+
+    // Adding the path replaces all of its conflicting entries.
+    for path in &resolved {
+        index.add_path(path)?;
+    }
+    index.write()?;
+    for path in &resolved {
+        if is_conflicting(ctx, Some(path))? {
+            resolve(ctx, path)?;
+        }
+    }
+    resolved.sort();
+    Ok(resolved)
+}
+
+/// Return `<stem>_<label>.<extension>` for the file name of `path`, keeping the extension so
+/// merge tools can recognize the kind of file.
+fn version_file_name(path: &Path, label: &str) -> OsString {
+    let mut name = path.file_stem().unwrap_or_default().to_owned();
+    name.push("_");
+    name.push(label);
+    if let Some(extension) = path.extension() {
+        name.push(".");
+        name.push(extension);
+    }
+    name
+}
+
 pub(crate) fn conflicting_files(ctx: &CommandContext) -> Result<Vec<PathBuf>> {
     let conflicts_path = conflicts_path(ctx);
     if !conflicts_path.exists() {
@@ -21,6 +21,9 @@ pub struct RemoteBranchFile {
     pub hunks: Vec<gitbutler_diff::GitHunk>,
     pub binary: bool,
     pub large: bool,
+    /// The `(old, new)` mode if the executable bit of the file changed.
+    #[serde(serialize_with = "gitbutler_serde::as_octal_mode_change")]
+    pub mode_change: Option<(git2::FileMode, git2::FileMode)>,
 }
 
 impl From<FileDiff> for RemoteBranchFile {
@@ -44,6 +47,7 @@ impl From<FileDiff> for RemoteBranchFile {
             hunks: file.hunks,
             binary,
             large,
+            mode_change: file.mode_change,
         }
     }
 }
@@ -94,6 +98,9 @@ pub struct VirtualBranchFile {
     pub conflicted: bool,
     pub binary: bool,
     pub large: bool,
+    /// The `(old, new)` mode if the executable bit of the file changed.
+    #[serde(serialize_with = "gitbutler_serde::as_octal_mode_change")]
+    pub mode_change: Option<(git2::FileMode, git2::FileMode)>,
 }
 
 pub trait Get<T> {
@@ -122,8 +129,16 @@ pub(crate) fn list_virtual_commit_files(
         .find_real_tree(&parent, Default::default())
         .context("failed to get parent tree")?;
     let diff = gitbutler_diff::trees(ctx.repo(), &parent_tree, &commit_tree, context_lines)?;
+    let mode_changes: HashMap<_, _> = diff
+        .iter()
+        .filter_map(|(path, file)| Some((path.clone(), file.mode_change?)))
+        .collect();
     let hunks_by_filepath = virtual_hunks_by_file_diffs(&ctx.project().path, diff);
-    Ok(virtual_hunks_into_virtual_files(ctx, hunks_by_filepath))
+    let mut files = virtual_hunks_into_virtual_files(ctx, hunks_by_filepath);
+    for file in &mut files {
+        file.mode_change = mode_changes.get(&file.path).copied();
+    }
+    Ok(files)
 }
 
 fn virtual_hunks_by_file_diffs<'a>(
@@ -159,6 +174,7 @@ pub(crate) fn virtual_hunks_into_virtual_files(
                 large: false,
                 modified_at,
                 conflicted,
+                mode_change: None,
             }
         })
         .collect::<Vec<_>>()
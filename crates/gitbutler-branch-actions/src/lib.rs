@@ -58,7 +58,7 @@ mod author;
 mod gravatar;
 mod status;
 use gitbutler_stack::VirtualBranchesHandle;
-pub use status::get_applied_status;
+pub use status::{effective_diff, get_applied_status, ownership_conflicts};
 trait VirtualBranchesExt {
     fn virtual_branches(&self) -> VirtualBranchesHandle;
 }
@@ -4,10 +4,11 @@ use anyhow::{Context, Result};
 use but_rebase::RebaseStep;
 use gitbutler_command_context::CommandContext;
 use gitbutler_commit::commit_ext::CommitExt;
+use gitbutler_error::error::{AnyhowContextExt, Code, PartialOutcome};
 use gitbutler_oplog::entry::{OperationKind, SnapshotDetails};
 use gitbutler_oplog::{OplogExt, SnapshotExt};
 use gitbutler_reference::normalize_branch_name;
-use gitbutler_repo_actions::RepoActionsExt;
+use gitbutler_repo_actions::{PushOutcome, RepoActionsExt};
 use gitbutler_stack::stack_context::{CommandContextExt, StackContext};
 use gitbutler_stack::{CommitOrChangeId, PatchReferenceUpdate, StackBranch};
 use gitbutler_stack::{Stack, StackId, Target};
@@ -167,9 +168,17 @@ pub fn update_series_pr_number(
     stack.set_pr_number(ctx, &head_name, pr_number)
 }
 
-/// Pushes all series in the stack to the remote.
-/// This operation will error out if the target has no push remote configured.
-pub fn push_stack(ctx: &CommandContext, stack_id: StackId, with_force: bool) -> Result<()> {
+/// Pushes all series in the stack to the remote, and returns the outcome for each series that
+/// needed pushing along with its name, in order. Series the remote already has, according to its
+/// advertised refs, are reported as up to date without pushing them.
+/// Failing to push one series doesn't prevent the others from being pushed, unless the
+/// authentication failed, which would fail for the others as well, so pushing stops then.
+/// Only if all of them failed, this is an error, just like when the target has no push remote configured.
+pub fn push_stack(
+    ctx: &CommandContext,
+    stack_id: StackId,
+    with_force: bool,
+) -> Result<PartialOutcome<(String, PushOutcome), (String, anyhow::Error)>> {
     ctx.verify()?;
     assure_open_workspace_mode(ctx).context("Requires an open workspace mode")?;
     let state = ctx.project().virtual_branches();
@@ -183,6 +192,7 @@ pub fn push_stack(ctx: &CommandContext, stack_id: StackId, with_force: bool) ->
     // First fetch, because we dont want to push integrated series
     ctx.fetch(
         &default_target.push_remote_name(),
+        vec![],
         Some("push_stack".into()),
     )?;
     let gix_repo = ctx.gix_repository_for_merging_non_persisting()?;
@@ -190,6 +200,11 @@ pub fn push_stack(ctx: &CommandContext, stack_id: StackId, with_force: bool) ->
     let mut graph = gix_repo.revision_graph(cache.as_ref());
     let mut check_commit = IsCommitIntegrated::new(ctx, &default_target, &gix_repo, &mut graph)?;
     let stack_branches = stack.branches();
+    // Listed once for all series, to not connect for those the remote already has. If this fails,
+    // pushing will fail as well and report why.
+    let push_remote = default_target.push_remote_name();
+    let advertised = ctx.remote_refs(&push_remote).unwrap_or_default();
+    let mut outcome = PartialOutcome::default();
     for branch in stack_branches {
         if branch.archived {
             // Nothing to push for this one
@@ -203,16 +218,41 @@ pub fn push_stack(ctx: &CommandContext, stack_id: StackId, with_force: bool) ->
             // Already integrated, nothing to push
             continue;
         }
-        let push_details = stack.push_details(ctx, branch.name().to_owned())?;
-        ctx.push(
-            push_details.head,
-            &push_details.remote_refname,
-            with_force,
-            None,
-            Some(Some(stack.id)),
-        )?
+        let name = branch.name().to_owned();
+        let result = stack
+            .push_details(ctx, name.clone())
+            .and_then(|push_details| {
+                let remote_refname = &push_details.remote_refname;
+                let is_advertised = remote_refname.remote() == push_remote
+                    && advertised.iter().any(|advertised| {
+                        advertised.target == push_details.head
+                            && advertised.name == format!("refs/heads/{}", remote_refname.branch())
+                    });
+                if is_advertised {
+                    return Ok(PushOutcome::UpToDate);
+                }
+                ctx.push(
+                    push_details.head,
+                    &push_details.remote_refname,
+                    with_force,
+                    None,
+                    Some(Some(stack.id)),
+                )
+            });
+        let auth_failed = result
+            .as_ref()
+            .err()
+            .and_then(|err| err.custom_context())
+            .is_some_and(|context| context.code == Code::ProjectGitAuth);
+        outcome.push(match result {
+            Ok(push_outcome) => Ok((name, push_outcome)),
+            Err(err) => Err((name, err)),
+        });
+        if auth_failed {
+            break;
+        }
     }
-    Ok(())
+    outcome.into_result().map_err(|(_name, err)| err)
 }
 
 pub(crate) fn branch_integrated(
@@ -9,14 +9,15 @@ use crate::{
     file::{virtual_hunks_into_virtual_files, VirtualBranchFile},
     hunk::{file_hunks_from_diffs, VirtualBranchHunk},
 };
-use anyhow::{bail, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use gitbutler_branch::BranchCreateRequest;
 use gitbutler_command_context::CommandContext;
-use gitbutler_diff::{diff_files_into_hunks, Hunk};
+use gitbutler_diff::{diff_files_into_hunks, FileDiff, Hunk};
+use gitbutler_error::error::Code;
 use gitbutler_hunk_dependency::locks::HunkDependencyResult;
 use gitbutler_operating_modes::assure_open_workspace_mode;
 use gitbutler_project::access::WorktreeWritePermission;
-use gitbutler_stack::{BranchOwnershipClaims, OwnershipClaim, Stack, StackId};
+use gitbutler_stack::{BranchOwnershipClaims, OwnershipClaim, OwnershipConflict, Stack, StackId};
 use tracing::instrument;
 
 /// Represents the uncommitted status of the applied virtual branches in the workspace.
@@ -38,6 +39,47 @@ pub fn get_applied_status(
     get_applied_status_cached(ctx, perm, &diffs)
 }
 
+/// Return the ranges of uncommitted lines that are claimed by more than one of the applied stacks,
+/// for the user to decide which one they belong to.
+pub fn ownership_conflicts(ctx: &CommandContext) -> Result<Vec<OwnershipConflict>> {
+    let stacks = ctx
+        .project()
+        .virtual_branches()
+        .list_stacks_in_workspace()?;
+    let diffs = gitbutler_diff::workdir(ctx.repo(), get_workspace_head(ctx)?)?;
+    Ok(gitbutler_stack::ownership_conflicts(&stacks, &diffs))
+}
+
+/// Return the changes of the applied stack `stack_id` as they are shown to the user, which are
+/// the changes of its commits along with the uncommitted changes it owns, relative to the merge-base
+/// of the stack with the target and sorted by path.
+pub fn effective_diff(ctx: &CommandContext, stack_id: StackId) -> Result<Vec<FileDiff>> {
+    let status = get_applied_status(ctx, None)?;
+    let (stack, files) = status
+        .branches
+        .into_iter()
+        .find(|(stack, _)| stack.id == stack_id)
+        .ok_or_else(|| {
+            anyhow!("The branch {stack_id} isn't applied to the workspace")
+                .context(Code::Validation)
+        })?;
+    let files = files
+        .into_iter()
+        .map(|file| (file.path, file.hunks))
+        .collect::<Vec<(PathBuf, Vec<VirtualBranchHunk>)>>();
+    let tree_id = gitbutler_diff::write::hunks_onto_commit(ctx, stack.head(), files)?;
+
+    let repo = ctx.repo();
+    let default_target = ctx.project().virtual_branches().get_default_target()?;
+    let merge_base = repo.find_commit(repo.merge_base(stack.head(), default_target.sha)?)?;
+    let mut diffs: Vec<_> =
+        gitbutler_diff::trees(repo, &merge_base.tree()?, &repo.find_tree(tree_id)?, true)?
+            .into_values()
+            .collect();
+    diffs.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(diffs)
+}
+
 /// Returns branches and their associated file changes, in addition to a list
 /// of skipped files.
 /// `worktree_changes` are all changed files against the current `HEAD^{tree}` and index
@@ -231,7 +273,12 @@ pub fn get_applied_status_cached(
     let files_by_branch: Vec<(Stack, Vec<VirtualBranchFile>)> = hunks_by_branch
         .iter()
         .map(|(branch, hunks)| {
-            let files = virtual_hunks_into_virtual_files(ctx, hunks.clone());
+            let mut files = virtual_hunks_into_virtual_files(ctx, hunks.clone());
+            for file in &mut files {
+                file.mode_change = worktree_changes
+                    .get(&file.path)
+                    .and_then(|diff| diff.mode_change);
+            }
             (branch.clone(), files)
         })
         .collect();
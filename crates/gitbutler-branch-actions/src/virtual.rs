@@ -868,7 +868,11 @@ pub(crate) fn push(
     vb_state
         .set_stack(stack.clone())
         .context("failed to write target branch after push")?;
-    ctx.fetch(remote_branch.remote(), askpass.map(|_| "modal".to_string()))?;
+    ctx.fetch(
+        remote_branch.remote(),
+        vec![],
+        askpass.map(|_| "modal".to_string()),
+    )?;
 
     Ok(PushResult {
         remote: upstream_remote,
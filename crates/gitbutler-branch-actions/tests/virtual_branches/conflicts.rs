@@ -0,0 +1,191 @@
+use gitbutler_branch_actions::conflicts;
+use gitbutler_error::error::{AnyhowContextExt as _, Code};
+
+use super::*;
+
+fn index_entry(repo: &git2::Repository, path: &str, content: &str) -> git2::IndexEntry {
+    git2::IndexEntry {
+        ctime: git2::IndexTime::new(0, 0),
+        mtime: git2::IndexTime::new(0, 0),
+        dev: 0,
+        ino: 0,
+        mode: git2::FileMode::Blob.into(),
+        uid: 0,
+        gid: 0,
+        file_size: content.len() as u32,
+        id: repo.blob(content.as_bytes()).unwrap(),
+        flags: 0,
+        flags_extended: 0,
+        path: path.into(),
+    }
+}
+
+fn mark_conflicted(repo: &git2::Repository, path: &str, versions: [&str; 3]) {
+    let [base, ours, theirs] = versions.map(|content| index_entry(repo, path, content));
+    let mut index = repo.index().unwrap();
+    index
+        .conflict_add(Some(&base), Some(&ours), Some(&theirs))
+        .unwrap();
+    index.write().unwrap();
+}
+
+#[test]
+fn extract_and_ingest_round_trip() -> anyhow::Result<()> {
+    let Test {
+        repository, ctx, ..
+    } = &Test::default();
+    let repo = ctx.repo();
+    mark_conflicted(repo, "file.txt", ["base\n", "ours\n", "theirs\n"]);
+    fs::write(repository.path().join("file.txt"), "conflict markers\n")?;
+
+    let versions_dir = tempfile::tempdir()?;
+    let files =
+        conflicts::extract_versions(repo, path::Path::new("file.txt"), versions_dir.path())?;
+    let base = files.base.clone().expect("the conflict has a base");
+    assert_eq!(base, versions_dir.path().join("file_BASE.txt"));
+    assert_eq!(fs::read_to_string(&base)?, "base\n");
+    assert_eq!(fs::read_to_string(files.local.as_ref().unwrap())?, "ours\n");
+    assert_eq!(
+        fs::read_to_string(files.remote.as_ref().unwrap())?,
+        "theirs\n"
+    );
+    assert_eq!(files.worktree, repository.path().join("file.txt"));
+
+    let resolved = versions_dir.path().join("resolved.txt");
+    fs::write(&resolved, "ours and theirs\n")?;
+    conflicts::ingest(ctx, path::Path::new("file.txt"), &resolved)?;
+    assert_eq!(
+        fs::read_to_string(repository.path().join("file.txt"))?,
+        "ours and theirs\n"
+    );
+    assert!(
+        !repo.index()?.has_conflicts(),
+        "the conflict is resolved in the index"
+    );
+
+    drop(files);
+    assert!(
+        !base.exists(),
+        "versions are removed once they aren't needed"
+    );
+    Ok(())
+}
+
+#[test]
+fn unconflicted_file_is_refused() {
+    let Test { ctx, .. } = &Test::default();
+    let versions_dir = tempfile::tempdir().unwrap();
+    let err =
+        conflicts::extract_versions(ctx.repo(), path::Path::new("file.txt"), versions_dir.path())
+            .unwrap_err();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::Validation)
+    );
+    assert_eq!(err.root_cause().to_string(), "'file.txt' isn't conflicted");
+}
+
+#[test]
+fn base_content_of_modify_modify_conflict() -> anyhow::Result<()> {
+    let Test { ctx, .. } = &Test::default();
+    let repo = ctx.repo();
+    mark_conflicted(repo, "file.txt", ["base\n", "ours\n", "theirs\n"]);
+
+    assert_eq!(
+        conflicts::base_content(repo, path::Path::new("file.txt"))?.as_deref(),
+        Some(b"base\n".as_slice())
+    );
+    Ok(())
+}
+
+#[test]
+fn add_add_conflict_has_no_base_content() -> anyhow::Result<()> {
+    let Test { ctx, .. } = &Test::default();
+    let repo = ctx.repo();
+    let mut index = repo.index()?;
+    index.conflict_add(
+        None,
+        Some(&index_entry(repo, "file.txt", "ours\n")),
+        Some(&index_entry(repo, "file.txt", "theirs\n")),
+    )?;
+    index.write()?;
+
+    assert_eq!(
+        conflicts::base_content(repo, path::Path::new("file.txt"))?,
+        None
+    );
+    Ok(())
+}
+
+#[test]
+fn auto_resolve_leaves_genuine_conflicts() -> anyhow::Result<()> {
+    let Test {
+        repository, ctx, ..
+    } = &Test::default();
+    let repo = ctx.repo();
+    mark_conflicted(
+        repo,
+        "whitespace.txt",
+        ["call(a, b)\n", "call(a,  b)\n", "call(a,\tb)\n"],
+    );
+    mark_conflicted(repo, "genuine.txt", ["base\n", "ours\n", "theirs\n"]);
+    fs::write(
+        repository.path().join("whitespace.txt"),
+        "conflict markers\n",
+    )?;
+    fs::write(repository.path().join("genuine.txt"), "conflict markers\n")?;
+
+    assert_eq!(
+        conflicts::auto_resolve(ctx, Default::default())?,
+        Vec::<path::PathBuf>::new(),
+        "whitespace isn't ignored by default"
+    );
+
+    let resolved = conflicts::auto_resolve(
+        ctx,
+        conflicts::AutoResolveOptions {
+            whitespace: true,
+            union: false,
+        },
+    )?;
+    assert_eq!(resolved, [path::PathBuf::from("whitespace.txt")]);
+    let content = fs::read_to_string(repository.path().join("whitespace.txt"))?;
+    assert!(content.starts_with("call(a,"), "{content:?}");
+    assert_eq!(
+        fs::read_to_string(repository.path().join("genuine.txt"))?,
+        "conflict markers\n",
+        "the genuine conflict is untouched"
+    );
+
+    let conflicted: Vec<_> = repo
+        .index()?
+        .conflicts()?
+        .map(|conflict| conflict.unwrap().our.unwrap().path)
+        .collect();
+    assert_eq!(conflicted, [b"genuine.txt".to_vec()]);
+    Ok(())
+}
+
+#[test]
+fn auto_resolve_with_union_keeps_both_sides() -> anyhow::Result<()> {
+    let Test {
+        repository, ctx, ..
+    } = &Test::default();
+    let repo = ctx.repo();
+    mark_conflicted(repo, "file.txt", ["base\n", "ours\n", "theirs\n"]);
+
+    let resolved = conflicts::auto_resolve(
+        ctx,
+        conflicts::AutoResolveOptions {
+            whitespace: false,
+            union: true,
+        },
+    )?;
+    assert_eq!(resolved, [path::PathBuf::from("file.txt")]);
+    assert_eq!(
+        fs::read_to_string(repository.path().join("file.txt"))?,
+        "ours\ntheirs\n"
+    );
+    assert!(!repo.index()?.has_conflicts());
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use gitbutler_branch::BranchCreateRequest;
+
+use super::*;
+
+#[test]
+fn commits_and_owned_uncommitted_changes() {
+    let Test {
+        repository, ctx, ..
+    } = &Test::default();
+
+    gitbutler_branch_actions::set_base_branch(ctx, &"refs/remotes/origin/master".parse().unwrap())
+        .unwrap();
+
+    let stack_entry =
+        gitbutler_branch_actions::create_virtual_branch(ctx, &BranchCreateRequest::default())
+            .unwrap();
+    std::fs::write(repository.path().join("committed.txt"), "committed\n").unwrap();
+    gitbutler_branch_actions::create_commit(ctx, stack_entry.id, "commit", None).unwrap();
+    std::fs::write(repository.path().join("owned.txt"), "owned\n").unwrap();
+    // Assign the change to the first branch before the other one gets selected for changes.
+    gitbutler_branch_actions::list_virtual_branches(ctx).unwrap();
+
+    let other_stack_entry = gitbutler_branch_actions::create_virtual_branch(
+        ctx,
+        &BranchCreateRequest {
+            selected_for_changes: Some(true),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    std::fs::write(repository.path().join("other.txt"), "other\n").unwrap();
+
+    let diffs = gitbutler_branch_actions::effective_diff(ctx, stack_entry.id).unwrap();
+    let paths: Vec<_> = diffs.iter().map(|diff| diff.path.as_path()).collect();
+    assert_eq!(
+        paths,
+        [
+            path::Path::new("committed.txt"),
+            path::Path::new("owned.txt")
+        ]
+    );
+    assert!(diffs[1].hunks[0].diff_lines.ends_with(b"+owned\n"));
+
+    let diffs = gitbutler_branch_actions::effective_diff(ctx, other_stack_entry.id).unwrap();
+    let paths: Vec<_> = diffs.iter().map(|diff| diff.path.as_path()).collect();
+    assert_eq!(paths, [path::Path::new("other.txt")]);
+}
@@ -60,8 +60,10 @@ impl Test {
 
 mod amend;
 mod apply_virtual_branch;
+mod conflicts;
 mod create_commit;
 mod create_virtual_branch_from_branch;
+mod effective_diff;
 mod init;
 mod insert_blank_commit;
 mod list;
@@ -247,6 +247,71 @@ fn basic_oplog() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn diff_snapshots_around_commits() -> anyhow::Result<()> {
+    let Test {
+        repository,
+        project,
+        ctx,
+        ..
+    } = &Test::default();
+
+    gitbutler_branch_actions::set_base_branch(ctx, &"refs/remotes/origin/master".parse()?)?;
+    let stack_entry =
+        gitbutler_branch_actions::create_virtual_branch(ctx, &BranchCreateRequest::default())?;
+
+    fs::write(repository.path().join("file.txt"), "content\n")?;
+    gitbutler_branch_actions::create_commit(ctx, stack_entry.id, "commit one", None)?;
+
+    fs::write(repository.path().join("file.txt"), "changed\n")?;
+    fs::write(repository.path().join("file2.txt"), "content2\n")?;
+    gitbutler_branch_actions::create_commit(ctx, stack_entry.id, "commit two", None)?;
+
+    let snapshots = project.list_snapshots(10, None)?;
+    let ops = snapshots
+        .iter()
+        .map(|c| &c.details.as_ref().unwrap().title)
+        .collect::<Vec<_>>();
+    assert_eq!(ops, vec!["CreateCommit", "CreateCommit", "CreateBranch"]);
+    let [second_commit, first_commit, create_branch] =
+        [0, 1, 2].map(|idx| snapshots[idx].commit_id);
+
+    let diff = project.diff_snapshots(first_commit, second_commit)?;
+    assert_eq!(
+        diff.iter()
+            .map(|file| (file.path.as_path(), file.hunks[0].diff_lines.to_string()))
+            .collect::<Vec<_>>(),
+        [
+            (
+                Path::new("file.txt"),
+                "@@ -1 +1 @@\n-content\n+changed\n".to_owned()
+            ),
+            (
+                Path::new("file2.txt"),
+                "@@ -0,0 +1 @@\n+content2\n".to_owned()
+            )
+        ],
+        "sorted by path"
+    );
+
+    let diff = project.diff_snapshots(create_branch, second_commit)?;
+    assert_eq!(
+        diff.iter()
+            .map(|file| file.path.as_path())
+            .collect::<Vec<_>>(),
+        [Path::new("file.txt"), Path::new("file2.txt")],
+        "multiple operations can be reviewed at once"
+    );
+
+    assert!(
+        project
+            .diff_snapshots(second_commit, second_commit)?
+            .is_empty(),
+        "a snapshot is the same as itself"
+    );
+    Ok(())
+}
+
 #[test]
 fn restores_gitbutler_workspace() -> anyhow::Result<()> {
     let Test {
@@ -31,6 +31,7 @@ mod error {
 
 mod go_back_to_workspace {
     use gitbutler_branch::BranchCreateRequest;
+    use gitbutler_error::error::{AnyhowContextExt as _, Code};
     use pretty_assertions::assert_eq;
 
     use super::*;
@@ -106,15 +107,16 @@ mod go_back_to_workspace {
         repository.checkout_commit(oid_one);
         std::fs::write(repository.path().join("file.txt"), "tree").unwrap();
 
-        assert!(matches!(
-            gitbutler_branch_actions::set_base_branch(
-                ctx,
-                &"refs/remotes/origin/master".parse().unwrap()
-            )
-            .unwrap_err()
-            .downcast_ref(),
-            Some(Marker::ProjectConflict)
-        ));
+        let err = gitbutler_branch_actions::set_base_branch(
+            ctx,
+            &"refs/remotes/origin/master".parse().unwrap(),
+        )
+        .unwrap_err();
+        assert!(matches!(err.downcast_ref(), Some(Marker::ProjectConflict)));
+        assert_eq!(
+            err.custom_context().map(|ctx| ctx.code),
+            Some(Code::ProjectDirtyWorktree)
+        );
     }
 
     #[test]
@@ -0,0 +1,238 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use bstr::{BString, ByteVec};
+use gitbutler_error::error::Code;
+
+use crate::{reverse_hunk, GitHunk};
+
+/// Where [`apply_patch()`] applies a patch.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ApplyTarget {
+    /// Apply the patch to the files in the worktree.
+    pub worktree: bool,
+    /// Apply the patch to the index.
+    pub index: bool,
+}
+
+/// The result of [`apply_patch()`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ApplyReport {
+    /// The paths the patch was applied to, in the order they appear in the patch.
+    pub applied: Vec<PathBuf>,
+    /// The paths the patch doesn't apply to cleanly, which were left untouched.
+    pub conflicted: Vec<PathBuf>,
+}
+
+/// Apply `patch`, a unified diff like the one produced by `git diff`, to `target` in `repo`,
+/// or undo it if `reverse` is `true`.
+///
+/// Each file in the patch is applied on its own, so files that don't apply cleanly are reported
+/// as conflicted while all others are applied.
+/// Fails with [`Code::Validation`] if `patch` can't be parsed or contains no changes, or if `target`
+/// is empty.
+pub fn apply_patch(
+    repo: &git2::Repository,
+    patch: &str,
+    target: ApplyTarget,
+    reverse: bool,
+) -> Result<ApplyReport> {
+    let location = match (target.worktree, target.index) {
+        (true, true) => git2::ApplyLocation::Both,
+        (true, false) => git2::ApplyLocation::WorkDir,
+        (false, true) => git2::ApplyLocation::Index,
+        (false, false) => {
+            return Err(
+                anyhow!("The patch must be applied to the worktree, the index or both")
+                    .context(Code::Validation),
+            );
+        }
+    };
+    let mut diff = parse_patch(patch.as_bytes())?;
+    if reverse {
+        diff = parse_patch(&reversed_patch(&diff)?)?;
+    }
+
+    let mut report = ApplyReport::default();
+    for delta in diff.deltas() {
+        let path = delta_path(&delta)?.to_owned();
+        let applies = {
+            let mut opts = git2::ApplyOptions::new();
+            opts.check(true).delta_callback(|delta| {
+                delta.is_some_and(|delta| delta_path(&delta).is_ok_and(|p| p == path.as_path()))
+            });
+            // Any failure to apply a single file, be it changed content or a missing file, is a conflict.
+            repo.apply(&diff, location, Some(&mut opts)).is_ok()
+        };
+        if applies {
+            report.applied.push(path);
+        } else {
+            report.conflicted.push(path);
+        }
+    }
+
+    if !report.applied.is_empty() {
+        let mut opts = git2::ApplyOptions::new();
+        opts.delta_callback(|delta| {
+            delta.is_some_and(|delta| {
+                delta_path(&delta)
+                    .is_ok_and(|path| report.applied.iter().any(|p| p.as_path() == path))
+            })
+        });
+        repo.apply(&diff, location, Some(&mut opts))
+            .context("failed to apply patch")?;
+    }
+    Ok(report)
+}
+
+/// Return the content of `file`, a blob, after applying `hunks` to it, or after undoing them if `reverse`
+/// is `true`, without changing anything in `repo`.
+///
+/// To apply, `file` is the old version of the file the hunks belong to, and to undo, it's the new version,
+/// like the blob of a staged file whose hunks are about to be unstaged.
+/// Fails with [`Code::Validation`] if any of the hunks is binary or doesn't apply to `file`.
+pub fn preview_apply(
+    repo: &git2::Repository,
+    file: git2::Oid,
+    hunks: &[GitHunk],
+    reverse: bool,
+) -> Result<Vec<u8>> {
+    let blob = repo
+        .find_blob(file)
+        .with_context(|| format!("failed to find blob {file} to preview hunks on"))?;
+    let mut hunks = hunks
+        .iter()
+        .map(|hunk| {
+            if hunk.binary {
+                return Err(anyhow!("Binary hunks can't be previewed").context(Code::Validation));
+            }
+            if !reverse {
+                return Ok(hunk.clone());
+            }
+            reverse_hunk(hunk)
+                .ok_or_else(|| anyhow!("The hunk can't be reversed").context(Code::Validation))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if hunks.is_empty() {
+        return Ok(blob.content().to_vec());
+    }
+    hunks.sort_by_key(|hunk| hunk.old_start);
+
+    let mut all_diffs = BString::default();
+    for hunk in &hunks {
+        all_diffs.push_str(&hunk.diff_lines);
+    }
+    let doesnt_apply = || anyhow!("The hunks don't apply to the file").context(Code::Validation);
+    let patch = diffy::Patch::from_bytes(&all_diffs).map_err(|_| doesnt_apply())?;
+    let content = crate::write::apply(blob.content(), &patch).map_err(|_| doesnt_apply())?;
+    Ok(content.into())
+}
+
+fn parse_patch(patch: &[u8]) -> Result<git2::Diff<'static>> {
+    let diff = git2::Diff::from_buffer(patch).map_err(|err| {
+        anyhow!("The patch is malformed: {}", err.message()).context(Code::Validation)
+    })?;
+    if diff.deltas().len() == 0 {
+        return Err(anyhow!("The patch doesn't contain any changes").context(Code::Validation));
+    }
+    Ok(diff)
+}
+
+fn delta_path<'a>(delta: &git2::DiffDelta<'a>) -> Result<&'a Path> {
+    delta
+        .new_file()
+        .path()
+        .or_else(|| delta.old_file().path())
+        .context("patch entry without a path")
+}
+
+/// Write `diff` as a patch that undoes it, with old and new sides swapped.
+fn reversed_patch(diff: &git2::Diff<'_>) -> Result<BString> {
+    let mut out = BString::default();
+    for (idx, delta) in diff.deltas().enumerate() {
+        if delta.flags().is_binary() {
+            return Err(anyhow!("Binary patches can't be reversed").context(Code::Validation));
+        }
+        // The new side of the patch is the old side of its reversal, and vice versa.
+        let (old, new) = (delta.new_file(), delta.old_file());
+        let old_path = old
+            .path_bytes()
+            .unwrap_or_else(|| new.path_bytes().unwrap_or_default());
+        let new_path = new.path_bytes().unwrap_or(old_path);
+        out.push_str(b"diff --git a/");
+        out.push_str(old_path);
+        out.push_str(b" b/");
+        out.push_str(new_path);
+        out.push(b'\n');
+        let mode = |file: &git2::DiffFile<'_>| format!("{:o}", u32::from(file.mode()));
+        match delta.status() {
+            git2::Delta::Added => out.push_str(format!("deleted file mode {}\n", mode(&old))),
+            git2::Delta::Deleted => out.push_str(format!("new file mode {}\n", mode(&new))),
+            _ if old.mode() != new.mode() => out.push_str(format!(
+                "old mode {}\nnew mode {}\n",
+                mode(&old),
+                mode(&new)
+            )),
+            _ => {}
+        }
+
+        let file_header = |marker: &str, prefix: &str, path: &[u8], exists: bool| {
+            let mut line = BString::from(marker);
+            line.push(b' ');
+            if exists {
+                line.push_str(prefix);
+                line.push_str(path);
+            } else {
+                line.push_str("/dev/null");
+            }
+            line.push(b'\n');
+            line
+        };
+        out.push_str(file_header(
+            "---",
+            "a/",
+            old_path,
+            delta.status() != git2::Delta::Deleted,
+        ));
+        out.push_str(file_header(
+            "+++",
+            "b/",
+            new_path,
+            delta.status() != git2::Delta::Added,
+        ));
+
+        let Some(patch) = git2::Patch::from_diff(diff, idx)? else {
+            continue;
+        };
+        for hunk_idx in 0..patch.num_hunks() {
+            let (hunk, num_lines) = patch.hunk(hunk_idx)?;
+            out.push_str(format!(
+                "@@ -{},{} +{},{} @@\n",
+                hunk.new_start(),
+                hunk.new_lines(),
+                hunk.old_start(),
+                hunk.old_lines()
+            ));
+            for line_idx in 0..num_lines {
+                let line = patch.line_in_hunk(hunk_idx, line_idx)?;
+                let origin = match line.origin() {
+                    '+' => b'-',
+                    '-' => b'+',
+                    ' ' => b' ',
+                    // The line before doesn't end with a newline, which stays true after swapping sides.
+                    '=' | '>' | '<' => {
+                        out.push_str("\\ No newline at end of file\n");
+                        continue;
+                    }
+                    _ => continue,
+                };
+                out.push(origin);
+                out.push_str(line.content());
+                if !line.content().ends_with(b"\n") {
+                    out.push(b'\n');
+                }
+            }
+        }
+    }
+    Ok(out)
+}
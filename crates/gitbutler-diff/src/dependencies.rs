@@ -0,0 +1,48 @@
+use crate::FileDiff;
+
+/// Identifies a hunk by its index into [`FileDiff::hunks`].
+pub type HunkId = usize;
+
+/// Return, for each hunk in `file_diff`, the hunks that must also be staged for it to apply cleanly.
+///
+/// Hunks whose line ranges in the old version of the file overlap or touch share context, so applying
+/// one without the other will make that context mismatch. As this is transitive, each hunk depends on
+/// all hunks in the chain of touching hunks it is part of.
+/// The returned list is ordered like [`FileDiff::hunks`], and each hunks dependencies are sorted.
+pub fn analyze_dependencies(file_diff: &FileDiff) -> Vec<(HunkId, Vec<HunkId>)> {
+    let hunks = &file_diff.hunks;
+    let mut by_position: Vec<HunkId> = (0..hunks.len()).collect();
+    by_position.sort_by_key(|&id| (hunks[id].old_start, hunks[id].old_lines));
+
+    let mut group_of = vec![0; hunks.len()];
+    let mut groups: Vec<Vec<HunkId>> = Vec::new();
+    let mut group_end = None;
+    for id in by_position {
+        let hunk = &hunks[id];
+        let end = hunk.old_start + hunk.old_lines;
+        match group_end {
+            Some(previous_end) if !hunk.binary && hunk.old_start <= previous_end => {
+                groups.last_mut().expect("group is started").push(id);
+                group_end = Some(end.max(previous_end));
+            }
+            _ => {
+                groups.push(vec![id]);
+                // Binary hunks replace everything, so none of the other hunks could touch them.
+                group_end = (!hunk.binary).then_some(end);
+            }
+        }
+        group_of[id] = groups.len() - 1;
+    }
+
+    (0..hunks.len())
+        .map(|id| {
+            let mut dependencies: Vec<_> = groups[group_of[id]]
+                .iter()
+                .copied()
+                .filter(|&other| other != id)
+                .collect();
+            dependencies.sort_unstable();
+            (id, dependencies)
+        })
+        .collect()
+}
@@ -1,14 +1,27 @@
-use std::{borrow::Cow, collections::HashMap, path::PathBuf, str};
-
-use anyhow::{Context, Result};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    rc::Rc,
+    str,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use anyhow::{anyhow, Context, Result};
 use bstr::{BStr, BString, ByteSlice, ByteVec};
 use git2::DiffHunk;
 use gitbutler_cherry_pick::RepositoryExt;
 use gitbutler_command_context::RepositoryExtLite;
+use gitbutler_error::error::Code;
 use gitbutler_serde::BStringForFrontend;
 use serde::{Deserialize, Serialize};
 use tracing::instrument;
 
+use crate::ImageMeta;
+
 pub type DiffByPathMap = HashMap<PathBuf, FileDiff>;
 
 /// The type of change
@@ -90,6 +103,27 @@ impl GitHunk {
 
 /// Access
 impl GitHunk {
+    /// Return an anchor for this hunk in the file at `path`, suitable for use as URL fragment to
+    /// link to it, like `hunk-0cc175b9c0f1-12-14`.
+    ///
+    /// It's derived only from `path` and the start of the hunk on both sides, so it remains the same
+    /// as long as the hunk is at the same position, even if the diff is recomputed.
+    pub fn anchor(&self, path: &Path) -> String {
+        // Always use `/` as separator so anchors are the same on all platforms.
+        let path = path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let path_hash = hex::encode(md5::compute(path.as_bytes()).0);
+        format!(
+            "hunk-{}-{}-{}",
+            &path_hash[..12],
+            self.old_start,
+            self.new_start
+        )
+    }
+
     pub(crate) fn contains(&self, line: u32) -> bool {
         self.new_start <= line && self.new_start + self.new_lines >= line
     }
@@ -125,6 +159,9 @@ impl PartialEq<DiffHunk<'_>> for &GitHunk {
 #[serde(rename_all = "camelCase")]
 pub struct FileDiff {
     pub path: PathBuf,
+    /// The path the file had before it was renamed to `path`, if rename tracking is enabled
+    /// with [`DiffOptions::renames`].
+    pub old_path: Option<PathBuf>,
     /// Hunks might be empty if nothing about the files content is known, which happens
     /// if the content is skipped due to it being a large file.
     pub hunks: Vec<GitHunk>,
@@ -134,24 +171,428 @@ pub struct FileDiff {
     pub binary: bool,
     pub old_size_bytes: u64,
     pub new_size_bytes: u64,
+    /// The `(old, new)` mode if only the executable bit of the file changed, which may also be
+    /// the only change.
+    /// Note that where there is no executable bit, `core.fileMode` is `false` and such changes aren't reported.
+    #[serde(serialize_with = "gitbutler_serde::as_octal_mode_change")]
+    pub mode_change: Option<(git2::FileMode, git2::FileMode)>,
+    /// How the change is best presented.
+    pub kind: DiffKind,
+    /// `true` if the last line of the old version of the file doesn't end with a newline,
+    /// and it's part of a hunk.
+    pub no_newline_at_eof_old: bool,
+    /// `true` if the last line of the new version of the file doesn't end with a newline,
+    /// and it's part of a hunk.
+    pub no_newline_at_eof_new: bool,
+    /// `true` if the file was renamed from [`old_path`](Self::old_path) without changing its content or mode.
+    pub rename_only: bool,
+    /// `true` if only the [mode](Self::mode_change) of the file changed, but not its content or path.
+    pub mode_only: bool,
+}
+
+/// Options to control how diffs are computed by [`workdir_with_options()`] and [`trees_with_options()`].
+#[derive(Debug, Default, Clone)]
+pub struct DiffOptions {
+    /// If `true`, a deletion and an addition of similar files are paired into a rename, which
+    /// is then a single [`FileDiff`] with [`FileDiff::old_path`] set, and hunks between the two versions.
+    pub renames: bool,
+    /// The algorithm to use for computing hunks.
+    pub algorithm: DiffAlgorithm,
+    /// If `true`, files whose only change is the executable bit are left out, which is useful if
+    /// modes were changed by accident, like by a checkout on a filesystem without executable bits.
+    /// Files with content changes are still reported along with their [`FileDiff::mode_change`].
+    pub ignore_mode_changes: bool,
+    /// If set, stop producing [`FileDiff`]s after this many files, in the order in which Git
+    /// diffs them, which is by path. Use [`workdir_truncated()`] or [`trees_truncated()`] to learn
+    /// if files were left out.
+    /// Note that files left out due to `ignore_mode_changes` still count towards the limit.
+    pub max_files: Option<usize>,
+    /// How files that were added or deleted as a whole are presented.
+    pub new_file_mode: NewFileMode,
+    /// If `true`, files that are [`rename_only`](FileDiff::rename_only) or [`mode_only`](FileDiff::mode_only)
+    /// have no hunks, so they can be presented as one-liners.
+    /// Otherwise, they have a single empty hunk like empty new files, which is how consumers that
+    /// only look at hunks learn about them.
+    pub skip_placeholder_hunks: bool,
+    /// If `true`, changes to symlinks are presented as [`DiffKind::Symlink`] with the paths they
+    /// point to, and a symlink that became a file or the other way around is reported as a single
+    /// type change instead of a deletion and an addition.
+    pub symlink_targets: bool,
+    /// If set, files for which the size of both versions combined in bytes exceeds this limit aren't
+    /// read, and are reported as [`DiffKind::TooLarge`] without hunks instead, which keeps
+    /// pathological files like multi-hundred megabyte logs from exhausting memory.
+    /// Note that renames from or to such files aren't detected.
+    pub max_diff_size: Option<u64>,
+    /// If set, the diff is abandoned once this becomes `true`, which is checked between files,
+    /// and fails with [`Code::Cancelled`]. This allows to stop diffing large change sets whose
+    /// result isn't needed anymore.
+    pub should_interrupt: Option<Arc<AtomicBool>>,
+}
+
+/// How added and deleted files are presented, see [`DiffOptions::new_file_mode`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NewFileMode {
+    /// Like any other change, as hunk in which each line is marked as added or removed.
+    #[default]
+    Patch,
+    /// Additionally provide the content without markers as [`DiffKind::Added`] or [`DiffKind::Deleted`],
+    /// which is easier to review. Hunks are left as they are, so the change can still be applied.
+    Compact,
+}
+
+/// The result of [`workdir_truncated()`] and [`trees_truncated()`], which may not contain all files
+/// if [`DiffOptions::max_files`] is set.
+#[derive(Debug, Default, Clone)]
+pub struct TruncatedDiff {
+    /// The diffs of all files up to the limit.
+    pub files: DiffByPathMap,
+    /// `true` if there are more files than fit into the limit.
+    pub truncated: bool,
+    /// The amount of changed files, including the ones that were left out.
+    pub total_files: usize,
+}
+
+/// The algorithm used to find the lines that changed between two versions of a file.
+///
+/// They all produce correct diffs, but differ in which of the possible hunks they pick.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DiffAlgorithm {
+    /// The default algorithm of Git, which is fast but can match unrelated lines like braces
+    /// and blank lines, producing confusing hunks when blocks of code are moved.
+    #[default]
+    Myers,
+    /// Like [`Myers`](Self::Myers), but spends extra time to produce the smallest possible diff,
+    /// which can be slow for large files.
+    Minimal,
+    /// Anchors the diff on lines that are unique in both versions, so reordered blocks and
+    /// moved functions result in fewer and more readable hunks, at a slight cost in speed.
+    Patience,
+}
+
+impl DiffOptions {
+    /// Configure `opts` to use our settings.
+    fn apply(&self, opts: &mut git2::DiffOptions) {
+        opts.minimal(self.algorithm == DiffAlgorithm::Minimal)
+            .patience(self.algorithm == DiffAlgorithm::Patience)
+            .include_typechange(self.symlink_targets);
+    }
+
+    /// Detect renames in `diff` if configured.
+    fn find_similar(&self, diff: &mut git2::Diff<'_>) -> Result<()> {
+        if self.renames {
+            diff.find_similar(Some(
+                git2::DiffFindOptions::new()
+                    .renames(true)
+                    .for_untracked(true),
+            ))?;
+        }
+        Ok(())
+    }
+
+    /// Create a diff with `create` using options set up by `configure`, limited to `paths` if set.
+    ///
+    /// Files that exceed [`max_diff_size`](Self::max_diff_size) are left out of the diff, and are
+    /// returned separately as [`DiffKind::TooLarge`]. To learn about their size without reading them,
+    /// the diff is created without content first, and once more for all other files if needed.
+    fn create_diff<'repo>(
+        &self,
+        repo: &'repo git2::Repository,
+        paths: Option<&[PathBuf]>,
+        configure: impl Fn(&mut git2::DiffOptions),
+        create: impl Fn(&mut git2::DiffOptions) -> Result<git2::Diff<'repo>, git2::Error>,
+    ) -> Result<(git2::Diff<'repo>, DiffByPathMap)> {
+        let diff_opts = |paths: &[&Path]| {
+            let mut diff_opts = git2::DiffOptions::new();
+            configure(&mut diff_opts);
+            if !paths.is_empty() {
+                diff_opts.disable_pathspec_match(true);
+                for path in paths {
+                    diff_opts.pathspec(path);
+                }
+            }
+            self.apply(&mut diff_opts);
+            diff_opts
+        };
+        let paths: Vec<_> = paths
+            .unwrap_or_default()
+            .iter()
+            .map(PathBuf::as_path)
+            .collect();
+        let diff = create(&mut diff_opts(&paths))?;
+        let Some(max_diff_size) = self.max_diff_size else {
+            return Ok((diff, DiffByPathMap::new()));
+        };
+
+        let mut too_large = DiffByPathMap::new();
+        let mut other_paths = Vec::new();
+        for delta in diff.deltas() {
+            let path = delta
+                .new_file()
+                .path()
+                .or_else(|| delta.old_file().path())
+                .context("failed to get file name from diff")?;
+            let old_size = file_size(repo, path, delta.old_file());
+            let new_size = file_size(repo, path, delta.new_file());
+            if old_size.saturating_add(new_size) <= max_diff_size {
+                other_paths.push(path);
+                continue;
+            }
+            too_large.insert(
+                path.to_owned(),
+                FileDiff {
+                    path: path.to_owned(),
+                    skipped: true,
+                    old_size_bytes: old_size,
+                    new_size_bytes: new_size,
+                    kind: DiffKind::TooLarge { old_size, new_size },
+                    ..Default::default()
+                },
+            );
+        }
+        if too_large.is_empty() {
+            return Ok((diff, too_large));
+        }
+        let diff = if other_paths.is_empty() {
+            // Without pathspecs, all files would be diffed.
+            repo.diff_tree_to_tree(None, None, None)?
+        } else {
+            create(&mut diff_opts(&other_paths))?
+        };
+        Ok((diff, too_large))
+    }
+
+    /// Turn `diff` into the files that are supposed to be reported, up to the limit, along with
+    /// the files that were `too_large` to be diffed.
+    /// `workdir_repo` is passed to [`hunks_by_filepath()`], and `repo` is used to read images.
+    /// If `patch_opts` is set, `diff` is between two trees, and the blobs it reads are cached.
+    fn collect(
+        &self,
+        workdir_repo: Option<&git2::Repository>,
+        repo: &git2::Repository,
+        diff: &git2::Diff<'_>,
+        too_large: DiffByPathMap,
+        patch_opts: Option<&mut git2::DiffOptions>,
+    ) -> Result<TruncatedDiff> {
+        let total_files = diff.deltas().len() + too_large.len();
+        let mut blobs = match patch_opts {
+            Some(opts) => Some((BlobCache::new(repo, diff)?, opts)),
+            None => None,
+        };
+        let mut files = hunks_by_filepath_limited(
+            workdir_repo,
+            diff,
+            self.max_files,
+            self.should_interrupt.as_deref(),
+            blobs.as_mut().map(|(blobs, opts)| (blobs, &mut **opts)),
+        )?;
+        files.extend(too_large);
+        if self.ignore_mode_changes {
+            files.retain(|_, file| {
+                file.mode_change.is_none()
+                    || file.hunks.iter().any(|hunk| !hunk.diff_lines.is_empty())
+            });
+        }
+        if self.skip_placeholder_hunks {
+            for file in files.values_mut() {
+                if file.rename_only || file.mode_only {
+                    file.hunks.clear();
+                }
+            }
+        }
+        detect_images(repo, diff, &mut files);
+        if self.symlink_targets {
+            detect_symlinks(repo, diff, &mut files);
+        }
+        if self.new_file_mode == NewFileMode::Compact {
+            for file in files.values_mut() {
+                if let Some(kind) = compact_kind(file) {
+                    file.kind = kind;
+                }
+            }
+        }
+        Ok(TruncatedDiff {
+            files,
+            truncated: self.max_files.is_some_and(|max| total_files > max),
+            total_files,
+        })
+    }
+}
+
+/// How the change to a file is best presented.
+#[derive(Debug, PartialEq, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum DiffKind {
+    /// The hunks of the file describe the change.
+    #[default]
+    Patch,
+    /// A binary file which is an image before or after the change, to be displayed side by side.
+    Image {
+        old: Option<ImageMeta>,
+        new: Option<ImageMeta>,
+    },
+    /// A text file that was added, with its `content` of `lines` lines, starting at line 1.
+    Added {
+        content: BStringForFrontend,
+        lines: u32,
+    },
+    /// A text file that was deleted, with the `content` of `lines` lines it had, starting at line 1.
+    Deleted {
+        content: BStringForFrontend,
+        lines: u32,
+    },
+    /// A symlink that was changed, with the paths it pointed to before and after.
+    /// A target is `None` if the file isn't a symlink on that side, so if only one of them is set,
+    /// it's either an addition or deletion, or a type change if the file exists on both sides.
+    Symlink {
+        old_target: Option<BStringForFrontend>,
+        new_target: Option<BStringForFrontend>,
+    },
+    /// A file that wasn't diffed as its versions, of `old_size` and `new_size` bytes, are too large
+    /// together, see [`DiffOptions::max_diff_size`].
+    TooLarge { old_size: u64, new_size: u64 },
+}
+
+/// Return the size in bytes of the version `file` of `path` without reading it, or `0` if it doesn't exist.
+fn file_size(repo: &git2::Repository, path: &Path, file: git2::DiffFile<'_>) -> u64 {
+    if !file.exists() {
+        return 0;
+    }
+    if file.size() != 0 {
+        return file.size();
+    }
+    if file.id().is_zero() {
+        // The file is in the worktree and wasn't hashed yet.
+        return repo
+            .workdir()
+            .and_then(|workdir| workdir.join(path).metadata().ok())
+            .map_or(0, |metadata| metadata.len());
+    }
+    repo.odb()
+        .and_then(|odb| odb.read_header(file.id()))
+        .map_or(0, |(size, _kind)| size as u64)
+}
+
+/// Return the compact presentation of `file` if it's a text file that was added or deleted as a whole.
+fn compact_kind(file: &FileDiff) -> Option<DiffKind> {
+    let [hunk] = file.hunks.as_slice() else {
+        return None;
+    };
+    if file.binary || hunk.binary || file.kind != DiffKind::Patch {
+        return None;
+    }
+    let (added, no_newline_at_eof) = match hunk.change_type {
+        ChangeType::Added | ChangeType::Untracked if hunk.old_lines == 0 => {
+            (true, file.no_newline_at_eof_new)
+        }
+        ChangeType::Deleted if hunk.new_lines == 0 => (false, file.no_newline_at_eof_old),
+        _ => return None,
+    };
+    let mut content = BString::default();
+    let mut lines = 0;
+    for line in hunk.diff_lines.lines_with_terminator() {
+        // Skip the hunk header and the marker for the missing newline.
+        if let Some(line) = line.strip_prefix(if added { b"+" } else { b"-" }) {
+            content.push_str(line);
+            lines += 1;
+        }
+    }
+    if lines == 0 {
+        return None;
+    }
+    if no_newline_at_eof && content.ends_with(b"\n") {
+        content.pop();
+    }
+    Some(if added {
+        DiffKind::Added {
+            content: content.into(),
+            lines,
+        }
+    } else {
+        DiffKind::Deleted {
+            content: content.into(),
+            lines,
+        }
+    })
+}
+
+/// Return the `(old, new)` mode of the file in `delta` if its executable bit was flipped.
+fn mode_change(delta: &git2::DiffDelta<'_>) -> Option<(git2::FileMode, git2::FileMode)> {
+    use git2::FileMode as M;
+    let (old, new) = (delta.old_file().mode(), delta.new_file().mode());
+    matches!(
+        (old, new),
+        (M::Blob, M::BlobExecutable) | (M::BlobExecutable, M::Blob)
+    )
+    .then_some((old, new))
 }
 
-#[instrument(level = tracing::Level::DEBUG, skip(repo))]
 pub fn workdir(repo: &git2::Repository, commit_oid: git2::Oid) -> Result<DiffByPathMap> {
+    workdir_with_options(repo, commit_oid, &DiffOptions::default())
+}
+
+/// Like [`workdir()`], but with `options` to control how the diff is computed.
+#[instrument(level = tracing::Level::DEBUG, skip(repo))]
+pub fn workdir_with_options(
+    repo: &git2::Repository,
+    commit_oid: git2::Oid,
+    options: &DiffOptions,
+) -> Result<DiffByPathMap> {
+    Ok(workdir_truncated(repo, commit_oid, options)?.files)
+}
+
+/// Like [`workdir_with_options()`], but also tell if files were left out due to [`DiffOptions::max_files`].
+#[instrument(level = tracing::Level::DEBUG, skip(repo))]
+pub fn workdir_truncated(
+    repo: &git2::Repository,
+    commit_oid: git2::Oid,
+    options: &DiffOptions,
+) -> Result<TruncatedDiff> {
+    workdir_limited_to(repo, commit_oid, options, None)
+}
+
+/// Update `previous`, the result of [`workdir_with_options()`] for `commit_oid` and `options`,
+/// by diffing only `changed_paths` again, like those reported by a filesystem watcher.
+///
+/// Files in `previous` that aren't in `changed_paths` are kept as they are, while changed paths
+/// that don't differ from `commit_oid` anymore are removed.
+/// Note that renames are only detected if both of their paths are in `changed_paths`.
+#[instrument(level = tracing::Level::DEBUG, skip(repo, previous))]
+pub fn recompute_paths(
+    repo: &git2::Repository,
+    commit_oid: git2::Oid,
+    previous: &DiffByPathMap,
+    changed_paths: &[PathBuf],
+    options: &DiffOptions,
+) -> Result<DiffByPathMap> {
+    let mut files: DiffByPathMap = previous
+        .iter()
+        .filter(|(path, file)| {
+            !changed_paths.contains(path)
+                && file
+                    .old_path
+                    .as_ref()
+                    .is_none_or(|old_path| !changed_paths.contains(old_path))
+        })
+        .map(|(path, file)| (path.clone(), file.clone()))
+        .collect();
+    if !changed_paths.is_empty() {
+        files.extend(workdir_limited_to(repo, commit_oid, options, Some(changed_paths))?.files);
+    }
+    Ok(files)
+}
+
+/// Diff the worktree against `commit_oid`, but only look at `paths` if set.
+fn workdir_limited_to(
+    repo: &git2::Repository,
+    commit_oid: git2::Oid,
+    options: &DiffOptions,
+    paths: Option<&[PathBuf]>,
+) -> Result<TruncatedDiff> {
     let commit = repo
         .find_commit(commit_oid)
         .context("failed to find commit")?;
     let old_tree = repo.find_real_tree(&commit, Default::default())?;
 
-    let mut diff_opts = git2::DiffOptions::new();
-    diff_opts
-        .recurse_untracked_dirs(true)
-        .include_untracked(true)
-        .show_binary(true)
-        .show_untracked_content(true)
-        .ignore_submodules(true)
-        .context_lines(3);
-
     let mut index = repo.index()?;
     // Just a hack to resolve conflicts, which don't get diffed.
     // Diffed conflicts are something we need though.
@@ -171,8 +612,22 @@ pub fn workdir(repo: &git2::Repository, commit_oid: git2::Oid) -> Result<DiffByP
         index.add_path(conflict_path_to_resolve.as_ref()).ok();
     }
     repo.ignore_large_files_in_diffs(50_000_000)?;
-    let diff = repo.diff_tree_to_workdir_with_index(Some(&old_tree), Some(&mut diff_opts))?;
-    hunks_by_filepath(Some(repo), &diff)
+    let (mut diff, too_large) = options.create_diff(
+        repo,
+        paths,
+        |diff_opts| {
+            diff_opts
+                .recurse_untracked_dirs(true)
+                .include_untracked(true)
+                .show_binary(true)
+                .show_untracked_content(true)
+                .ignore_submodules(true)
+                .context_lines(3);
+        },
+        |diff_opts| repo.diff_tree_to_workdir_with_index(Some(&old_tree), Some(diff_opts)),
+    )?;
+    options.find_similar(&mut diff)?;
+    options.collect(Some(repo), repo, &diff, too_large, None)
 }
 
 pub fn trees(
@@ -181,18 +636,416 @@ pub fn trees(
     new_tree: &git2::Tree,
     include_context: bool,
 ) -> Result<DiffByPathMap> {
-    let mut diff_opts = git2::DiffOptions::new();
+    trees_with_options(
+        repo,
+        old_tree,
+        new_tree,
+        include_context,
+        &DiffOptions::default(),
+    )
+}
+
+/// Like [`trees()`], but with `options` to control how the diff is computed.
+pub fn trees_with_options(
+    repo: &git2::Repository,
+    old_tree: &git2::Tree,
+    new_tree: &git2::Tree,
+    include_context: bool,
+    options: &DiffOptions,
+) -> Result<DiffByPathMap> {
+    Ok(trees_truncated(repo, old_tree, new_tree, include_context, options)?.files)
+}
+
+/// Like [`trees_with_options()`], but also tell if files were left out due to [`DiffOptions::max_files`].
+pub fn trees_truncated(
+    repo: &git2::Repository,
+    old_tree: &git2::Tree,
+    new_tree: &git2::Tree,
+    include_context: bool,
+    options: &DiffOptions,
+) -> Result<TruncatedDiff> {
     let context_lines = match include_context {
         true => 3,
         false => 0,
     };
-    diff_opts
-        .show_binary(true)
-        .ignore_submodules(true)
-        .context_lines(context_lines);
+    let configure = |diff_opts: &mut git2::DiffOptions| {
+        diff_opts
+            .show_binary(true)
+            .ignore_submodules(true)
+            .context_lines(context_lines);
+    };
+    let (mut diff, too_large) = options.create_diff(repo, None, configure, |diff_opts| {
+        repo.diff_tree_to_tree(Some(old_tree), Some(new_tree), Some(diff_opts))
+    })?;
+    options.find_similar(&mut diff)?;
+    // Patches of single files are produced with the same options as the diff itself.
+    let mut patch_opts = git2::DiffOptions::new();
+    configure(&mut patch_opts);
+    options.apply(&mut patch_opts);
+    options.collect(None, repo, &diff, too_large, Some(&mut patch_opts))
+}
 
+/// The amount of lines that changed in a file, as shown next to it in lists of changes.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileStat {
+    pub path: PathBuf,
+    /// The amount of added lines, which is `0` for binary files.
+    pub insertions: usize,
+    /// The amount of removed lines, which is `0` for binary files.
+    pub deletions: usize,
+    /// `true` if the file has undiffable content, so its lines can't be counted.
+    pub binary: bool,
+}
+
+/// Count the lines that changed in each file between `old_tree` and `new_tree`, without
+/// collecting the hunks, which is much cheaper than [`trees()`] if only the numbers are needed.
+///
+/// Files are returned in the order Git diffs them, which is by path.
+pub fn per_file_stats(
+    repo: &git2::Repository,
+    old_tree: &git2::Tree,
+    new_tree: &git2::Tree,
+) -> Result<Vec<FileStat>> {
+    let mut diff_opts = git2::DiffOptions::new();
+    diff_opts.ignore_submodules(true).context_lines(0);
     let diff = repo.diff_tree_to_tree(Some(old_tree), Some(new_tree), Some(&mut diff_opts))?;
-    hunks_by_filepath(None, &diff)
+
+    let mut stats = Vec::with_capacity(diff.deltas().len());
+    for (idx, delta) in diff.deltas().enumerate() {
+        let path = delta
+            .new_file()
+            .path()
+            .or_else(|| delta.old_file().path())
+            .context("diff entry without a path")?
+            .to_owned();
+        // Loading the patch also determines if the file is binary.
+        let patch = git2::Patch::from_diff(&diff, idx)?;
+        let binary = patch.as_ref().map_or(delta.flags().is_binary(), |patch| {
+            patch.delta().flags().is_binary()
+        });
+        let (insertions, deletions) = match patch {
+            Some(patch) if !binary => {
+                let (_context, insertions, deletions) = patch.line_stats()?;
+                (insertions, deletions)
+            }
+            _ => (0, 0),
+        };
+        stats.push(FileStat {
+            path,
+            insertions,
+            deletions,
+            binary,
+        });
+    }
+    Ok(stats)
+}
+
+/// The changes below a directory, as returned by [`directory_summary()`].
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirSummary {
+    /// The worktree-relative path of the directory.
+    pub path: PathBuf,
+    /// The amount of changed files anywhere below this directory.
+    pub files_changed: usize,
+    /// The amount of added lines anywhere below this directory, which is `0` for binary files.
+    pub insertions: usize,
+    /// The amount of removed lines anywhere below this directory, which is `0` for binary files.
+    pub deletions: usize,
+    /// The directories directly inside of this one that contain changes, sorted by path.
+    pub children: Vec<DirSummary>,
+}
+
+/// Roll up the changes in `file_diffs` by directory, for display as a tree of directories
+/// that can be collapsed.
+///
+/// The returned directories are those at the top-level of the worktree, sorted by path.
+/// Files at the top-level aren't part of any directory, and thus aren't counted.
+pub fn directory_summary(file_diffs: &DiffByPathMap) -> Vec<DirSummary> {
+    let mut root = DirSummary {
+        path: PathBuf::new(),
+        files_changed: 0,
+        insertions: 0,
+        deletions: 0,
+        children: Vec::new(),
+    };
+    let mut files: Vec<_> = file_diffs.iter().collect();
+    files.sort_by(|a, b| a.0.cmp(b.0));
+    for (path, file) in files {
+        let count = |prefix: u8| {
+            file.hunks
+                .iter()
+                .filter(|_| !file.binary)
+                .flat_map(|hunk| hunk.diff_lines.lines())
+                .filter(|line| line.first() == Some(&prefix))
+                .count()
+        };
+        let (insertions, deletions) = (count(b'+'), count(b'-'));
+
+        let mut dir = &mut root;
+        for component in path.parent().into_iter().flat_map(Path::components) {
+            let dir_path = dir.path.join(component);
+            let idx = match dir.children.iter().position(|child| child.path == dir_path) {
+                Some(idx) => idx,
+                None => {
+                    dir.children.push(DirSummary {
+                        path: dir_path,
+                        files_changed: 0,
+                        insertions: 0,
+                        deletions: 0,
+                        children: Vec::new(),
+                    });
+                    dir.children.len() - 1
+                }
+            };
+            dir = &mut dir.children[idx];
+            dir.files_changed += 1;
+            dir.insertions += insertions;
+            dir.deletions += deletions;
+        }
+    }
+    root.children
+}
+
+/// Diff the tree of the commit `commit_id` against the merge-base of all of its parents, which
+/// for merge commits shows the net effect of the merge instead of only what it brought into one parent.
+///
+/// For commits with a single parent, this is a diff against that parent, and root commits are
+/// diffed against the empty tree.
+pub fn against_merge_base(repo: &git2::Repository, commit_id: git2::Oid) -> Result<DiffByPathMap> {
+    let commit = repo
+        .find_commit(commit_id)
+        .context("failed to find commit")?;
+    let parent_ids: Vec<_> = commit.parent_ids().collect();
+    let base_tree = match parent_ids.split_first() {
+        None => repo.find_tree(repo.treebuilder(None)?.write()?)?,
+        Some((first_parent, other_parents)) => {
+            let base_id = other_parents
+                .iter()
+                .try_fold(*first_parent, |base, parent| {
+                    repo.merge_base(base, *parent)
+                        .context("Failed to find merge base")
+                })?;
+            repo.find_real_tree(&repo.find_commit(base_id)?, Default::default())?
+        }
+    };
+    let tree = repo.find_real_tree(&commit, Default::default())?;
+    trees(repo, &base_tree, &tree, true)
+}
+
+/// Diff the commits `a` and `b` like `git diff a...b` if `three_dot` is `true`, i.e. `b` against the
+/// merge base of `a` and `b`, for the changes made on `b` since it forked off `a`.
+/// Otherwise, diff them like `git diff a..b`, i.e. `b` against `a`, which also shows everything that
+/// changed on `a` in the meantime as reverted.
+///
+/// Files are sorted by path. Three-dot diffs fail with [`Code::Validation`] if `a` and `b` don't
+/// share any history.
+pub fn range_diff(
+    repo: &git2::Repository,
+    a: git2::Oid,
+    b: git2::Oid,
+    three_dot: bool,
+) -> Result<Vec<FileDiff>> {
+    let old_id = if three_dot {
+        repo.merge_base(a, b).map_err(|err| match err.code() {
+            git2::ErrorCode::NotFound => {
+                anyhow!("{a} and {b} don't have a merge base").context(Code::Validation)
+            }
+            _ => err.into(),
+        })?
+    } else {
+        a
+    };
+    let old_tree = repo.find_real_tree(&repo.find_commit(old_id)?, Default::default())?;
+    let new_tree = repo.find_real_tree(&repo.find_commit(b)?, Default::default())?;
+    let mut files: Vec<_> = trees(repo, &old_tree, &new_tree, true)?
+        .into_values()
+        .collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// Set [`FileDiff::kind`] for all binary `files` in `diff` that are images, reading their content from `repo`.
+fn detect_images(repo: &git2::Repository, diff: &git2::Diff<'_>, files: &mut DiffByPathMap) {
+    let image_meta = |file: git2::DiffFile<'_>| -> Option<ImageMeta> {
+        if !file.exists() {
+            return None;
+        }
+        if file.id().is_zero() {
+            // Worktree files are only hashed if needed.
+            let data = std::fs::read(repo.workdir()?.join(file.path()?)).ok()?;
+            return ImageMeta::from_bytes(&data);
+        }
+        ImageMeta::from_bytes(repo.find_blob(file.id()).ok()?.content())
+    };
+    for delta in diff.deltas() {
+        let Some(file) = delta
+            .new_file()
+            .path()
+            .or_else(|| delta.old_file().path())
+            .and_then(|path| files.get_mut(path))
+            .filter(|file| file.binary)
+        else {
+            continue;
+        };
+        let (old, new) = (image_meta(delta.old_file()), image_meta(delta.new_file()));
+        if old.is_some() || new.is_some() {
+            file.kind = DiffKind::Image { old, new };
+        }
+    }
+}
+
+/// Set [`FileDiff::kind`] for all `files` in `diff` that are symlinks before or after the change,
+/// reading their targets from `repo`.
+fn detect_symlinks(repo: &git2::Repository, diff: &git2::Diff<'_>, files: &mut DiffByPathMap) {
+    let target = |file: git2::DiffFile<'_>| -> Option<BStringForFrontend> {
+        if !file.exists() || file.mode() != git2::FileMode::Link {
+            return None;
+        }
+        let target = if file.id().is_zero() {
+            // Worktree files are only hashed if needed.
+            let target = std::fs::read_link(repo.workdir()?.join(file.path()?)).ok()?;
+            Vec::from_path_buf(target).ok()?
+        } else {
+            repo.find_blob(file.id()).ok()?.content().to_owned()
+        };
+        Some(BString::from(target).into())
+    };
+    for delta in diff.deltas() {
+        let Some(file) = delta
+            .new_file()
+            .path()
+            .or_else(|| delta.old_file().path())
+            .and_then(|path| files.get_mut(path))
+        else {
+            continue;
+        };
+        let (old_target, new_target) = (target(delta.old_file()), target(delta.new_file()));
+        if old_target.is_some() || new_target.is_some() {
+            file.kind = DiffKind::Symlink {
+                old_target,
+                new_target,
+            };
+        }
+    }
+}
+
+/// The content of blobs read while diffing two trees, so blobs that are used by more than one file,
+/// like copies, are only read and decompressed once per diff.
+///
+/// A blob is only kept until the last file using it is diffed.
+struct BlobCache<'repo> {
+    odb: git2::Odb<'repo>,
+    /// Whether the patch of each delta, by index, is produced from the cached blobs.
+    patchable: Vec<bool>,
+    /// The amount of files that are still going to read each blob.
+    uses: HashMap<git2::Oid, usize>,
+    blobs: HashMap<git2::Oid, Rc<[u8]>>,
+    /// The amount of blobs that were read from the object database.
+    reads: usize,
+}
+
+impl<'repo> BlobCache<'repo> {
+    fn new(repo: &'repo git2::Repository, diff: &git2::Diff<'_>) -> Result<Self> {
+        let mut patchable = Vec::with_capacity(diff.deltas().len());
+        let mut uses = HashMap::new();
+        for delta in diff.deltas() {
+            let can_patch = can_patch_from_blobs(repo, &delta);
+            if can_patch {
+                for file in [delta.old_file(), delta.new_file()] {
+                    *uses.entry(file.id()).or_default() += 1;
+                }
+            }
+            patchable.push(can_patch);
+        }
+        Ok(BlobCache {
+            odb: repo.odb()?,
+            patchable,
+            uses,
+            blobs: HashMap::new(),
+            reads: 0,
+        })
+    }
+
+    /// Return the content of the blob `id`, reading it from the object database unless it's cached.
+    fn get(&mut self, id: git2::Oid) -> Result<Rc<[u8]>, git2::Error> {
+        let uses = self.uses.get_mut(&id).map(|uses| {
+            *uses = uses.saturating_sub(1);
+            *uses
+        });
+        let data = match self.blobs.get(&id) {
+            Some(data) => Rc::clone(data),
+            None => {
+                self.reads += 1;
+                Rc::from(self.odb.read(id)?.data())
+            }
+        };
+        if uses.unwrap_or_default() > 0 {
+            self.blobs.insert(id, Rc::clone(&data));
+        } else {
+            self.blobs.remove(&id);
+        }
+        Ok(data)
+    }
+}
+
+/// Return `true` if the patch of `delta` can be produced from its blobs alone, which is the case
+/// for regular files whose content changed, and that have no diff driver or `-diff` in `.gitattributes`.
+fn can_patch_from_blobs(repo: &git2::Repository, delta: &git2::DiffDelta<'_>) -> bool {
+    use git2::FileMode as M;
+    let (old, new) = (delta.old_file(), delta.new_file());
+    let is_blob = |file: &git2::DiffFile<'_>| {
+        !file.id().is_zero() && matches!(file.mode(), M::Blob | M::BlobExecutable)
+    };
+    matches!(
+        delta.status(),
+        git2::Delta::Modified | git2::Delta::Renamed | git2::Delta::Copied
+    ) && is_blob(&old)
+        && is_blob(&new)
+        // Without a change in content, there is no patch and not even a header.
+        && old.id() != new.id()
+        && [old.path(), new.path()].into_iter().flatten().all(|path| {
+            repo.get_attr(path, "diff", git2::AttrCheckFlags::default())
+                .is_ok_and(|driver| driver.is_none())
+        })
+}
+
+/// Like [`git2::Diff::print()`], but produce the patch of each file in `diff` from the blobs in `blobs`
+/// with `opts` where possible, while `on_line` always receives the deltas of `diff`.
+fn print_with_cached_blobs(
+    diff: &git2::Diff<'_>,
+    blobs: &mut BlobCache<'_>,
+    opts: &mut git2::DiffOptions,
+    on_line: &mut dyn FnMut(git2::DiffDelta<'_>, Option<DiffHunk<'_>>, git2::DiffLine<'_>) -> bool,
+) -> Result<(), git2::Error> {
+    for (idx, delta) in diff.deltas().enumerate() {
+        let (old, new) = (delta.old_file(), delta.new_file());
+        let buffers = if blobs.patchable[idx] {
+            Some((blobs.get(old.id())?, blobs.get(new.id())?))
+        } else {
+            None
+        };
+        let patch = match &buffers {
+            Some((old_data, new_data)) => Some(git2::Patch::from_buffers(
+                old_data,
+                old.path(),
+                new_data,
+                new.path(),
+                Some(&mut *opts),
+            )?),
+            None => git2::Patch::from_diff(diff, idx)?,
+        };
+        let Some(mut patch) = patch else {
+            continue;
+        };
+        // Patches made from buffers don't know about modes, renames or sizes, which are taken from `diff`.
+        patch.print(&mut |_delta, hunk, line| {
+            let delta = diff.get_delta(idx).expect("index is in bounds");
+            on_line(delta, hunk, line)
+        })?;
+    }
+    Ok(())
 }
 
 /// Transform `diff` into a mapping of `worktree-relative path -> FileDiff`, where `FileDiff` is
@@ -205,6 +1058,20 @@ pub fn trees(
 pub fn hunks_by_filepath(
     repo: Option<&git2::Repository>,
     diff: &git2::Diff,
+) -> Result<DiffByPathMap> {
+    hunks_by_filepath_limited(repo, diff, None, None, None)
+}
+
+/// Like [`hunks_by_filepath()`], but stop after `max_files` files if set, and fail with
+/// [`Code::Cancelled`] once `should_interrupt` is `true` before a file is started.
+/// If `blobs` is set, `diff` is between two trees, and the patch of each file is produced from
+/// the cached blobs, with the given options, where possible.
+fn hunks_by_filepath_limited(
+    repo: Option<&git2::Repository>,
+    diff: &git2::Diff,
+    max_files: Option<usize>,
+    should_interrupt: Option<&AtomicBool>,
+    blobs: Option<(&mut BlobCache<'_>, &mut git2::DiffOptions)>,
 ) -> Result<DiffByPathMap> {
     enum LineOrHexHash<'a> {
         Line(Cow<'a, BStr>),
@@ -213,114 +1080,164 @@ pub fn hunks_by_filepath(
     // find all the hunks
     let mut diff_files = HashMap::new();
     let mut err = None;
-
-    diff.print(
-        git2::DiffFormat::Patch,
-        |delta, hunk, line: git2::DiffLine<'_>| {
-            let change_type: ChangeType = delta.status().into();
-            let file_path = delta.new_file().path().unwrap_or_else(|| {
-                delta
-                    .old_file()
-                    .path()
-                    .expect("failed to get file name from diff")
-            });
-
-            let new_start = hunk.as_ref().map_or(0, git2::DiffHunk::new_start);
-            let new_lines = hunk.as_ref().map_or(0, git2::DiffHunk::new_lines);
-            let old_start = hunk.as_ref().map_or(0, git2::DiffHunk::old_start);
-            let old_lines = hunk.as_ref().map_or(0, git2::DiffHunk::old_lines);
-
-            use git2::DiffLineType as D;
-            let line = match line.origin_value() {
-                D::Addition | D::Deletion | D::Context => {
-                    let mut buf = BString::new(Vec::with_capacity(line.content().len() + 1));
-                    buf.push_char(line.origin());
-                    buf.push_str(line.content());
-                    Some(LineOrHexHash::Line(buf.into()))
-                }
-                D::Binary => {
-                    if let Some((full_path, repo)) = repo
-                        .and_then(|repo| repo.workdir())
-                        .map(|workdir| workdir.join(file_path))
-                        .zip(repo)
-                    {
-                        if !delta.new_file().id().is_zero() && full_path.exists() {
-                            let oid = repo.blob_path(full_path.as_path()).unwrap();
-                            if delta.new_file().id() != oid {
-                                err = Some(format!("we only store the file which is already known by the diff system, but it was different: {} != {}", delta.new_file().id(), oid));
-                                return false
-                            }
+    // Binary blobs that were already written to the object database, so identical files, like copies,
+    // are only read and hashed once. Their id guarantees that the content is the same.
+    let mut stored_blobs = HashSet::new();
+    let mut limit_reached = false;
+    let mut interrupted = false;
+
+    let mut on_line = |delta: git2::DiffDelta<'_>,
+                       hunk: Option<DiffHunk<'_>>,
+                       line: git2::DiffLine<'_>| {
+        let change_type: ChangeType = delta.status().into();
+        let file_path = delta.new_file().path().unwrap_or_else(|| {
+            delta
+                .old_file()
+                .path()
+                .expect("failed to get file name from diff")
+        });
+
+        let new_start = hunk.as_ref().map_or(0, git2::DiffHunk::new_start);
+        let new_lines = hunk.as_ref().map_or(0, git2::DiffHunk::new_lines);
+        let old_start = hunk.as_ref().map_or(0, git2::DiffHunk::old_start);
+        let old_lines = hunk.as_ref().map_or(0, git2::DiffHunk::old_lines);
+
+        use git2::DiffLineType as D;
+        // Which of the `(old, new)` versions lack the final newline, with the marker line rendered into the hunk.
+        let no_newline_at_eof = match line.origin_value() {
+            D::ContextEOFNL => Some((true, true)),
+            D::AddEOFNL => Some((true, false)),
+            D::DeleteEOFNL => Some((false, true)),
+            _ => None,
+        };
+        let line = match line.origin_value() {
+            D::Addition | D::Deletion | D::Context => {
+                let mut buf = BString::new(Vec::with_capacity(line.content().len() + 1));
+                buf.push_char(line.origin());
+                buf.push_str(line.content());
+                Some(LineOrHexHash::Line(buf.into()))
+            }
+            D::Binary => {
+                if let Some((full_path, repo)) = repo
+                    .and_then(|repo| repo.workdir())
+                    .map(|workdir| workdir.join(file_path))
+                    .zip(repo)
+                {
+                    let id = delta.new_file().id();
+                    if !id.is_zero() && !stored_blobs.contains(&id) && full_path.exists() {
+                        let oid = repo.blob_path(full_path.as_path()).unwrap();
+                        if id != oid {
+                            err = Some(format!("we only store the file which is already known by the diff system, but it was different: {} != {}", id, oid));
+                            return false;
                         }
+                        stored_blobs.insert(id);
                     }
-                    Some(LineOrHexHash::HexHashOfBinaryBlob(delta.new_file().id().to_string()))
-                }
-                D::FileHeader => None,
-                D::HunkHeader | D::ContextEOFNL | D::AddEOFNL | D::DeleteEOFNL => {
-                    Some(LineOrHexHash::Line(line.content().as_bstr().into()))
                 }
-            };
+                Some(LineOrHexHash::HexHashOfBinaryBlob(
+                    delta.new_file().id().to_string(),
+                ))
+            }
+            D::FileHeader => None,
+            D::HunkHeader | D::ContextEOFNL | D::AddEOFNL | D::DeleteEOFNL => {
+                Some(LineOrHexHash::Line(line.content().as_bstr().into()))
+            }
+        };
 
-            match line {
-                None => {
-                    let existing = diff_files
-                        .insert(file_path.to_path_buf(),
-                            FileDiff {
-                                path: file_path.to_path_buf(),
-                                hunks: Vec::new(),
-                                skipped: false,
-                                binary: delta.new_file().is_binary(),
-                                old_size_bytes: delta.old_file().size(),
-                                new_size_bytes: delta.new_file().size(),
-                        });
-                    if existing.is_some() {
-                        err = Some(format!("Encountered an invalid internal state related to the diff: {existing:?}"));
-                        return false;
-                    }
+        match line {
+            None => {
+                if should_interrupt.is_some_and(|flag| flag.load(Ordering::Relaxed)) {
+                    interrupted = true;
+                    return false;
                 }
-                Some(line) => {
-                    let hunks = &mut diff_files.get_mut(file_path).expect("File header inserts the hunk-list").hunks;
-                    let same_hunk = hunks.last_mut().filter(|previous_hunk| {
-                        previous_hunk.old_start == old_start
-                            && previous_hunk.old_lines == old_lines
-                            && previous_hunk.new_start == new_start
-                            && previous_hunk.new_lines == new_lines
-                    });
-                    match same_hunk {
-                        Some(hunk) => match line {
-                            LineOrHexHash::Line(line) => {
-                                hunk.diff_lines.push_str(line.as_ref());
-                            }
+                if max_files.is_some_and(|max| diff_files.len() >= max) {
+                    limit_reached = true;
+                    return false;
+                }
+                let existing = diff_files.insert(
+                    file_path.to_path_buf(),
+                    FileDiff {
+                        path: file_path.to_path_buf(),
+                        old_path: (delta.status() == git2::Delta::Renamed)
+                            .then(|| delta.old_file().path().map(ToOwned::to_owned))
+                            .flatten(),
+                        hunks: Vec::new(),
+                        skipped: false,
+                        binary: delta.new_file().is_binary(),
+                        old_size_bytes: delta.old_file().size(),
+                        new_size_bytes: delta.new_file().size(),
+                        mode_change: mode_change(&delta),
+                        kind: DiffKind::Patch,
+                        no_newline_at_eof_old: false,
+                        no_newline_at_eof_new: false,
+                        rename_only: false,
+                        mode_only: false,
+                    },
+                );
+                if existing.is_some() {
+                    err = Some(format!(
+                        "Encountered an invalid internal state related to the diff: {existing:?}"
+                    ));
+                    return false;
+                }
+            }
+            Some(line) => {
+                let file = diff_files
+                    .get_mut(file_path)
+                    .expect("File header inserts the hunk-list");
+                if let Some((old, new)) = no_newline_at_eof {
+                    file.no_newline_at_eof_old |= old;
+                    file.no_newline_at_eof_new |= new;
+                }
+                let hunks = &mut file.hunks;
+                let same_hunk = hunks.last_mut().filter(|previous_hunk| {
+                    previous_hunk.old_start == old_start
+                        && previous_hunk.old_lines == old_lines
+                        && previous_hunk.new_start == new_start
+                        && previous_hunk.new_lines == new_lines
+                });
+                match same_hunk {
+                    Some(hunk) => match line {
+                        LineOrHexHash::Line(line) => {
+                            hunk.diff_lines.push_str(line.as_ref());
+                        }
+                        LineOrHexHash::HexHashOfBinaryBlob(id) => {
+                            let marker = GitHunk::binary_marker(id, hunk.change_type);
+                            *hunk = marker;
+                        }
+                    },
+                    None => {
+                        let new_hunk = match line {
+                            LineOrHexHash::Line(line) => GitHunk {
+                                old_start,
+                                old_lines,
+                                new_start,
+                                new_lines,
+                                diff_lines: line.into_owned().into(),
+                                binary: false,
+                                change_type,
+                            },
                             LineOrHexHash::HexHashOfBinaryBlob(id) => {
-                                let marker =  GitHunk::binary_marker(id, hunk.change_type) ;
-                                *hunk = marker;
+                                GitHunk::binary_marker(id, change_type)
                             }
-                        },
-                        None => {
-                            let new_hunk = match line {
-                                LineOrHexHash::Line(line) => {
-                                    GitHunk {
-                                        old_start,
-                                        old_lines,
-                                        new_start,
-                                        new_lines,
-                                        diff_lines: line.into_owned().into(),
-                                        binary: false,
-                                        change_type,
-                                    }
-                                }
-                                LineOrHexHash::HexHashOfBinaryBlob(id) => {
-                                    GitHunk::binary_marker(id, change_type)
-                                }
-                            };
-                            hunks.push(new_hunk);
-                        }
+                        };
+                        hunks.push(new_hunk);
                     }
                 }
             }
-            true
-        },
-    )
-    .with_context(|| format!("failed to print diff: {err:?}"))?;
+        }
+        true
+    };
+    let res = match blobs {
+        Some((blobs, opts)) => print_with_cached_blobs(diff, blobs, opts, &mut on_line),
+        None => diff.print(git2::DiffFormat::Patch, &mut on_line),
+    };
+    if interrupted {
+        return Err(anyhow!("The diff was cancelled").context(Code::Cancelled));
+    }
+    // Aborting the diff once the limit is reached is reported as error.
+    if !limit_reached {
+        res.with_context(|| format!("failed to print diff: {err:?}"))?;
+    }
 
     for file in diff_files.values_mut() {
         if let Some(binary_hunk) = file
@@ -334,6 +1251,8 @@ pub fn hunks_by_filepath(
                 file.hunks = vec![binary_hunk];
             }
         } else if file.hunks.is_empty() {
+            file.rename_only = file.old_path.is_some() && file.mode_change.is_none();
+            file.mode_only = file.mode_change.is_some() && file.old_path.is_none();
             file.hunks = vec![GitHunk::generic_new_file()];
         }
     }
@@ -897,4 +1816,41 @@ mod test {
             )
         );
     }
+
+    #[test]
+    fn blobs_shared_by_files_are_read_once() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let repo = git2::Repository::init(tmp.path())?;
+        let tree = |content: &str| -> anyhow::Result<git2::Oid> {
+            let blob = repo.blob(content.as_bytes())?;
+            let mut builder = repo.treebuilder(None)?;
+            for name in ["copy-1", "copy-2", "original"] {
+                builder.insert(name, blob, git2::FileMode::Blob.into())?;
+            }
+            Ok(builder.write()?)
+        };
+        let old_tree = repo.find_tree(tree("1\n2\n3\n")?)?;
+        let new_tree = repo.find_tree(tree("1\ntwo\n3\n")?)?;
+        let mut opts = git2::DiffOptions::new();
+        opts.show_binary(true).context_lines(3);
+        let diff = repo.diff_tree_to_tree(Some(&old_tree), Some(&new_tree), Some(&mut opts))?;
+
+        let mut blobs = BlobCache::new(&repo, &diff)?;
+        let files =
+            hunks_by_filepath_limited(None, &diff, None, None, Some((&mut blobs, &mut opts)))?;
+        assert_eq!(
+            blobs.reads, 2,
+            "the old and the new blob are read once for all three files"
+        );
+        assert!(
+            blobs.blobs.is_empty(),
+            "blobs are released after the last file using them"
+        );
+        assert_eq!(
+            files,
+            hunks_by_filepath(None, &diff)?,
+            "the result is the same as when each file reads its own blobs"
+        );
+        Ok(())
+    }
 }
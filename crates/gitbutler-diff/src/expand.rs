@@ -0,0 +1,88 @@
+use anyhow::{Context, Result};
+use bstr::ByteSlice;
+use gitbutler_serde::BStringForFrontend;
+use serde::Serialize;
+
+use crate::GitHunk;
+
+/// The side of a hunk to reveal more lines on with [`expand_hunk()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExpandDirection {
+    /// Reveal the lines above the hunk.
+    Up,
+    /// Reveal the lines below the hunk.
+    Down,
+}
+
+/// An unchanged line surrounding a hunk, as returned by [`expand_hunk()`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextLine {
+    /// The 1-based line number in the old version of the file.
+    pub old_lineno: u32,
+    /// The 1-based line number in the new version of the file.
+    pub new_lineno: u32,
+    /// The content of the line, without its line separator.
+    pub content: BStringForFrontend,
+}
+
+/// Return up to `lines` unchanged lines in `direction` of `hunk`, read from `file`, the blob of the
+/// old version of the file the hunk belongs to, in the order they appear in the file.
+///
+/// As the lines around a hunk are the same on both sides of the diff, the old blob is used even for
+/// worktree changes, whose new version isn't stored in the object database.
+/// Fewer lines are returned if the start or end of the file is reached, and none if the hunk is
+/// there already.
+pub fn expand_hunk(
+    repo: &git2::Repository,
+    file: git2::Oid,
+    hunk: &GitHunk,
+    direction: ExpandDirection,
+    lines: usize,
+) -> Result<Vec<ContextLine>> {
+    let blob = repo
+        .find_blob(file)
+        .with_context(|| format!("failed to find blob {file} to expand hunk in"))?;
+    let file_lines: Vec<_> = blob.content().lines().collect();
+    let lines = u32::try_from(lines).unwrap_or(u32::MAX);
+
+    // Hunks without lines on a side start at the line *before* them, instead of their first line.
+    let lines_before = |start: u32, len: u32| if len == 0 { start } else { start - 1 };
+    let old_before = lines_before(hunk.old_start, hunk.old_lines);
+    let new_before = lines_before(hunk.new_start, hunk.new_lines);
+
+    let (first_old, end_old, new_offset) = match direction {
+        ExpandDirection::Up => (
+            old_before.saturating_sub(lines) + 1,
+            old_before + 1,
+            i64::from(new_before) - i64::from(old_before),
+        ),
+        ExpandDirection::Down => {
+            let first_old = old_before + hunk.old_lines + 1;
+            let end_old = first_old
+                .saturating_add(lines)
+                .min(u32::try_from(file_lines.len()).unwrap_or(u32::MAX) + 1);
+            let first_new = new_before + hunk.new_lines + 1;
+            (
+                first_old,
+                end_old,
+                i64::from(first_new) - i64::from(first_old),
+            )
+        }
+    };
+
+    (first_old..end_old)
+        .map(|old_lineno| {
+            let content = file_lines
+                .get(old_lineno as usize - 1)
+                .with_context(|| format!("line {old_lineno} is out of range of blob {file}"))?;
+            Ok(ContextLine {
+                old_lineno,
+                new_lineno: u32::try_from(i64::from(old_lineno) + new_offset)
+                    .context("hunk doesn't belong to the given blob")?,
+                content: content.as_bstr().into(),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,123 @@
+use serde::Serialize;
+
+/// The image formats we can recognize by their magic bytes.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Gif,
+    Webp,
+}
+
+/// What's known about an image from its header, without decoding the pixels.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageMeta {
+    pub format: ImageFormat,
+    pub width: u32,
+    pub height: u32,
+    pub size_bytes: u64,
+}
+
+impl ImageMeta {
+    /// Return the metadata of the image in `data`, or `None` if it isn't an image in one of the
+    /// supported [formats](ImageFormat), or if its header is truncated.
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let (format, (width, height)) = if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            (ImageFormat::Png, png_dimensions(data)?)
+        } else if data.starts_with(b"\xff\xd8") {
+            (ImageFormat::Jpeg, jpeg_dimensions(data)?)
+        } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+            (ImageFormat::Gif, gif_dimensions(data)?)
+        } else if data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP") {
+            (ImageFormat::Webp, webp_dimensions(data)?)
+        } else {
+            return None;
+        };
+        Some(ImageMeta {
+            format,
+            width,
+            height,
+            size_bytes: data.len() as u64,
+        })
+    }
+}
+
+fn u16_be(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]).into())
+}
+
+fn u16_le(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]).into())
+}
+
+fn u24_le(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 3)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], 0]))
+}
+
+fn u32_be(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes(bytes.try_into().ok()?))
+}
+
+/// The `IHDR` chunk always comes first, right after the signature.
+fn png_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    if data.get(12..16)? != b"IHDR" {
+        return None;
+    }
+    Some((u32_be(data, 16)?, u32_be(data, 20)?))
+}
+
+/// Walk the segments until the first start-of-frame, which holds the dimensions.
+fn jpeg_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let mut offset = 2;
+    loop {
+        if *data.get(offset)? != 0xff {
+            return None;
+        }
+        let marker = *data.get(offset + 1)?;
+        match marker {
+            // Padding before a marker.
+            0xff => offset += 1,
+            // Markers without a payload.
+            0x01 | 0xd0..=0xd7 => offset += 2,
+            // Start-of-frame, except for DHT, JPG and DAC, which share the range.
+            0xc0..=0xcf if !matches!(marker, 0xc4 | 0xc8 | 0xcc) => {
+                let height = u16_be(data, offset + 5)?;
+                let width = u16_be(data, offset + 7)?;
+                return Some((width, height));
+            }
+            _ => offset += 2 + u16_be(data, offset + 2)? as usize,
+        }
+    }
+}
+
+/// The logical screen size follows the signature.
+fn gif_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    Some((u16_le(data, 6)?, u16_le(data, 8)?))
+}
+
+/// The first chunk is one of the lossy, lossless or extended formats, all of which encode dimensions differently.
+fn webp_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    match data.get(12..16)? {
+        b"VP8 " => Some((u16_le(data, 26)? & 0x3fff, u16_le(data, 28)? & 0x3fff)),
+        b"VP8L" => {
+            let bits = data.get(21..25)?;
+            let (b0, b1, b2, b3) = (
+                u32::from(bits[0]),
+                u32::from(bits[1]),
+                u32::from(bits[2]),
+                u32::from(bits[3]),
+            );
+            let width = 1 + (((b1 & 0x3f) << 8) | b0);
+            let height = 1 + (((b3 & 0x0f) << 10) | (b2 << 2) | ((b1 & 0xc0) >> 6));
+            Some((width, height))
+        }
+        b"VP8X" => Some((1 + u24_le(data, 24)?, 1 + u24_le(data, 27)?)),
+        _ => None,
+    }
+}
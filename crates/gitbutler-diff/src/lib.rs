@@ -1,8 +1,19 @@
+mod apply;
+pub use apply::{apply_patch, preview_apply, ApplyReport, ApplyTarget};
+mod dependencies;
+pub use dependencies::{analyze_dependencies, HunkId};
 mod diff;
+mod expand;
+pub use expand::{expand_hunk, ContextLine, ExpandDirection};
 mod hunk;
+mod image;
+pub use image::{ImageFormat, ImageMeta};
 pub mod write;
 pub use diff::{
-    diff_files_into_hunks, hunks_by_filepath, reverse_hunk, reverse_hunk_lines, trees, workdir,
-    ChangeType, DiffByPathMap, FileDiff, GitHunk,
+    against_merge_base, diff_files_into_hunks, directory_summary, hunks_by_filepath,
+    per_file_stats, range_diff, recompute_paths, reverse_hunk, reverse_hunk_lines, trees,
+    trees_truncated, trees_with_options, workdir, workdir_truncated, workdir_with_options,
+    ChangeType, DiffAlgorithm, DiffByPathMap, DiffKind, DiffOptions, DirSummary, FileDiff,
+    FileStat, GitHunk, NewFileMode, TruncatedDiff,
 };
 pub use hunk::{Hunk, HunkHash};
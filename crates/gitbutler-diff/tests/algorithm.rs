@@ -0,0 +1,98 @@
+use std::path::Path;
+
+use gitbutler_diff::{DiffAlgorithm, DiffOptions};
+
+use crate::{init_repo, tree};
+
+const OLD: &str = r#"#include <stdio.h>
+
+// Frobs foo heartily
+int frobnitz(int foo)
+{
+    int i;
+    for(i = 0; i < 10; i++)
+    {
+        printf("Your answer is: ");
+        printf("%d\n", foo);
+    }
+}
+
+int fact(int n)
+{
+    if(n > 1)
+    {
+        return fact(n-1) * n;
+    }
+    return 1;
+}
+
+int main(int argc, char **argv)
+{
+    frobnitz(fact(10));
+}
+"#;
+
+/// `fact()` was replaced by `fib()`, which moved to the top.
+const NEW: &str = r#"#include <stdio.h>
+
+int fib(int n)
+{
+    if(n > 2)
+    {
+        return fib(n-1) + fib(n-2);
+    }
+    return 1;
+}
+
+// Frobs foo heartily
+int frobnitz(int foo)
+{
+    int i;
+    for(i = 0; i < 10; i++)
+    {
+        printf("%d\n", foo);
+    }
+}
+
+int main(int argc, char **argv)
+{
+    frobnitz(fib(10));
+}
+"#;
+
+#[test]
+fn patience_produces_fewer_hunks_for_moved_blocks() -> anyhow::Result<()> {
+    let (_tmp, repo) = init_repo()?;
+    let old_tree = tree(&repo, &[("file.c", OLD)])?;
+    let new_tree = tree(&repo, &[("file.c", NEW)])?;
+
+    let hunk_count = |algorithm: DiffAlgorithm| -> anyhow::Result<usize> {
+        let diff = gitbutler_diff::trees_with_options(
+            &repo,
+            &old_tree,
+            &new_tree,
+            false,
+            &DiffOptions {
+                algorithm,
+                ..Default::default()
+            },
+        )?;
+        Ok(diff[Path::new("file.c")].hunks.len())
+    };
+
+    let myers = hunk_count(DiffAlgorithm::Myers)?;
+    assert_eq!(
+        myers,
+        gitbutler_diff::trees(&repo, &old_tree, &new_tree, false)?[Path::new("file.c")]
+            .hunks
+            .len(),
+        "Myers is the default"
+    );
+    let patience = hunk_count(DiffAlgorithm::Patience)?;
+    assert!(
+        patience < myers,
+        "patience anchors on unique lines, and doesn't match braces and blank lines: {patience} < {myers}"
+    );
+    assert!(hunk_count(DiffAlgorithm::Minimal)? <= myers);
+    Ok(())
+}
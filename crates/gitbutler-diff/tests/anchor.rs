@@ -0,0 +1,55 @@
+use std::path::Path;
+
+use gitbutler_diff::{ChangeType, GitHunk};
+
+fn hunk(old_start: u32, new_start: u32, diff: &str) -> GitHunk {
+    GitHunk {
+        old_start,
+        old_lines: 1,
+        new_start,
+        new_lines: 1,
+        diff_lines: diff.into(),
+        binary: false,
+        change_type: ChangeType::Modified,
+    }
+}
+
+#[test]
+fn same_position_same_anchor() {
+    let path = Path::new("dir/file");
+    let anchor = hunk(3, 4, "@@ -3,1 +4,1 @@\n-a\n+b\n").anchor(path);
+    assert_eq!(
+        anchor,
+        hunk(3, 4, "@@ -3,1 +4,1 @@\n-a\n+b\n").anchor(path),
+        "anchors are deterministic"
+    );
+    assert_eq!(
+        anchor,
+        hunk(3, 4, "@@ -3,1 +4,1 @@\n-a\n+c\n").anchor(path),
+        "only the position matters, not the content"
+    );
+    assert!(
+        anchor.starts_with("hunk-") && anchor.ends_with("-3-4"),
+        "{anchor}"
+    );
+    assert!(
+        anchor
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-'),
+        "it can be used as URL fragment as is"
+    );
+}
+
+#[test]
+fn different_hunks_differ() {
+    let path = Path::new("dir/file");
+    let anchor = hunk(3, 4, "").anchor(path);
+    assert_ne!(anchor, hunk(4, 4, "").anchor(path));
+    assert_ne!(anchor, hunk(3, 5, "").anchor(path));
+    assert_ne!(anchor, hunk(3, 4, "").anchor(Path::new("dir/other")));
+    assert_ne!(
+        hunk(1, 23, "").anchor(path),
+        hunk(12, 3, "").anchor(path),
+        "line numbers are separated"
+    );
+}
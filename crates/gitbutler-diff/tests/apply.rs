@@ -0,0 +1,171 @@
+use gitbutler_diff::{apply_patch, ApplyReport, ApplyTarget};
+use gitbutler_error::error::{AnyhowContextExt, Code};
+
+use crate::init_repo;
+
+const PATCH: &str = "diff --git a/file b/file
+index 01e79c3..5ca8d2f 100644
+--- a/file
++++ b/file
+@@ -1,3 +1,3 @@
+ 1
+-2
++two
+ 3
+diff --git a/other b/other
+index 01e79c3..5ca8d2f 100644
+--- a/other
++++ b/other
+@@ -1,3 +1,3 @@
+ 1
+-2
++two
+ 3
+";
+
+const WORKTREE: ApplyTarget = ApplyTarget {
+    worktree: true,
+    index: false,
+};
+
+#[test]
+fn well_formed_patch_applies_per_file() -> anyhow::Result<()> {
+    let (tmp, repo) = init_repo()?;
+    std::fs::write(tmp.path().join("file"), "1\n2\n3\n")?;
+    std::fs::write(tmp.path().join("other"), "1\nchanged\n3\n")?;
+
+    let report = apply_patch(&repo, PATCH, WORKTREE, false)?;
+    assert_eq!(
+        report,
+        ApplyReport {
+            applied: vec!["file".into()],
+            conflicted: vec!["other".into()],
+        }
+    );
+    assert_eq!(
+        std::fs::read_to_string(tmp.path().join("file"))?,
+        "1\ntwo\n3\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(tmp.path().join("other"))?,
+        "1\nchanged\n3\n",
+        "conflicting files are left untouched"
+    );
+
+    let report = apply_patch(&repo, PATCH, WORKTREE, true)?;
+    assert_eq!(report.applied, ["file"].map(std::path::PathBuf::from));
+    assert_eq!(
+        std::fs::read_to_string(tmp.path().join("file"))?,
+        "1\n2\n3\n",
+        "the reversed patch undoes the change"
+    );
+    Ok(())
+}
+
+#[test]
+fn malformed_patch_is_rejected() -> anyhow::Result<()> {
+    let (tmp, repo) = init_repo()?;
+    std::fs::write(tmp.path().join("file"), "1\n2\n3\n")?;
+
+    for patch in [
+        "this is not a patch",
+        "diff --git a/file b/file\n--- a/file\n+++ b/file\n@@ -1,3 +1,3 @@\n-2\n",
+    ] {
+        let err = apply_patch(&repo, patch, WORKTREE, false).unwrap_err();
+        assert_eq!(
+            err.custom_context().map(|ctx| ctx.code),
+            Some(Code::Validation),
+            "{patch:?}"
+        );
+    }
+    assert_eq!(
+        std::fs::read_to_string(tmp.path().join("file"))?,
+        "1\n2\n3\n"
+    );
+    Ok(())
+}
+
+mod preview_apply {
+    use std::path::Path;
+
+    use gitbutler_diff::{preview_apply, GitHunk};
+    use gitbutler_error::error::{AnyhowContextExt, Code};
+
+    use crate::{init_repo, tree};
+
+    fn blob_and_tree<'repo>(
+        repo: &'repo git2::Repository,
+        content: &str,
+    ) -> anyhow::Result<(git2::Oid, git2::Tree<'repo>)> {
+        let tree = tree(repo, &[("file", content)])?;
+        Ok((tree.get_path(Path::new("file"))?.id(), tree))
+    }
+
+    fn numbered_lines(range: std::ops::RangeInclusive<u32>) -> String {
+        range.map(|n| format!("line {n}\n")).collect()
+    }
+
+    /// Return the blobs of `old` and `new` along with the hunks of their diff.
+    fn hunks_between(
+        repo: &git2::Repository,
+        old: &str,
+        new: &str,
+    ) -> anyhow::Result<(git2::Oid, git2::Oid, Vec<GitHunk>)> {
+        let (old_blob, old_tree) = blob_and_tree(repo, old)?;
+        let (new_blob, new_tree) = blob_and_tree(repo, new)?;
+        let diff = gitbutler_diff::trees(repo, &old_tree, &new_tree, true)?;
+        Ok((old_blob, new_blob, diff[Path::new("file")].hunks.clone()))
+    }
+
+    #[test]
+    fn reversal_of_one_hunk() -> anyhow::Result<()> {
+        let (_tmp, repo) = init_repo()?;
+        let old = numbered_lines(1..=20);
+        let new = old
+            .replace("line 2\n", "two\n")
+            .replace("line 18\n", "eighteen\n");
+        let (old_blob, new_blob, hunks) = hunks_between(&repo, &old, &new)?;
+        assert_eq!(hunks.len(), 2);
+
+        let preview = preview_apply(&repo, new_blob, &hunks[1..], true)?;
+        assert_eq!(
+            preview,
+            old.replace("line 2\n", "two\n").as_bytes(),
+            "only the second hunk is undone"
+        );
+        assert_eq!(
+            preview_apply(&repo, new_blob, &hunks, true)?,
+            old.as_bytes()
+        );
+        assert_eq!(
+            preview_apply(&repo, old_blob, &hunks[..1], false)?,
+            old.replace("line 2\n", "two\n").as_bytes()
+        );
+        assert_eq!(
+            preview_apply(&repo, old_blob, &hunks, false)?,
+            new.as_bytes()
+        );
+        assert_eq!(
+            repo.find_blob(new_blob)?.content(),
+            new.as_bytes(),
+            "nothing is changed"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn hunks_that_dont_apply_are_rejected() -> anyhow::Result<()> {
+        let (_tmp, repo) = init_repo()?;
+        let old = numbered_lines(1..=20);
+        let new = old.replace("line 10\n", "ten\n");
+        let (old_blob, _new_blob, hunks) = hunks_between(&repo, &old, &new)?;
+
+        // The old version doesn't contain the lines the reversal removes.
+        let err = preview_apply(&repo, old_blob, &hunks, true).unwrap_err();
+        assert_eq!(
+            err.custom_context().map(|ctx| ctx.code),
+            Some(Code::Validation)
+        );
+        Ok(())
+    }
+}
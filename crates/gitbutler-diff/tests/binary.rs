@@ -0,0 +1,32 @@
+use std::path::Path;
+
+use crate::{commit, init_repo};
+
+#[test]
+fn identical_binary_files_are_stored_once_and_share_their_id() -> anyhow::Result<()> {
+    let (tmp, repo) = init_repo()?;
+    let commit_id = commit(&repo, &[], &[])?;
+
+    let content = b"\0binary\0content";
+    for name in ["original", "copy"] {
+        std::fs::write(tmp.path().join(name), content)?;
+    }
+
+    let diff = gitbutler_diff::workdir(&repo, commit_id)?;
+    let expected_id = git2::Oid::hash_object(git2::ObjectType::Blob, content)?;
+    for name in ["original", "copy"] {
+        let file = &diff[Path::new(name)];
+        assert!(file.binary);
+        assert_eq!(file.hunks.len(), 1);
+        assert_eq!(
+            file.hunks[0].diff_lines.to_string(),
+            expected_id.to_string(),
+            "the marker hunk refers to the same blob"
+        );
+    }
+    assert!(
+        repo.find_blob(expected_id).is_ok(),
+        "the content was stored in the object database"
+    );
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use gitbutler_diff::{analyze_dependencies, ChangeType, FileDiff, GitHunk};
+
+fn hunk(old_start: u32, old_lines: u32, new_start: u32, new_lines: u32) -> GitHunk {
+    GitHunk {
+        old_start,
+        old_lines,
+        new_start,
+        new_lines,
+        diff_lines: Default::default(),
+        binary: false,
+        change_type: ChangeType::Modified,
+    }
+}
+
+fn file_diff(hunks: Vec<GitHunk>) -> FileDiff {
+    FileDiff {
+        path: "file".into(),
+        hunks,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn hunks_sharing_context_depend_on_each_other() {
+    let diff = file_diff(vec![hunk(1, 6, 1, 7), hunk(5, 6, 6, 5)]);
+    assert_eq!(
+        analyze_dependencies(&diff),
+        [(0, vec![1]), (1, vec![0])],
+        "the context of the second hunk contains lines changed by the first one"
+    );
+}
+
+#[test]
+fn separate_hunks_are_independent() {
+    let diff = file_diff(vec![hunk(1, 6, 1, 7), hunk(20, 6, 21, 5)]);
+    assert_eq!(analyze_dependencies(&diff), [(0, vec![]), (1, vec![])]);
+}
+
+#[test]
+fn dependencies_are_transitive() {
+    let diff = file_diff(vec![
+        hunk(12, 3, 12, 4),
+        hunk(1, 6, 1, 7),
+        hunk(7, 5, 8, 5),
+        hunk(40, 3, 41, 3),
+    ]);
+    assert_eq!(
+        analyze_dependencies(&diff),
+        [
+            (0, vec![1, 2]),
+            (1, vec![0, 2]),
+            (2, vec![0, 1]),
+            (3, vec![])
+        ],
+        "touching ranges chain up, and the result is in the order of the hunks"
+    );
+}
@@ -0,0 +1,56 @@
+use std::path::PathBuf;
+
+use crate::{init_repo, tree};
+
+#[test]
+fn counts_roll_up_by_directory() -> anyhow::Result<()> {
+    let (_tmp, repo) = init_repo()?;
+    let old = tree(
+        &repo,
+        &[
+            ("docs/readme", "gone\n"),
+            ("src/lib.rs", "1\n2\n3\n"),
+            ("top", "a\n"),
+        ],
+    )?;
+    let new = tree(
+        &repo,
+        &[
+            ("src/lib.rs", "1\ntwo\n3\n4\n"),
+            ("src/util/mod.rs", "new\n"),
+            ("top", "b\n"),
+        ],
+    )?;
+    let diff = gitbutler_diff::trees(&repo, &old, &new, true)?;
+    assert_eq!(diff.len(), 4);
+
+    let summary = gitbutler_diff::directory_summary(&diff);
+    let dirs: Vec<_> = summary.iter().map(|dir| dir.path.clone()).collect();
+    assert_eq!(
+        dirs,
+        [PathBuf::from("docs"), PathBuf::from("src")],
+        "files at the top-level don't belong to a directory"
+    );
+
+    let docs = &summary[0];
+    assert_eq!(
+        (docs.files_changed, docs.insertions, docs.deletions),
+        (1, 0, 1)
+    );
+    assert!(docs.children.is_empty());
+
+    let src = &summary[1];
+    assert_eq!(
+        (src.files_changed, src.insertions, src.deletions),
+        (2, 3, 1),
+        "nested directories are included in the counts of their parents"
+    );
+    assert_eq!(src.children.len(), 1);
+    let util = &src.children[0];
+    assert_eq!(util.path, PathBuf::from("src/util"));
+    assert_eq!(
+        (util.files_changed, util.insertions, util.deletions),
+        (1, 1, 0)
+    );
+    Ok(())
+}
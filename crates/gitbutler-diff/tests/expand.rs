@@ -0,0 +1,124 @@
+use std::path::Path;
+
+use gitbutler_diff::{expand_hunk, ExpandDirection, GitHunk};
+
+use crate::{init_repo, tree};
+
+fn numbered_lines(range: std::ops::RangeInclusive<u32>) -> String {
+    range.map(|n| format!("{n}\n")).collect()
+}
+
+/// Return the blob of the old version of `file` along with the only hunk of its diff to `new`.
+fn hunk_between(
+    repo: &git2::Repository,
+    old: &str,
+    new: &str,
+) -> anyhow::Result<(git2::Oid, GitHunk)> {
+    let old_tree = tree(repo, &[("file", old)])?;
+    let new_tree = tree(repo, &[("file", new)])?;
+    let diff = gitbutler_diff::trees(repo, &old_tree, &new_tree, true)?;
+    let mut hunks = diff[Path::new("file")].hunks.clone();
+    assert_eq!(hunks.len(), 1);
+    let blob = old_tree.get_name("file").expect("just written").id();
+    Ok((blob, hunks.remove(0)))
+}
+
+/// Render the lines as `old:new:content` for easy comparison.
+fn render(lines: Vec<gitbutler_diff::ContextLine>) -> Vec<String> {
+    lines
+        .into_iter()
+        .map(|line| format!("{}:{}:{}", line.old_lineno, line.new_lineno, *line.content))
+        .collect()
+}
+
+#[test]
+fn up_and_down_with_shifted_line_numbers() -> anyhow::Result<()> {
+    let (_tmp, repo) = init_repo()?;
+    let old = numbered_lines(1..=20);
+    let new = old.replace("10\n", "10a\n10b\n");
+    let (blob, hunk) = hunk_between(&repo, &old, &new)?;
+    assert_eq!(
+        (
+            hunk.old_start,
+            hunk.old_lines,
+            hunk.new_start,
+            hunk.new_lines
+        ),
+        (7, 7, 7, 8)
+    );
+
+    assert_eq!(
+        render(expand_hunk(&repo, blob, &hunk, ExpandDirection::Up, 2)?),
+        ["5:5:5", "6:6:6"]
+    );
+    assert_eq!(
+        render(expand_hunk(&repo, blob, &hunk, ExpandDirection::Down, 3)?),
+        ["14:15:14", "15:16:15", "16:17:16"],
+        "lines below the hunk are shifted by the added line on the new side"
+    );
+    Ok(())
+}
+
+#[test]
+fn stops_at_file_boundaries() -> anyhow::Result<()> {
+    let (_tmp, repo) = init_repo()?;
+    let old = numbered_lines(1..=10);
+    let new = old.replace("5\n", "five\n");
+    let (blob, hunk) = hunk_between(&repo, &old, &new)?;
+    assert_eq!((hunk.old_start, hunk.old_lines), (2, 7));
+
+    assert_eq!(
+        render(expand_hunk(&repo, blob, &hunk, ExpandDirection::Up, 100)?),
+        ["1:1:1"],
+        "only the lines up to the start of the file are returned"
+    );
+    assert_eq!(
+        render(expand_hunk(&repo, blob, &hunk, ExpandDirection::Down, 100)?),
+        ["9:9:9", "10:10:10"],
+        "only the lines up to the end of the file are returned"
+    );
+    Ok(())
+}
+
+#[test]
+fn nothing_to_reveal_at_file_boundaries() -> anyhow::Result<()> {
+    let (_tmp, repo) = init_repo()?;
+    let old = numbered_lines(1..=3);
+    let new = numbered_lines(1..=4);
+    let (blob, hunk) = hunk_between(&repo, &old, &new)?;
+    assert_eq!(
+        (
+            hunk.old_start,
+            hunk.old_lines,
+            hunk.new_start,
+            hunk.new_lines
+        ),
+        (1, 3, 1, 4)
+    );
+
+    assert!(expand_hunk(&repo, blob, &hunk, ExpandDirection::Up, 5)?.is_empty());
+    assert!(expand_hunk(&repo, blob, &hunk, ExpandDirection::Down, 5)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn around_pure_insertion() -> anyhow::Result<()> {
+    let (_tmp, repo) = init_repo()?;
+    let old = numbered_lines(1..=10);
+    let (blob, mut hunk) = hunk_between(&repo, &old, &old.replace("5\n", "5\nnew\n"))?;
+    // Strip the context lines to get a hunk with nothing on the old side.
+    hunk.old_start = 5;
+    hunk.old_lines = 0;
+    hunk.new_start = 6;
+    hunk.new_lines = 1;
+
+    assert_eq!(
+        render(expand_hunk(&repo, blob, &hunk, ExpandDirection::Up, 1)?),
+        ["5:5:5"]
+    );
+    assert_eq!(
+        render(expand_hunk(&repo, blob, &hunk, ExpandDirection::Down, 1)?),
+        ["6:7:6"]
+    );
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use gitbutler_diff::{DiffKind, ImageFormat, ImageMeta};
+
+use crate::{init_repo, tree_with_modes};
+
+/// The signature and an `IHDR` chunk, which is all that's needed for the metadata.
+fn png(width: u32, height: u32) -> Vec<u8> {
+    let mut data = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+    data.extend_from_slice(&width.to_be_bytes());
+    data.extend_from_slice(&height.to_be_bytes());
+    data.extend_from_slice(b"\x08\x06\0\0\0");
+    data
+}
+
+/// A JFIF header followed by a baseline start-of-frame.
+fn jpeg(width: u16, height: u16) -> Vec<u8> {
+    let mut data = b"\xff\xd8\xff\xe0\0\x10JFIF\0\x01\x01\0\0\x01\0\x01\0\0".to_vec();
+    data.extend_from_slice(b"\xff\xc0\0\x11\x08");
+    data.extend_from_slice(&height.to_be_bytes());
+    data.extend_from_slice(&width.to_be_bytes());
+    data.extend_from_slice(b"\x03\x01\x22\0\x02\x11\x01\x03\x11\x01");
+    data
+}
+
+#[test]
+fn png_meta() {
+    let data = png(640, 480);
+    assert_eq!(
+        ImageMeta::from_bytes(&data),
+        Some(ImageMeta {
+            format: ImageFormat::Png,
+            width: 640,
+            height: 480,
+            size_bytes: data.len() as u64,
+        })
+    );
+}
+
+#[test]
+fn jpeg_meta() {
+    let data = jpeg(1024, 768);
+    assert_eq!(
+        ImageMeta::from_bytes(&data),
+        Some(ImageMeta {
+            format: ImageFormat::Jpeg,
+            width: 1024,
+            height: 768,
+            size_bytes: data.len() as u64,
+        })
+    );
+}
+
+#[test]
+fn gif_meta() {
+    let data = b"GIF89a\x20\x00\x10\x00\x80\0\0";
+    let meta = ImageMeta::from_bytes(data).unwrap();
+    assert_eq!(
+        (meta.format, meta.width, meta.height),
+        (ImageFormat::Gif, 32, 16)
+    );
+}
+
+#[test]
+fn webp_extended_meta() {
+    let mut data = b"RIFF\0\0\0\0WEBPVP8X\x0a\0\0\0\0\0\0\0".to_vec();
+    data.extend_from_slice(&[199, 0, 0, 99, 0, 0]);
+    let meta = ImageMeta::from_bytes(&data).unwrap();
+    assert_eq!(
+        (meta.format, meta.width, meta.height),
+        (ImageFormat::Webp, 200, 100)
+    );
+}
+
+#[test]
+fn not_an_image_or_truncated() {
+    assert_eq!(ImageMeta::from_bytes(b"plain text"), None);
+    assert_eq!(ImageMeta::from_bytes(&png(1, 1)[..20]), None);
+    assert_eq!(ImageMeta::from_bytes(b"\xff\xd8\xff\xe0\0\x10"), None);
+}
+
+#[test]
+fn image_change_between_trees() -> anyhow::Result<()> {
+    let (_tmp, repo) = init_repo()?;
+    let old_tree = tree_with_modes(&repo, &[("image.png", &png(16, 16), git2::FileMode::Blob)])?;
+    let new_tree = tree_with_modes(&repo, &[("image.png", &png(32, 16), git2::FileMode::Blob)])?;
+
+    let diff = gitbutler_diff::trees(&repo, &old_tree, &new_tree, true)?;
+    let file = &diff[std::path::Path::new("image.png")];
+    let DiffKind::Image {
+        old: Some(old),
+        new: Some(new),
+    } = file.kind
+    else {
+        panic!("expected an image diff for both sides, got {:?}", file.kind);
+    };
+    assert_eq!((old.width, old.height), (16, 16));
+    assert_eq!((new.width, new.height), (32, 16));
+
+    Ok(())
+}
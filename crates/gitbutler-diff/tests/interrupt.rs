@@ -0,0 +1,41 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use gitbutler_diff::DiffOptions;
+use gitbutler_error::error::{AnyhowContextExt, Code};
+
+use crate::{init_repo, tree};
+
+#[test]
+fn interrupted_diff_is_cancelled() -> anyhow::Result<()> {
+    let (_tmp, repo) = init_repo()?;
+    let old_tree = tree(&repo, &[])?;
+    let files: Vec<_> = (0..50)
+        .map(|idx| (format!("file-{idx:02}"), format!("{idx}\n")))
+        .collect();
+    let files: Vec<_> = files
+        .iter()
+        .map(|(path, content)| (path.as_str(), content.as_str()))
+        .collect();
+    let new_tree = tree(&repo, &files)?;
+
+    let should_interrupt = Arc::new(AtomicBool::new(false));
+    let options = DiffOptions {
+        should_interrupt: Some(should_interrupt.clone()),
+        ..Default::default()
+    };
+    let diff = gitbutler_diff::trees_with_options(&repo, &old_tree, &new_tree, true, &options)?;
+    assert_eq!(diff.len(), 50, "without interruption, all files are diffed");
+
+    should_interrupt.store(true, Ordering::Relaxed);
+    let err = gitbutler_diff::trees_with_options(&repo, &old_tree, &new_tree, true, &options)
+        .unwrap_err();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::Cancelled),
+        "the diff stops before the next file"
+    );
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use std::path::Path;
+
+use gitbutler_diff::DiffOptions;
+
+use crate::{commit, init_repo, tree};
+
+#[test]
+fn only_the_first_files_are_diffed() -> anyhow::Result<()> {
+    let (_tmp, repo) = init_repo()?;
+    let old_tree = tree(&repo, &[])?;
+    let files: Vec<_> = (0..50)
+        .map(|idx| (format!("file-{idx:02}"), format!("{idx}\n")))
+        .collect();
+    let files: Vec<_> = files
+        .iter()
+        .map(|(path, content)| (path.as_str(), content.as_str()))
+        .collect();
+    let new_tree = tree(&repo, &files)?;
+
+    let options = DiffOptions {
+        max_files: Some(10),
+        ..Default::default()
+    };
+    let diff = gitbutler_diff::trees_truncated(&repo, &old_tree, &new_tree, true, &options)?;
+    assert_eq!(diff.files.len(), 10);
+    assert!(diff.truncated);
+    assert_eq!(diff.total_files, 50);
+    for idx in 0..10 {
+        assert!(
+            diff.files
+                .contains_key(Path::new(&format!("file-{idx:02}"))),
+            "files are diffed in order"
+        );
+    }
+
+    let options = DiffOptions {
+        max_files: Some(50),
+        ..Default::default()
+    };
+    let diff = gitbutler_diff::trees_truncated(&repo, &old_tree, &new_tree, true, &options)?;
+    assert_eq!(diff.files.len(), 50);
+    assert!(!diff.truncated, "all files fit");
+    Ok(())
+}
+
+#[test]
+fn limit_applies_to_the_worktree() -> anyhow::Result<()> {
+    let (tmp, repo) = init_repo()?;
+    let commit_id = commit(&repo, &[], &[])?;
+    for idx in 0..50 {
+        std::fs::write(tmp.path().join(format!("file-{idx:02}")), "content\n")?;
+    }
+
+    let options = DiffOptions {
+        max_files: Some(10),
+        ..Default::default()
+    };
+    let diff = gitbutler_diff::workdir_truncated(&repo, commit_id, &options)?;
+    assert_eq!(diff.files.len(), 10);
+    assert!(diff.truncated);
+    assert_eq!(diff.total_files, 50);
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use std::path::{Path, PathBuf};
+
+use crate::{commit, init_repo};
+
+#[test]
+fn two_parent_merge_shows_changes_of_both_sides() -> anyhow::Result<()> {
+    let (_tmp, repo) = init_repo()?;
+    let base = commit(&repo, &[("one", "base\n"), ("two", "base\n")], &[])?;
+    let ours = commit(&repo, &[("one", "ours\n"), ("two", "base\n")], &[base])?;
+    let theirs = commit(&repo, &[("one", "base\n"), ("two", "theirs\n")], &[base])?;
+    let merge = commit(
+        &repo,
+        &[("one", "ours\n"), ("two", "theirs\n"), ("three", "new\n")],
+        &[ours, theirs],
+    )?;
+
+    let diff = gitbutler_diff::against_merge_base(&repo, merge)?;
+    let mut paths: Vec<_> = diff.keys().map(PathBuf::as_path).collect();
+    paths.sort();
+    assert_eq!(
+        paths,
+        [Path::new("one"), Path::new("three"), Path::new("two")],
+        "everything that changed since the merge-base, not only the changes against the first parent"
+    );
+    assert_eq!(
+        *diff[Path::new("one")].hunks[0].diff_lines,
+        "@@ -1 +1 @@\n-base\n+ours\n"
+    );
+
+    let diff = gitbutler_diff::against_merge_base(&repo, ours)?;
+    assert_eq!(
+        diff.keys().map(PathBuf::as_path).collect::<Vec<_>>(),
+        [Path::new("one")],
+        "single-parent commits are diffed against their parent"
+    );
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use std::path::{Path, PathBuf};
+
+use gitbutler_diff::DiffOptions;
+
+use crate::{init_repo, tree_with_modes};
+
+const CONTENT: &[u8] = b"1\n2\n3\n";
+
+#[test]
+fn pure_rename() -> anyhow::Result<()> {
+    let (_tmp, repo) = init_repo()?;
+    let old_tree = tree_with_modes(&repo, &[("old", CONTENT, git2::FileMode::Blob)])?;
+    let new_tree = tree_with_modes(&repo, &[("new", CONTENT, git2::FileMode::Blob)])?;
+
+    let mut options = DiffOptions {
+        renames: true,
+        skip_placeholder_hunks: true,
+        ..Default::default()
+    };
+    let diff = gitbutler_diff::trees_with_options(&repo, &old_tree, &new_tree, true, &options)?;
+    let file = &diff[Path::new("new")];
+    assert_eq!(file.old_path, Some(PathBuf::from("old")));
+    assert!(file.rename_only);
+    assert!(!file.mode_only);
+    assert!(file.hunks.is_empty());
+
+    options.skip_placeholder_hunks = false;
+    let diff = gitbutler_diff::trees_with_options(&repo, &old_tree, &new_tree, true, &options)?;
+    let file = &diff[Path::new("new")];
+    assert!(file.rename_only);
+    assert_eq!(file.hunks.len(), 1, "by default, there is a placeholder");
+    assert!(file.hunks[0].diff_lines.is_empty());
+    Ok(())
+}
+
+#[test]
+fn pure_chmod() -> anyhow::Result<()> {
+    let (_tmp, repo) = init_repo()?;
+    let old_tree = tree_with_modes(&repo, &[("script", CONTENT, git2::FileMode::Blob)])?;
+    let new_tree = tree_with_modes(
+        &repo,
+        &[("script", CONTENT, git2::FileMode::BlobExecutable)],
+    )?;
+
+    let options = DiffOptions {
+        skip_placeholder_hunks: true,
+        ..Default::default()
+    };
+    let diff = gitbutler_diff::trees_with_options(&repo, &old_tree, &new_tree, true, &options)?;
+    let file = &diff[Path::new("script")];
+    assert!(file.mode_only);
+    assert!(!file.rename_only);
+    assert!(file.hunks.is_empty());
+    Ok(())
+}
+
+#[test]
+fn content_changes_are_neither() -> anyhow::Result<()> {
+    let (_tmp, repo) = init_repo()?;
+    let old_tree = tree_with_modes(&repo, &[("script", CONTENT, git2::FileMode::Blob)])?;
+    let new_tree = tree_with_modes(
+        &repo,
+        &[("script", b"1\n2\nthree\n", git2::FileMode::BlobExecutable)],
+    )?;
+
+    let diff = gitbutler_diff::trees(&repo, &old_tree, &new_tree, true)?;
+    let file = &diff[Path::new("script")];
+    assert!(!file.mode_only, "the content changed as well");
+    assert!(!file.rename_only);
+    assert_eq!(file.hunks.len(), 1);
+    Ok(())
+}
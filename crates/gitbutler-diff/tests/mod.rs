@@ -1 +1,89 @@
+pub mod algorithm;
+pub mod anchor;
+pub mod apply;
+pub mod binary;
+pub mod dependencies;
+pub mod directory_summary;
+pub mod expand;
 pub mod hunk;
+pub mod image;
+pub mod interrupt;
+pub mod max_files;
+pub mod merge_base;
+pub mod metadata_only;
+pub mod mode_change;
+pub mod new_file_mode;
+pub mod no_newline;
+pub mod range_diff;
+pub mod recompute;
+pub mod renames;
+pub mod stats;
+pub mod symlinks;
+pub mod too_large;
+
+/// Return a new repository with a worktree in a temporary directory, which is removed when the
+/// returned directory is dropped.
+pub fn init_repo() -> anyhow::Result<(tempfile::TempDir, git2::Repository)> {
+    let tmp = tempfile::tempdir()?;
+    let repo = git2::Repository::init(tmp.path())?;
+    Ok((tmp, repo))
+}
+
+/// Write a tree with the regular `files`, given as `(path, content)`, whose paths may contain directories.
+pub fn tree<'repo>(
+    repo: &'repo git2::Repository,
+    files: &[(&str, &str)],
+) -> anyhow::Result<git2::Tree<'repo>> {
+    let files: Vec<_> = files
+        .iter()
+        .map(|(path, content)| (*path, content.as_bytes(), git2::FileMode::Blob))
+        .collect();
+    tree_with_modes(repo, &files)
+}
+
+/// Write a tree with `files`, given as `(path, content, mode)`, whose paths may contain directories.
+pub fn tree_with_modes<'repo>(
+    repo: &'repo git2::Repository,
+    files: &[(&str, &[u8], git2::FileMode)],
+) -> anyhow::Result<git2::Tree<'repo>> {
+    let mut index = git2::Index::new()?;
+    for (path, content, mode) in files {
+        index.add(&git2::IndexEntry {
+            ctime: git2::IndexTime::new(0, 0),
+            mtime: git2::IndexTime::new(0, 0),
+            dev: 0,
+            ino: 0,
+            mode: (*mode).into(),
+            uid: 0,
+            gid: 0,
+            file_size: content.len() as u32,
+            id: repo.blob(content)?,
+            flags: 0,
+            flags_extended: 0,
+            path: path.as_bytes().to_vec(),
+        })?;
+    }
+    Ok(repo.find_tree(index.write_tree_to(repo)?)?)
+}
+
+/// Commit the regular `files` on top of `parents`, without updating any reference.
+pub fn commit(
+    repo: &git2::Repository,
+    files: &[(&str, &str)],
+    parents: &[git2::Oid],
+) -> anyhow::Result<git2::Oid> {
+    let tree = tree(repo, files)?;
+    let parents = parents
+        .iter()
+        .map(|id| repo.find_commit(*id))
+        .collect::<Result<Vec<_>, _>>()?;
+    let signature = git2::Signature::now("author", "author@example.com")?;
+    Ok(repo.commit(
+        None,
+        &signature,
+        &signature,
+        "message",
+        &tree,
+        &parents.iter().collect::<Vec<_>>(),
+    )?)
+}
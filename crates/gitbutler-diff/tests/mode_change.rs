@@ -0,0 +1,121 @@
+use std::path::Path;
+
+use git2::FileMode;
+use gitbutler_diff::DiffOptions;
+
+use crate::{init_repo, tree_with_modes};
+
+#[test]
+fn executable_bit_without_content_change() -> anyhow::Result<()> {
+    let (_tmp, repo) = init_repo()?;
+    let old_tree = tree_with_modes(&repo, &[("script", b"#!/bin/sh\n", FileMode::Blob)])?;
+    let new_tree = tree_with_modes(
+        &repo,
+        &[("script", b"#!/bin/sh\n", FileMode::BlobExecutable)],
+    )?;
+
+    let diff = gitbutler_diff::trees(&repo, &old_tree, &new_tree, true)?;
+    let file = &diff[Path::new("script")];
+    assert_eq!(
+        file.mode_change,
+        Some((FileMode::Blob, FileMode::BlobExecutable))
+    );
+    assert!(
+        file.hunks.iter().all(|hunk| hunk.diff_lines.is_empty()),
+        "there is no change in content"
+    );
+
+    let diff = gitbutler_diff::trees(&repo, &new_tree, &new_tree, true)?;
+    assert!(diff.is_empty(), "no change, no mode change");
+    Ok(())
+}
+
+#[test]
+fn mode_only_changes_can_be_ignored() -> anyhow::Result<()> {
+    let (_tmp, repo) = init_repo()?;
+    let old_tree = tree_with_modes(
+        &repo,
+        &[
+            ("chmod-only", b"a\n", FileMode::Blob),
+            ("chmod-and-edit", b"a\n", FileMode::Blob),
+        ],
+    )?;
+    let new_tree = tree_with_modes(
+        &repo,
+        &[
+            ("chmod-only", b"a\n", FileMode::BlobExecutable),
+            ("chmod-and-edit", b"b\n", FileMode::BlobExecutable),
+        ],
+    )?;
+
+    let options = DiffOptions {
+        ignore_mode_changes: true,
+        ..Default::default()
+    };
+    let diff = gitbutler_diff::trees_with_options(&repo, &old_tree, &new_tree, true, &options)?;
+    assert!(!diff.contains_key(Path::new("chmod-only")));
+    assert_eq!(
+        diff[Path::new("chmod-and-edit")].mode_change,
+        Some((FileMode::Blob, FileMode::BlobExecutable)),
+        "files with content changes are still reported"
+    );
+
+    let diff = gitbutler_diff::trees(&repo, &old_tree, &new_tree, true)?;
+    assert_eq!(
+        diff[Path::new("chmod-only")].mode_change,
+        Some((FileMode::Blob, FileMode::BlobExecutable)),
+        "without the option, the mode change is reported"
+    );
+    Ok(())
+}
+
+#[cfg(unix)]
+mod worktree {
+    use std::{os::unix::fs::PermissionsExt, path::Path};
+
+    use git2::FileMode;
+
+    use crate::{commit, init_repo};
+
+    /// Commit a script, add it to the index and make it executable in the worktree,
+    /// returning the diff of the worktree after applying `configure` to the repository.
+    fn chmod_x(
+        configure: impl FnOnce(&git2::Repository) -> anyhow::Result<()>,
+    ) -> anyhow::Result<gitbutler_diff::DiffByPathMap> {
+        let (tmp, repo) = init_repo()?;
+        configure(&repo)?;
+        let commit_id = commit(&repo, &[("script", "#!/bin/sh\n")], &[])?;
+        let path = tmp.path().join("script");
+        std::fs::write(&path, "#!/bin/sh\n")?;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644))?;
+        let mut index = repo.index()?;
+        index.add_path(Path::new("script"))?;
+        index.write()?;
+
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+        gitbutler_diff::workdir(&repo, commit_id)
+    }
+
+    #[test]
+    fn executable_bit_flipped() -> anyhow::Result<()> {
+        let diff = chmod_x(|repo| Ok(repo.config()?.set_bool("core.fileMode", true)?))?;
+        let file = &diff[Path::new("script")];
+        assert_eq!(
+            file.mode_change,
+            Some((FileMode::Blob, FileMode::BlobExecutable))
+        );
+        assert!(file.mode_only, "the content didn't change");
+        Ok(())
+    }
+
+    #[test]
+    fn executable_bit_ignored_without_file_mode() -> anyhow::Result<()> {
+        // This is what Git configures on filesystems without an executable bit, like on Windows.
+        let diff = chmod_x(|repo| Ok(repo.config()?.set_bool("core.fileMode", false)?))?;
+        assert!(
+            diff.is_empty(),
+            "without a trustworthy executable bit, there is no change: {diff:?}"
+        );
+        Ok(())
+    }
+}
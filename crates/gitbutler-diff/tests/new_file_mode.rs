@@ -0,0 +1,53 @@
+use std::path::Path;
+
+use gitbutler_diff::{DiffKind, DiffOptions, NewFileMode};
+
+use crate::{init_repo, tree};
+
+#[test]
+fn added_and_deleted_files_are_compact() -> anyhow::Result<()> {
+    let (_tmp, repo) = init_repo()?;
+    let old_tree = tree(&repo, &[("deleted", "gone\n"), ("modified", "a\n")])?;
+    let new_tree = tree(&repo, &[("added", "first\nsecond"), ("modified", "b\n")])?;
+
+    let options = DiffOptions {
+        new_file_mode: NewFileMode::Compact,
+        ..Default::default()
+    };
+    let diff = gitbutler_diff::trees_with_options(&repo, &old_tree, &new_tree, true, &options)?;
+
+    let added = &diff[Path::new("added")];
+    assert_eq!(
+        added.kind,
+        DiffKind::Added {
+            content: "first\nsecond".into(),
+            lines: 2,
+        },
+        "the missing newline at the end is preserved"
+    );
+    assert_eq!(
+        *added.hunks[0].diff_lines,
+        "@@ -0,0 +1,2 @@\n+first\n+second\n\\ No newline at end of file\n",
+        "hunks are still available"
+    );
+    assert_eq!(
+        diff[Path::new("deleted")].kind,
+        DiffKind::Deleted {
+            content: "gone\n".into(),
+            lines: 1,
+        }
+    );
+    assert_eq!(
+        diff[Path::new("modified")].kind,
+        DiffKind::Patch,
+        "modifications are shown as patch"
+    );
+
+    let diff = gitbutler_diff::trees(&repo, &old_tree, &new_tree, true)?;
+    assert_eq!(
+        diff[Path::new("added")].kind,
+        DiffKind::Patch,
+        "it's opt-in"
+    );
+    Ok(())
+}
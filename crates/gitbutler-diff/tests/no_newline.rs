@@ -0,0 +1,29 @@
+use std::path::Path;
+
+use crate::{init_repo, tree};
+
+#[test]
+fn file_loses_its_trailing_newline() -> anyhow::Result<()> {
+    let (_tmp, repo) = init_repo()?;
+    let with_newline = tree(&repo, &[("file", "a\nb\n")])?;
+    let without_newline = tree(&repo, &[("file", "a\nb")])?;
+
+    let diff = gitbutler_diff::trees(&repo, &with_newline, &without_newline, true)?;
+    let file = &diff[Path::new("file")];
+    assert!(!file.no_newline_at_eof_old);
+    assert!(file.no_newline_at_eof_new);
+    assert_eq!(
+        *file.hunks[0].diff_lines, "@@ -1,2 +1,2 @@\n a\n-b\n+b\n\\ No newline at end of file\n",
+        "the marker is rendered after the line lacking the newline"
+    );
+
+    let diff = gitbutler_diff::trees(&repo, &without_newline, &with_newline, true)?;
+    let file = &diff[Path::new("file")];
+    assert!(file.no_newline_at_eof_old);
+    assert!(!file.no_newline_at_eof_new);
+    assert_eq!(
+        *file.hunks[0].diff_lines,
+        "@@ -1,2 +1,2 @@\n a\n-b\n\\ No newline at end of file\n+b\n"
+    );
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use std::path::{Path, PathBuf};
+
+use gitbutler_error::error::{AnyhowContextExt, Code};
+
+use crate::{commit, init_repo};
+
+fn paths(files: &[gitbutler_diff::FileDiff]) -> Vec<&Path> {
+    files.iter().map(|file| file.path.as_path()).collect()
+}
+
+#[test]
+fn two_dot_and_three_dot_on_forked_history() -> anyhow::Result<()> {
+    let (_tmp, repo) = init_repo()?;
+    let base = commit(&repo, &[("one", "base\n"), ("two", "base\n")], &[])?;
+    let target = commit(&repo, &[("one", "target\n"), ("two", "base\n")], &[base])?;
+    let branch = commit(
+        &repo,
+        &[("one", "base\n"), ("three", "new\n"), ("two", "branch\n")],
+        &[base],
+    )?;
+
+    let three_dot = gitbutler_diff::range_diff(&repo, target, branch, true)?;
+    assert_eq!(
+        paths(&three_dot),
+        [Path::new("three"), Path::new("two")],
+        "only the changes of the branch since it forked off, sorted by path"
+    );
+
+    let two_dot = gitbutler_diff::range_diff(&repo, target, branch, false)?;
+    assert_eq!(
+        paths(&two_dot),
+        [Path::new("one"), Path::new("three"), Path::new("two")],
+        "the change on the target shows up as reverted"
+    );
+    assert_eq!(
+        *two_dot[0].hunks[0].diff_lines,
+        "@@ -1 +1 @@\n-target\n+base\n"
+    );
+    assert_ne!(three_dot, two_dot);
+    Ok(())
+}
+
+#[test]
+fn three_dot_needs_a_merge_base() -> anyhow::Result<()> {
+    let (_tmp, repo) = init_repo()?;
+    let a = commit(&repo, &[("file", "a\n")], &[])?;
+    let b = commit(&repo, &[("file", "b\n")], &[])?;
+
+    let err = gitbutler_diff::range_diff(&repo, a, b, true).unwrap_err();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::Validation)
+    );
+    assert_eq!(
+        paths(&gitbutler_diff::range_diff(&repo, a, b, false)?),
+        [PathBuf::from("file")],
+        "unrelated commits can still be compared directly"
+    );
+    Ok(())
+}
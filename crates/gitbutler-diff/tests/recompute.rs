@@ -0,0 +1,43 @@
+use std::path::{Path, PathBuf};
+
+use gitbutler_diff::DiffOptions;
+
+use crate::{commit, init_repo};
+
+#[test]
+fn only_changed_paths_are_recomputed() -> anyhow::Result<()> {
+    let (tmp, repo) = init_repo()?;
+    let commit_id = commit(&repo, &[], &[])?;
+    for name in ["edited", "untouched", "reverted"] {
+        std::fs::write(tmp.path().join(name), "first\n")?;
+    }
+    let options = DiffOptions::default();
+    let previous = gitbutler_diff::workdir_with_options(&repo, commit_id, &options)?;
+
+    std::fs::write(tmp.path().join("edited"), "second\n")?;
+    // Not reported as changed, so the previous result must be kept.
+    std::fs::write(tmp.path().join("untouched"), "second\n")?;
+    std::fs::remove_file(tmp.path().join("reverted"))?;
+    let diff = gitbutler_diff::recompute_paths(
+        &repo,
+        commit_id,
+        &previous,
+        &[PathBuf::from("edited"), PathBuf::from("reverted")],
+        &options,
+    )?;
+
+    assert_eq!(
+        *diff[Path::new("edited")].hunks[0].diff_lines,
+        "@@ -0,0 +1 @@\n+second\n"
+    );
+    assert_eq!(
+        diff[Path::new("untouched")],
+        previous[Path::new("untouched")],
+        "files that weren't reported as changed are reused"
+    );
+    assert!(
+        !diff.contains_key(Path::new("reverted")),
+        "files without changes are dropped"
+    );
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use std::path::{Path, PathBuf};
+
+use gitbutler_diff::DiffOptions;
+
+use crate::{init_repo, tree};
+
+#[test]
+fn renamed_and_edited_file_is_a_single_file_diff() -> anyhow::Result<()> {
+    let (_tmp, repo) = init_repo()?;
+    let old_tree = tree(&repo, &[("old", "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n")])?;
+    let new_tree = tree(&repo, &[("new", "1\n2\n3\n4\nfive\n6\n7\n8\n9\n10\n")])?;
+
+    let diff = gitbutler_diff::trees_with_options(
+        &repo,
+        &old_tree,
+        &new_tree,
+        true,
+        &DiffOptions {
+            renames: true,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(diff.len(), 1, "there is no deletion and addition");
+    let file = &diff[Path::new("new")];
+    assert_eq!(file.old_path, Some(PathBuf::from("old")));
+    assert_eq!(file.hunks.len(), 1);
+    let hunk = &file.hunks[0];
+    assert_eq!((hunk.old_start, hunk.old_lines), (2, 7));
+    assert_eq!((hunk.new_start, hunk.new_lines), (2, 7));
+    assert_eq!(
+        hunk.diff_lines.to_string(),
+        "@@ -2,7 +2,7 @@\n 2\n 3\n 4\n-5\n+five\n 6\n 7\n 8\n",
+        "the hunks are between the old and the new version"
+    );
+
+    let diff = gitbutler_diff::trees(&repo, &old_tree, &new_tree, true)?;
+    assert_eq!(diff.len(), 2, "by default, renames aren't detected");
+    assert_eq!(diff[Path::new("new")].old_path, None);
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use bstr::ByteSlice;
+
+use crate::{init_repo, tree};
+
+#[test]
+fn numbers_match_the_full_diff() -> anyhow::Result<()> {
+    let (_tmp, repo) = init_repo()?;
+    let old = tree(
+        &repo,
+        &[
+            ("binary", "\0one"),
+            ("deleted", "a\nb\n"),
+            ("modified", "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n"),
+        ],
+    )?;
+    let new = tree(
+        &repo,
+        &[
+            ("added", "x\ny\nz\n"),
+            ("binary", "\0two"),
+            ("modified", "1\ntwo\n3\n4\n5\n6\n7\n8\nnine\n10\n11\n"),
+        ],
+    )?;
+
+    let stats = gitbutler_diff::per_file_stats(&repo, &old, &new)?;
+    let full = gitbutler_diff::trees(&repo, &old, &new, true)?;
+    assert_eq!(stats.len(), full.len());
+    for stat in &stats {
+        let file = &full[&stat.path];
+        assert_eq!(stat.binary, file.binary, "{}", stat.path.display());
+        if stat.binary {
+            assert_eq!((stat.insertions, stat.deletions), (0, 0));
+            continue;
+        }
+        let count = |prefix: u8| {
+            file.hunks
+                .iter()
+                .flat_map(|hunk| hunk.diff_lines.lines())
+                .filter(|line| line.first() == Some(&prefix))
+                .count()
+        };
+        assert_eq!(
+            (stat.insertions, stat.deletions),
+            (count(b'+'), count(b'-')),
+            "{}",
+            stat.path.display()
+        );
+    }
+
+    let numbers: Vec<_> = stats
+        .iter()
+        .map(|stat| (stat.path.to_str().unwrap(), stat.insertions, stat.deletions))
+        .collect();
+    assert_eq!(
+        numbers,
+        [
+            ("added", 3, 0),
+            ("binary", 0, 0),
+            ("deleted", 0, 2),
+            ("modified", 3, 2)
+        ]
+    );
+    Ok(())
+}
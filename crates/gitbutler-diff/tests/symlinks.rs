@@ -0,0 +1,52 @@
+use std::path::Path;
+
+use gitbutler_diff::{DiffKind, DiffOptions};
+
+use crate::{init_repo, tree_with_modes};
+
+#[test]
+fn changed_target() -> anyhow::Result<()> {
+    let (_tmp, repo) = init_repo()?;
+    let old_tree = tree_with_modes(&repo, &[("link", b"old/target", git2::FileMode::Link)])?;
+    let new_tree = tree_with_modes(&repo, &[("link", b"new/target", git2::FileMode::Link)])?;
+
+    let options = DiffOptions {
+        symlink_targets: true,
+        ..Default::default()
+    };
+    let diff = gitbutler_diff::trees_with_options(&repo, &old_tree, &new_tree, true, &options)?;
+    assert_eq!(
+        diff[Path::new("link")].kind,
+        DiffKind::Symlink {
+            old_target: Some("old/target".into()),
+            new_target: Some("new/target".into()),
+        }
+    );
+
+    let diff = gitbutler_diff::trees(&repo, &old_tree, &new_tree, true)?;
+    assert_eq!(diff[Path::new("link")].kind, DiffKind::Patch, "it's opt-in");
+    Ok(())
+}
+
+#[test]
+fn symlink_to_file_is_a_type_change() -> anyhow::Result<()> {
+    let (_tmp, repo) = init_repo()?;
+    let old_tree = tree_with_modes(&repo, &[("path", b"target", git2::FileMode::Link)])?;
+    let new_tree = tree_with_modes(&repo, &[("path", b"content\n", git2::FileMode::Blob)])?;
+
+    let options = DiffOptions {
+        symlink_targets: true,
+        ..Default::default()
+    };
+    let diff = gitbutler_diff::trees_with_options(&repo, &old_tree, &new_tree, true, &options)?;
+    assert_eq!(diff.len(), 1);
+    assert_eq!(
+        diff[Path::new("path")].kind,
+        DiffKind::Symlink {
+            old_target: Some("target".into()),
+            new_target: None,
+        },
+        "the file exists on both sides, so it's not a deletion"
+    );
+    Ok(())
+}
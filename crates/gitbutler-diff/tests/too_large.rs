@@ -0,0 +1,80 @@
+use std::path::Path;
+
+use gitbutler_diff::{DiffKind, DiffOptions};
+
+use crate::{commit, init_repo, tree};
+
+#[test]
+fn files_above_the_ceiling_are_not_diffed() -> anyhow::Result<()> {
+    let (_tmp, repo) = init_repo()?;
+    let old_tree = tree(&repo, &[])?;
+    let new_tree = tree(
+        &repo,
+        &[("large", &"line\n".repeat(1000)), ("small", "line\n")],
+    )?;
+
+    let options = DiffOptions {
+        max_diff_size: Some(1000),
+        ..Default::default()
+    };
+    let diff = gitbutler_diff::trees_truncated(&repo, &old_tree, &new_tree, true, &options)?;
+    assert_eq!(diff.total_files, 2);
+    let file = &diff.files[Path::new("large")];
+    assert_eq!(
+        file.kind,
+        DiffKind::TooLarge {
+            old_size: 0,
+            new_size: 5000
+        }
+    );
+    assert!(file.hunks.is_empty(), "the content isn't read");
+    assert!(file.skipped);
+    let file = &diff.files[Path::new("small")];
+    assert_eq!(file.kind, DiffKind::Patch);
+    assert_eq!(*file.hunks[0].diff_lines, "@@ -0,0 +1 @@\n+line\n");
+
+    let diff = gitbutler_diff::trees_truncated(
+        &repo,
+        &old_tree,
+        &new_tree,
+        true,
+        &DiffOptions::default(),
+    )?;
+    assert_eq!(
+        diff.files[Path::new("large")].kind,
+        DiffKind::Patch,
+        "there is no ceiling by default"
+    );
+    Ok(())
+}
+
+#[test]
+fn ceiling_applies_to_the_worktree() -> anyhow::Result<()> {
+    let (tmp, repo) = init_repo()?;
+    let commit_id = commit(&repo, &[("large", &"line\n".repeat(100))], &[])?;
+    std::fs::write(tmp.path().join("large"), "line\n".repeat(200))?;
+    std::fs::write(tmp.path().join("untracked"), "line\n".repeat(300))?;
+
+    let options = DiffOptions {
+        max_diff_size: Some(1000),
+        ..Default::default()
+    };
+    let diff = gitbutler_diff::workdir_truncated(&repo, commit_id, &options)?;
+    assert_eq!(diff.files.len(), 2);
+    assert_eq!(
+        diff.files[Path::new("large")].kind,
+        DiffKind::TooLarge {
+            old_size: 500,
+            new_size: 1000
+        },
+        "both versions count towards the ceiling"
+    );
+    assert_eq!(
+        diff.files[Path::new("untracked")].kind,
+        DiffKind::TooLarge {
+            old_size: 0,
+            new_size: 1500
+        }
+    );
+    Ok(())
+}
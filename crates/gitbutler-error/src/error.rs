@@ -134,6 +134,14 @@ pub enum Code {
     CommitMergeConflictFailure,
     ProjectMissing,
     AuthorMissing,
+    ProjectCheckoutWouldOverwrite,
+    ProjectGitSizeLimit,
+    ProjectDirtyWorktree,
+    ProjectGitTimeout,
+    ProjectRefExists,
+    ProjectGitLocked,
+    ProjectGitServerRefused,
+    Cancelled,
 }
 
 impl std::fmt::Display for Code {
@@ -147,6 +155,14 @@ impl std::fmt::Display for Code {
             Code::CommitMergeConflictFailure => "errors.commit.merge_conflict_failure",
             Code::AuthorMissing => "errors.git.author_missing",
             Code::ProjectMissing => "errors.projects.missing",
+            Code::ProjectCheckoutWouldOverwrite => "errors.projects.checkout.would_overwrite",
+            Code::ProjectGitSizeLimit => "errors.projects.git.size_limit",
+            Code::ProjectDirtyWorktree => "errors.projects.worktree.dirty",
+            Code::ProjectGitTimeout => "errors.projects.git.timeout",
+            Code::ProjectRefExists => "errors.projects.ref.exists",
+            Code::ProjectGitLocked => "errors.projects.git.locked",
+            Code::ProjectGitServerRefused => "errors.projects.git.server_refused",
+            Code::Cancelled => "errors.cancelled",
         };
         f.write_str(code)
     }
@@ -263,3 +279,111 @@ impl std::fmt::Display for Marker {
         }
     }
 }
+
+/// The results of an operation on multiple items, like pushing multiple branches, which may
+/// succeed for some items and fail for others.
+///
+/// The errors are `anyhow::Error` by default, but may also carry the item they belong to.
+///
+/// ```rust
+///# use anyhow::anyhow;
+///# use gitbutler_error::error::PartialOutcome;
+/// let outcome: PartialOutcome<&str> = [Ok("a"), Err(anyhow!("b was rejected")), Ok("c")]
+///     .into_iter()
+///     .collect();
+/// assert_eq!(outcome.succeeded().collect::<Vec<_>>(), [&"a", &"c"]);
+/// assert_eq!(outcome.failed().map(ToString::to_string).collect::<Vec<_>>(), ["b was rejected"]);
+///
+/// let outcome = outcome.into_result().expect("it only fails if nothing succeeded");
+/// let (succeeded, failed) = outcome.into_parts();
+/// assert_eq!((succeeded.len(), failed.len()), (2, 1));
+///
+/// let outcome: PartialOutcome<()> = [Err(anyhow!("a")), Err(anyhow!("b"))].into_iter().collect();
+/// assert_eq!(outcome.into_result().unwrap_err().to_string(), "a");
+///
+/// let outcome: PartialOutcome<&str, (&str, anyhow::Error)> =
+///     [Ok("a"), Err(("b", anyhow!("rejected")))].into_iter().collect();
+/// assert_eq!(
+///     outcome.into_iter().map(|result| result.map_err(|(item, _err)| item)).collect::<Vec<_>>(),
+///     [Ok("a"), Err("b")],
+///     "all results can be visited in order"
+/// );
+/// ```
+#[derive(Debug)]
+pub struct PartialOutcome<T, E = anyhow::Error> {
+    results: Vec<Result<T, E>>,
+}
+
+impl<T, E> Default for PartialOutcome<T, E> {
+    fn default() -> Self {
+        PartialOutcome {
+            results: Vec::new(),
+        }
+    }
+}
+
+impl<T, E> FromIterator<Result<T, E>> for PartialOutcome<T, E> {
+    fn from_iter<I: IntoIterator<Item = Result<T, E>>>(iter: I) -> Self {
+        PartialOutcome {
+            results: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<T, E> IntoIterator for PartialOutcome<T, E> {
+    type Item = Result<T, E>;
+    type IntoIter = std::vec::IntoIter<Result<T, E>>;
+
+    /// Return the results of all items in the order they were recorded.
+    fn into_iter(self) -> Self::IntoIter {
+        self.results.into_iter()
+    }
+}
+
+impl<T, E> PartialOutcome<T, E> {
+    /// Record the `result` of the operation on the next item.
+    pub fn push(&mut self, result: Result<T, E>) {
+        self.results.push(result);
+    }
+
+    /// Return the values of all items the operation succeeded for, in the order they were recorded.
+    pub fn succeeded(&self) -> impl Iterator<Item = &T> {
+        self.results
+            .iter()
+            .filter_map(|result| result.as_ref().ok())
+    }
+
+    /// Return the errors of all items the operation failed for, in the order they were recorded.
+    pub fn failed(&self) -> impl Iterator<Item = &E> {
+        self.results
+            .iter()
+            .filter_map(|result| result.as_ref().err())
+    }
+
+    /// Return `true` if the operation failed for at least one item.
+    pub fn has_failures(&self) -> bool {
+        self.failed().next().is_some()
+    }
+
+    /// Fail with the first error as is if the operation failed for all items, or return `self`
+    /// otherwise, which also is the case if there were no items.
+    pub fn into_result(mut self) -> Result<Self, E> {
+        if self.results.is_empty() || self.results.iter().any(Result::is_ok) {
+            return Ok(self);
+        }
+        Err(self.results.swap_remove(0).err().expect("all are errors"))
+    }
+
+    /// Split the outcome into the values of successful items and the errors of failed ones.
+    pub fn into_parts(self) -> (Vec<T>, Vec<E>) {
+        let mut succeeded = Vec::new();
+        let mut failed = Vec::new();
+        for result in self.results {
+            match result {
+                Ok(value) => succeeded.push(value),
+                Err(err) => failed.push(err),
+            }
+        }
+        (succeeded, failed)
+    }
+}
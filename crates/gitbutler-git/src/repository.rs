@@ -336,6 +336,9 @@ where
 /// Any prompts for the user are passed to the asynchronous callback `on_prompt`,
 /// which should return the user's response or `None` if the operation should be
 /// aborted, in which case an `Err` value is returned from this function.
+///
+/// Returns `true` if the remote ref already pointed to the pushed commit, so nothing was pushed,
+/// along with what `git` printed to stderr, which includes the messages of the remote.
 pub async fn push<P, F, Fut, E, Extra>(
     repo_path: P,
     executor: E,
@@ -344,7 +347,7 @@ pub async fn push<P, F, Fut, E, Extra>(
     force: bool,
     on_prompt: F,
     extra: Extra,
-) -> Result<(), crate::Error<Error<E>>>
+) -> Result<(bool, String), crate::Error<Error<E>>>
 where
     P: AsRef<Path>,
     E: GitExecutor,
@@ -352,7 +355,9 @@ where
     Fut: std::future::Future<Output = Option<String>>,
     Extra: Send + Clone,
 {
-    let mut args = vec!["push", "--quiet", "--no-verify"];
+    // The status of each ref is only printed without `--quiet`, and in a parseable format
+    // with `--porcelain`.
+    let mut args = vec!["push", "--porcelain", "--no-verify"];
 
     let refspec = refspec.to_string();
 
@@ -367,7 +372,15 @@ where
         execute_with_auth_harness(repo_path, &executor, &args, None, on_prompt, extra).await?;
 
     if status == 0 {
-        Ok(())
+        // Each ref is printed like `=\trefs/heads/main:refs/heads/main\t[up to date]`, with `=`
+        // indicating that it was up to date.
+        let mut ref_flags = stdout
+            .lines()
+            .filter(|line| line.contains('\t'))
+            .map(|line| line.chars().next());
+        let first_flag = ref_flags.next();
+        let up_to_date = first_flag == Some(Some('=')) && ref_flags.all(|flag| flag == Some('='));
+        Ok((up_to_date, stderr))
     } else {
         // Was the ref not found?
         if let Some(refname) = stderr
@@ -142,6 +142,12 @@ pub trait OplogExt {
     /// This is useful to show what has changed in this particular snapshot
     fn snapshot_diff(&self, sha: git2::Oid) -> Result<HashMap<PathBuf, FileDiff>>;
 
+    /// Returns the diff of the working directory captured by the snapshot `a` to the one captured by the
+    /// snapshot `b`, sorted by path. Like [`snapshot_diff`](Self::snapshot_diff), it only includes the workdir changes.
+    ///
+    /// This is useful to review what one or more operations did before keeping or undoing them.
+    fn diff_snapshots(&self, a: git2::Oid, b: git2::Oid) -> Result<Vec<FileDiff>>;
+
     /// Gets the sha of the last snapshot commit if present.
     fn oplog_head(&self) -> Result<Option<git2::Oid>>;
 }
@@ -319,32 +325,15 @@ impl OplogExt for Project {
     }
 
     fn snapshot_diff(&self, sha: git2::Oid) -> Result<HashMap<PathBuf, FileDiff>> {
-        let worktree_dir = self.path.as_path();
-        let gix_repo = gitbutler_command_context::gix_repository_for_merging(worktree_dir)?;
-        let repo = git2::Repository::init(worktree_dir)?;
-
-        let commit = repo.find_commit(sha)?;
-
-        let wd_tree_id = tree_from_applied_vbranches(&gix_repo, commit.id())?;
-        let wd_tree = repo.find_tree(wd_tree_id)?;
-        let old_wd_tree_id = tree_from_applied_vbranches(&gix_repo, commit.parent(0)?.id())?;
-        let old_wd_tree = repo.find_tree(old_wd_tree_id)?;
-
-        repo.ignore_large_files_in_diffs(AUTO_TRACK_LIMIT_BYTES)?;
-
-        let mut diff_opts = git2::DiffOptions::new();
-        diff_opts
-            .recurse_untracked_dirs(true)
-            .include_untracked(true)
-            .show_binary(true)
-            .ignore_submodules(true)
-            .show_untracked_content(true);
-
-        let diff =
-            repo.diff_tree_to_tree(Some(&old_wd_tree), Some(&wd_tree), Some(&mut diff_opts))?;
+        let repo = git2::Repository::open(&self.path)?;
+        let parent_id = repo.find_commit(sha)?.parent_id(0)?;
+        diff_snapshot_worktrees(self, parent_id, sha)
+    }
 
-        let hunks = hunks_by_filepath(None, &diff)?;
-        Ok(hunks)
+    fn diff_snapshots(&self, a: git2::Oid, b: git2::Oid) -> Result<Vec<FileDiff>> {
+        let mut files: Vec<_> = diff_snapshot_worktrees(self, a, b)?.into_values().collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(files)
     }
 
     /// Gets the sha of the last snapshot commit if present.
@@ -354,6 +343,37 @@ impl OplogExt for Project {
     }
 }
 
+/// Diff the working directory captured by the snapshot commit `old` to the one captured by `new`.
+fn diff_snapshot_worktrees(
+    project: &Project,
+    old: git2::Oid,
+    new: git2::Oid,
+) -> Result<HashMap<PathBuf, FileDiff>> {
+    let worktree_dir = project.path.as_path();
+    let gix_repo = gitbutler_command_context::gix_repository_for_merging(worktree_dir)?;
+    let repo = git2::Repository::init(worktree_dir)?;
+
+    let wd_tree_id = tree_from_applied_vbranches(&gix_repo, new)?;
+    let wd_tree = repo.find_tree(wd_tree_id)?;
+    let old_wd_tree_id = tree_from_applied_vbranches(&gix_repo, old)?;
+    let old_wd_tree = repo.find_tree(old_wd_tree_id)?;
+
+    repo.ignore_large_files_in_diffs(AUTO_TRACK_LIMIT_BYTES)?;
+
+    let mut diff_opts = git2::DiffOptions::new();
+    diff_opts
+        .recurse_untracked_dirs(true)
+        .include_untracked(true)
+        .show_binary(true)
+        .ignore_submodules(true)
+        .show_untracked_content(true);
+
+    let diff = repo.diff_tree_to_tree(Some(&old_wd_tree), Some(&wd_tree), Some(&mut diff_opts))?;
+
+    let hunks = hunks_by_filepath(None, &diff)?;
+    Ok(hunks)
+}
+
 /// Get a tree of the working dir (applied branches merged)
 fn get_workdir_tree(
     wd_trees_cache: Option<&mut HashMap<gix::ObjectId, gix::ObjectId>>,
@@ -0,0 +1,58 @@
+//! Classify fetches that the remote refused to serve, which unlike failures of the transport
+//! won't succeed when retried.
+use gitbutler_error::error::{Code, Context};
+
+/// Phrases in the errors of `git` and `git2` that indicate that the remote refused a fetch, in lower-case.
+const REFUSAL_PHRASES: &[&str] = &[
+    // An `ERR` packet sent during the negotiation, with the reason of the remote.
+    "remote error:",
+    // Shallow fetches and unshallowing aren't allowed.
+    "does not support shallow",
+    "does not support --deepen",
+    "does not support --shallow",
+    // Objects that aren't advertised, like when deepening a restricted repository.
+    "not our ref",
+    "does not allow request for unadvertised object",
+];
+
+/// Return the message of the remote if `text`, the error of a fetch, says that the remote refused
+/// to serve it, or `None` if it failed for another reason, like the connection.
+pub fn server_refusal(text: &str) -> Option<String> {
+    text.lines().find_map(|line| {
+        let lowercase = line.to_ascii_lowercase();
+        let phrase = REFUSAL_PHRASES
+            .iter()
+            .find(|phrase| lowercase.contains(*phrase))?;
+        let message = match phrase.strip_suffix(':') {
+            // Only what follows is from the remote.
+            Some(_) => &line[lowercase.find(phrase)? + phrase.len()..],
+            None => line,
+        };
+        let message = message.trim_start_matches("remote:").trim();
+        let message = message
+            .strip_prefix("fatal:")
+            .or_else(|| message.strip_prefix("error:"))
+            .unwrap_or(message)
+            .trim();
+        Some(message.to_owned())
+    })
+}
+
+/// Return the error for a fetch the remote refused with `message`, as returned by [`server_refusal()`],
+/// which is classified as [`Code::ProjectGitServerRefused`].
+pub fn server_refused_error(message: String) -> anyhow::Error {
+    let context = Context::new(format!(
+        "The remote refused to serve the fetch, which is likely a limitation of the repository or the hosting provider rather than a network problem: {message}"
+    ))
+    .with_code(Code::ProjectGitServerRefused);
+    anyhow::Error::msg(format!("remote refused fetch: {message}")).context(context)
+}
+
+/// Classify `err`, the error of a fetch, as [refused by the remote](server_refused_error()) if its
+/// message says so, or return it unchanged.
+pub(crate) fn classify(err: anyhow::Error) -> anyhow::Error {
+    match server_refusal(&format!("{err:#}")) {
+        Some(message) => server_refused_error(message),
+        None => err,
+    }
+}
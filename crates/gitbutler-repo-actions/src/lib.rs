@@ -2,3 +2,19 @@ pub mod askpass;
 
 mod repository;
 pub use repository::RepoActionsExt;
+
+mod push;
+pub use push::{remote_messages, PushError, PushOutcome};
+
+mod refspec;
+pub use refspec::parse_refspec;
+
+pub mod fetch;
+
+pub mod proxy;
+pub use proxy::ProxyConfig;
+
+pub mod remote_refs;
+
+pub mod remote_op;
+pub use remote_op::{RemoteError, RemoteOp};
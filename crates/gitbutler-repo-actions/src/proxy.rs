@@ -0,0 +1,109 @@
+use anyhow::Result;
+use gitbutler_error::error::Code;
+
+/// How to reach remotes over HTTP(S) when pushing or fetching.
+///
+/// It's stored as `http.proxy` in the local Git configuration of the repository, so the `git`
+/// executable, if used as transport, will do the same.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ProxyConfig {
+    /// Use the proxy configured in Git, via `remote.<name>.proxy` or `http.proxy`,
+    /// or in the environment, via `https_proxy`, `http_proxy` or `all_proxy`.
+    #[default]
+    Auto,
+    /// Connect directly, even if a proxy is configured elsewhere.
+    None,
+    /// Always use the proxy at the given URL.
+    Specified(String),
+}
+
+const PROXY_KEY: &str = "http.proxy";
+
+/// Configure `repo` to use `proxy` for all future pushes and fetches.
+pub fn set_proxy(repo: &git2::Repository, proxy: &ProxyConfig) -> Result<()> {
+    let mut config = repo.config()?.open_level(git2::ConfigLevel::Local)?;
+    match proxy {
+        ProxyConfig::Auto => match config.remove(PROXY_KEY) {
+            Err(err) if err.code() != git2::ErrorCode::NotFound => return Err(err.into()),
+            _ => {}
+        },
+        // Git disables proxying if the value is empty.
+        ProxyConfig::None => config.set_str(PROXY_KEY, "")?,
+        ProxyConfig::Specified(url) => config.set_str(PROXY_KEY, url)?,
+    }
+    Ok(())
+}
+
+/// Return the proxy configuration as previously set with [`set_proxy()`].
+pub fn proxy(repo: &git2::Repository) -> Result<ProxyConfig> {
+    let config = repo.config()?.open_level(git2::ConfigLevel::Local)?;
+    Ok(match config.get_string(PROXY_KEY) {
+        Ok(url) if url.is_empty() => ProxyConfig::None,
+        Ok(url) => ProxyConfig::Specified(url),
+        Err(err) if err.code() == git2::ErrorCode::NotFound => ProxyConfig::Auto,
+        Err(err) => return Err(err.into()),
+    })
+}
+
+impl ProxyConfig {
+    /// Return the URL of the proxy to use when connecting to the remote named `remote_name` in `repo`,
+    /// or `None` if the connection should be direct.
+    pub fn url(&self, repo: &git2::Repository, remote_name: &str) -> Result<Option<String>> {
+        Ok(match self {
+            ProxyConfig::None => None,
+            ProxyConfig::Specified(url) => Some(url.clone()),
+            ProxyConfig::Auto => {
+                let config = repo.config()?.snapshot()?;
+                let configured = [format!("remote.{remote_name}.proxy"), PROXY_KEY.into()]
+                    .iter()
+                    .find_map(|key| config.get_string(key).ok());
+                match configured {
+                    Some(url) => (!url.is_empty()).then_some(url),
+                    None => [
+                        "https_proxy",
+                        "HTTPS_PROXY",
+                        "http_proxy",
+                        "all_proxy",
+                        "ALL_PROXY",
+                    ]
+                    .into_iter()
+                    .find_map(|var| std::env::var(var).ok().filter(|url| !url.is_empty())),
+                }
+            }
+        })
+    }
+}
+
+/// Return the proxy options to use when connecting to `remote_name` in `repo`.
+pub(crate) fn proxy_options(
+    repo: &git2::Repository,
+    remote_name: &str,
+) -> Result<git2::ProxyOptions<'static>> {
+    let mut opts = git2::ProxyOptions::new();
+    if let Some(url) = proxy(repo)?.url(repo, remote_name)? {
+        opts.url(&url);
+    }
+    Ok(opts)
+}
+
+/// Return `true` if `err` indicates that the proxy didn't accept our credentials, which is when it
+/// responded with `407 Proxy Authentication Required`.
+///
+/// Other failures involving the proxy, like not being able to connect to it, are network problems
+/// that credentials can't fix.
+pub fn is_proxy_auth_error(err: &git2::Error) -> bool {
+    if err.class() != git2::ErrorClass::Http {
+        return false;
+    }
+    let message = err.message().to_lowercase();
+    message.contains("proxy authentication required")
+        || ["status code: 407", "status: 407", "http code 407"]
+            .iter()
+            .any(|status| message.contains(status))
+}
+
+/// The error to return if all attempts to connect failed as the proxy refused to let us through.
+pub(crate) fn proxy_auth_error() -> anyhow::Error {
+    anyhow::anyhow!("Authentication with the proxy failed - check the credentials in the proxy URL")
+        .context(Code::ProjectGitAuth)
+}
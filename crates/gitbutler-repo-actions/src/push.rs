@@ -0,0 +1,189 @@
+use std::fmt;
+
+use gitbutler_error::error::{Code, Context};
+
+/// What a successful push did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PushOutcome {
+    /// The remote was updated, and printed these messages while doing so, see [`remote_messages()`].
+    Pushed(Vec<String>),
+    /// The remote branch already pointed to the pushed commit, so nothing was transferred.
+    UpToDate,
+}
+
+/// The ways a push can fail that the frontend may want to react to.
+///
+/// Each variant knows its own classification, see [`PushError::into_context()`], which is
+/// attached when converting into an `anyhow::Error`.
+#[derive(Debug)]
+pub enum PushError {
+    /// None of the available credentials were accepted by the remote.
+    AuthenticationFailed,
+    /// The local ref that was supposed to be pushed couldn't be found.
+    RefNotFound(String),
+    /// The remote declined to update `refname`, and told us why in `reason`.
+    Rejected { refname: String, reason: String },
+    /// The remote refused the push as it exceeds a size limit of the hosting provider,
+    /// like a file that is too large, which is at `path` if the remote told us.
+    SizeLimitExceeded { path: Option<String> },
+    /// Anything we don't know how to classify, passed on as is.
+    Other(anyhow::Error),
+}
+
+impl PushError {
+    /// Classify the rejection of `refname` for `reason`, using the messages the remote sent
+    /// while processing the push to detect known causes.
+    pub fn rejected(refname: String, reason: String, remote_messages: &str) -> Self {
+        match size_limit_exceeded(&reason).or_else(|| size_limit_exceeded(remote_messages)) {
+            Some(path) => PushError::SizeLimitExceeded { path },
+            None => PushError::Rejected { refname, reason },
+        }
+    }
+
+    /// Return the context to attach to this error so the frontend can classify it.
+    pub fn into_context(&self) -> Context {
+        match self {
+            PushError::AuthenticationFailed => Context::new_static(
+                Code::ProjectGitAuth,
+                "Authentication failed - none of the configured credentials were accepted by the remote",
+            ),
+            PushError::RefNotFound(refname) => {
+                Context::new(format!("The reference '{refname}' to push could not be found"))
+                    .with_code(Code::Validation)
+            }
+            PushError::Rejected { refname, reason } => {
+                Context::new(format!("The remote rejected the update of '{refname}': {reason}"))
+            }
+            PushError::SizeLimitExceeded { path } => Context::new(match path {
+                Some(path) => format!(
+                    "The remote refused the push as '{path}' exceeds its file size limit - consider storing it with Git LFS or removing it from history"
+                ),
+                None => "The remote refused the push as it exceeds its size limit - consider storing large files with Git LFS or removing them from history".into(),
+            })
+            .with_code(Code::ProjectGitSizeLimit),
+            PushError::Other(_) => Context::default(),
+        }
+    }
+}
+
+impl fmt::Display for PushError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PushError::AuthenticationFailed => f.write_str("authentication failed"),
+            PushError::RefNotFound(refname) => write!(f, "a ref-spec was not found: {refname}"),
+            PushError::Rejected { refname, reason } => {
+                write!(f, "remote rejected {refname}: {reason}")
+            }
+            PushError::SizeLimitExceeded { path: Some(path) } => {
+                write!(f, "size limit exceeded by {path}")
+            }
+            PushError::SizeLimitExceeded { path: None } => f.write_str("size limit exceeded"),
+            PushError::Other(err) => fmt::Display::fmt(err, f),
+        }
+    }
+}
+
+impl From<PushError> for anyhow::Error {
+    fn from(err: PushError) -> Self {
+        let context = err.into_context();
+        match err {
+            // Keep the original chain, there is nothing we could add to it.
+            PushError::Other(err) => err,
+            err => anyhow::Error::msg(err.to_string()).context(context),
+        }
+    }
+}
+
+impl<E> From<gitbutler_git::Error<E>> for PushError
+where
+    E: std::error::Error + core::fmt::Debug + Send + Sync + 'static,
+{
+    fn from(err: gitbutler_git::Error<E>) -> Self {
+        match err {
+            gitbutler_git::Error::RefNotFound(refname) => PushError::RefNotFound(refname),
+            gitbutler_git::Error::AuthorizationFailed(_) => PushError::AuthenticationFailed,
+            // The output of `git` is only available as part of the message.
+            err => match size_limit_exceeded(&err.to_string()) {
+                Some(path) => PushError::SizeLimitExceeded { path },
+                None => PushError::Other(err.into()),
+            },
+        }
+    }
+}
+
+/// Phrases used by hosting providers when refusing pushes that are too large, in lower-case.
+const SIZE_LIMIT_PHRASES: &[&str] = &[
+    // GitHub
+    "exceeds github's file size limit",
+    "gh001: large files detected",
+    // GitLab
+    "is larger than the allowed size",
+    // Bitbucket
+    "includes at least one file that is",
+    // Any provider, or a proxy in front of it.
+    "pack exceeds maximum allowed size",
+    "http 413",
+];
+
+/// Return `Some` if `text` indicates that a size limit was exceeded, along with the path of the
+/// offending file if it's mentioned in a line like `File <path> is 123.00 MB`.
+fn size_limit_exceeded(text: &str) -> Option<Option<String>> {
+    let lowercase = text.to_lowercase();
+    if !SIZE_LIMIT_PHRASES
+        .iter()
+        .any(|phrase| lowercase.contains(phrase))
+    {
+        return None;
+    }
+    let path = text.lines().find_map(|line| {
+        let line = line.trim_start_matches("remote:").trim();
+        let line = line.strip_prefix("error:").unwrap_or(line).trim_start();
+        let (path, _size) = line.strip_prefix("File ")?.split_once(" is ")?;
+        Some(path.trim_matches('"').to_owned())
+    });
+    Some(path)
+}
+
+/// Turn the `output` a remote sent while processing a push into the messages it has for the user,
+/// like the URL to create a pull request, one per line.
+///
+/// ANSI escape sequences and progress reports are removed, as well as the `remote:` prefix
+/// if `output` was printed by `git`.
+pub fn remote_messages(output: &str) -> Vec<String> {
+    strip_ansi_escapes(output)
+        .lines()
+        .filter_map(|line| {
+            // Progress is updated by returning the carriage, only the last update is visible.
+            let line = line.rsplit('\r').find(|part| !part.trim().is_empty())?;
+            let line = line.strip_prefix("remote:").unwrap_or(line).trim();
+            (!line.is_empty() && !is_progress(line)).then(|| line.to_owned())
+        })
+        .collect()
+}
+
+/// Return `true` if `line` looks like `Resolving deltas: 100% (3/3), done.`
+fn is_progress(line: &str) -> bool {
+    line.split_once(": ").is_some_and(|(_, status)| {
+        status.starts_with(|c: char| c.is_ascii_digit())
+            && status
+                .split_once('%')
+                .is_some_and(|(percentage, _)| percentage.chars().all(|c| c.is_ascii_digit()))
+    })
+}
+
+fn strip_ansi_escapes(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            // Control sequences end with a byte in the range `@` to `~`.
+            Some('[') => for _ in chars.by_ref().take_while(|c| !('@'..='~').contains(c)) {},
+            Some(_) | None => {}
+        }
+    }
+    out
+}
@@ -0,0 +1,60 @@
+use anyhow::{anyhow, Result};
+use gitbutler_error::error::Code;
+use gitbutler_git::RefSpec;
+
+/// Parse `spec`, a refspec entered by the user like `+refs/heads/*:refs/remotes/origin/*`, and
+/// validate that it can be used for pushing or fetching.
+///
+/// A leading `+` sets [`RefSpec::update_non_fastforward`] to force the update. Like with `git push`,
+/// a missing destination is the same as the source, and a missing source deletes the destination.
+/// Each side must be a valid reference name, which may be abbreviated like `main`, and the source
+/// may also be a full commit hash.
+///
+/// Fails with [`Code::Validation`] and a message describing the problem if `spec` is malformed.
+pub fn parse_refspec(spec: &str) -> Result<RefSpec> {
+    let invalid = |reason: String| {
+        anyhow!("The refspec '{spec}' is invalid: {reason}").context(Code::Validation)
+    };
+    let refspec = RefSpec::parse(spec.trim()).map_err(|err| invalid(err.to_string()))?;
+    let (source, destination) = (refspec.source.as_deref(), refspec.destination.as_deref());
+    if source.is_none() && destination.is_none() {
+        return Err(invalid("it has neither source nor destination".into()));
+    }
+    if let Some(source) = source {
+        if source.len() == 40 && git2::Oid::from_str(source).is_ok() {
+            if destination == Some(source) {
+                return Err(invalid("a commit hash needs a destination".into()));
+            }
+        } else {
+            validate_side("source", source).map_err(invalid)?;
+        }
+    }
+    if let Some(destination) = destination {
+        validate_side("destination", destination).map_err(invalid)?;
+    }
+    let is_glob = |side: Option<&str>| side.is_some_and(|side| side.contains('*'));
+    if is_glob(source) != is_glob(destination) {
+        return Err(invalid(
+            "a pattern needs a `*` on both the source and destination".into(),
+        ));
+    }
+    Ok(refspec)
+}
+
+/// Return why `name` isn't a valid reference name or pattern for the `side` of a refspec.
+fn validate_side(side: &str, name: &str) -> std::result::Result<(), String> {
+    if name.matches('*').count() > 1 {
+        return Err(format!("the {side} '{name}' may only contain a single `*`"));
+    }
+    // Patterns match any valid name, so validate one of them.
+    let name_to_validate = name.replace('*', "pattern");
+    let full_name = if name_to_validate.starts_with("refs/") || name_to_validate == "HEAD" {
+        name_to_validate
+    } else {
+        format!("refs/heads/{name_to_validate}")
+    };
+    if !git2::Reference::is_valid_name(&full_name) {
+        return Err(format!("the {side} '{name}' isn't a valid reference name"));
+    }
+    Ok(())
+}
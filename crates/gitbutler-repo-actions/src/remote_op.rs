@@ -0,0 +1,277 @@
+//! Enforce a timeout and cancellation on operations that talk to remotes, like fetching, pushing
+//! or listing refs, which may otherwise hang if the network or the remote stalls.
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt,
+    future::Future,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, LazyLock, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use gitbutler_error::error::{Code, Context};
+
+/// A handle to cancel a [`RemoteOp`] from another thread, with all clones sharing the same state.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Abort all operations run with this token at their next progress report.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Return `true` if [`cancel()`](Self::cancel()) was called.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// The message of the errors returned to `git2` from the callbacks of aborted operations, which
+/// [`RemoteOp::run()`] replaces with the reason for aborting them.
+pub(crate) const ABORTED: &str = "aborted by the remote operation guard";
+
+/// The ways a [`RemoteOp`] can fail.
+///
+/// Timeouts and cancellations are turned into an `anyhow::Error` carrying the [`Code`] of
+/// [`RemoteError::into_context()`], so the frontend can tell them apart from the network or the
+/// remote failing.
+#[derive(Debug)]
+pub enum RemoteError {
+    /// The operation was aborted as it took longer than the given timeout.
+    TimedOut(Duration),
+    /// The operation was aborted through its [`CancellationToken`].
+    Cancelled,
+    /// The operation failed by itself, passed on as is.
+    Other(anyhow::Error),
+}
+
+impl RemoteError {
+    /// Return the context to attach to this error so the frontend can classify it.
+    pub fn into_context(&self) -> Context {
+        match self {
+            RemoteError::TimedOut(timeout) => Context::new(format!(
+                "The remote didn't respond within {} seconds - check your network connection and try again",
+                timeout.as_secs_f32()
+            ))
+            .with_code(Code::ProjectGitTimeout),
+            RemoteError::Cancelled => {
+                Context::new("The remote operation was cancelled").with_code(Code::Cancelled)
+            }
+            RemoteError::Other(_) => Context::default(),
+        }
+    }
+}
+
+impl fmt::Display for RemoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemoteError::TimedOut(timeout) => write!(f, "timed out after {timeout:?}"),
+            RemoteError::Cancelled => f.write_str("cancelled"),
+            RemoteError::Other(err) => fmt::Display::fmt(err, f),
+        }
+    }
+}
+
+impl From<RemoteError> for anyhow::Error {
+    fn from(err: RemoteError) -> Self {
+        let context = err.into_context();
+        match err {
+            // The operation already described its own failure.
+            RemoteError::Other(err) => err,
+            err => anyhow::Error::msg(err.to_string()).context(context),
+        }
+    }
+}
+
+/// Runs an operation on a remote, aborting it once it exceeds its timeout or is cancelled.
+///
+/// The limits are enforced by the callbacks `git2` invokes while transferring, see
+/// [`RemoteOpGuard::install()`], so they apply to all operations the same way. Operations using
+/// the `git` executable are checked regularly instead, and killed once they are aborted.
+#[derive(Debug, Clone, Default)]
+pub struct RemoteOp {
+    timeout: Option<Duration>,
+    cancellation: CancellationToken,
+}
+
+impl RemoteOp {
+    /// Create a runner without timeout that can only be cancelled through [`cancellation()`](Self::cancellation()).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Abort operations that take longer than `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Abort operations once `token` is cancelled.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// Return the token to cancel operations run by this instance.
+    pub fn cancellation(&self) -> &CancellationToken {
+        &self.cancellation
+    }
+
+    /// Run `op`, which is expected to [install](RemoteOpGuard::install()) the guard it's passed
+    /// into the callbacks of the remote operation, or to [check](RemoteOpGuard::proceed()) it
+    /// regularly, and return its result.
+    ///
+    /// If `op` was aborted by the guard, its error is replaced by the reason for aborting it.
+    pub fn run<T, E>(
+        &self,
+        op: impl FnOnce(&RemoteOpGuard) -> Result<T, E>,
+    ) -> Result<T, RemoteError>
+    where
+        E: Into<anyhow::Error>,
+    {
+        let guard = RemoteOpGuard {
+            deadline: self
+                .timeout
+                .map(|timeout| (Instant::now() + timeout, timeout)),
+            cancellation: self.cancellation.clone(),
+            aborted: RefCell::new(None),
+        };
+        let result = op(&guard);
+        if let Some(reason) = guard.aborted.take() {
+            return Err(reason);
+        }
+        result.map_err(|err| RemoteError::Other(err.into()))
+    }
+}
+
+/// Decides if a remote operation run by [`RemoteOp::run()`] may continue.
+#[derive(Debug)]
+pub struct RemoteOpGuard {
+    deadline: Option<(Instant, Duration)>,
+    cancellation: CancellationToken,
+    aborted: RefCell<Option<RemoteError>>,
+}
+
+impl RemoteOpGuard {
+    /// Return `true` if the operation may continue, or `false` if it should be aborted as it's
+    /// cancelled or past its deadline, for use in callbacks that report progress.
+    pub fn proceed(&self) -> bool {
+        let reason = if self.cancellation.is_cancelled() {
+            RemoteError::Cancelled
+        } else {
+            match self.deadline {
+                Some((deadline, timeout)) if Instant::now() >= deadline => {
+                    RemoteError::TimedOut(timeout)
+                }
+                _ => return true,
+            }
+        };
+        self.aborted.replace(Some(reason));
+        false
+    }
+
+    /// Run the future created by `make_fut` on its own runtime in another thread, like the
+    /// operations using the `git` executable do, and drop it, which kills the processes it
+    /// spawned, once [`proceed()`](Self::proceed()) says so, which is checked before starting it
+    /// as well.
+    pub(crate) fn block_on<T, Fut>(
+        &self,
+        make_fut: impl FnOnce() -> Fut + Send + 'static,
+    ) -> anyhow::Result<T>
+    where
+        T: Send + 'static,
+        Fut: Future<Output = T>,
+    {
+        if !self.proceed() {
+            anyhow::bail!(ABORTED);
+        }
+        let (abort_tx, abort_rx) = tokio::sync::oneshot::channel::<()>();
+        let (result_tx, result_rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            tokio::runtime::Runtime::new()
+                .unwrap()
+                .block_on(async move {
+                    tokio::select! {
+                        result = make_fut() => {
+                            result_tx.send(result).ok();
+                        }
+                        Ok(()) = abort_rx => {}
+                    }
+                });
+        });
+        loop {
+            match result_rx.recv_timeout(Duration::from_millis(50)) {
+                Ok(result) => return Ok(result),
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+                    anyhow::bail!("the remote operation panicked")
+                }
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
+                    if !self.proceed() {
+                        abort_tx.send(()).ok();
+                        anyhow::bail!(ABORTED);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Set the callbacks of `callbacks` that can abort a transfer, which are the progress of
+    /// fetches and the negotiation of pushes, to abort once [`proceed()`](Self::proceed()) says so.
+    ///
+    /// Other callbacks, like the sideband progress, are left to the caller, who may check
+    /// [`proceed()`](Self::proceed()) in them as well.
+    pub fn install<'a>(&'a self, callbacks: &mut git2::RemoteCallbacks<'a>) {
+        callbacks.transfer_progress(|_progress| self.proceed());
+        callbacks.push_negotiation(|_updates| {
+            if self.proceed() {
+                Ok(())
+            } else {
+                Err(git2::Error::from_str(ABORTED))
+            }
+        });
+    }
+}
+
+/// The timeout of operations on remotes of repositories without their own [`RemoteOp`].
+///
+/// It limits the whole operation, not just the time without progress, so it's generous enough for
+/// large transfers and only catches remotes that stopped responding.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+/// The runners used by [`RepoActionsExt`](crate::RepoActionsExt) for the operations on the
+/// remotes of each repository.
+#[derive(Debug, Default)]
+pub struct RemoteOps {
+    ops: Mutex<HashMap<PathBuf, RemoteOp>>,
+}
+
+impl RemoteOps {
+    /// Run the operations on remotes of the repository at `git_dir` with `op`, replacing the
+    /// previous runner.
+    pub fn set(&self, git_dir: &Path, op: RemoteOp) {
+        self.ops.lock().unwrap().insert(git_dir.to_owned(), op);
+    }
+
+    /// Return the runner for the repository at `git_dir`, which times out after
+    /// [`DEFAULT_TIMEOUT`] unless another one was [set](Self::set()).
+    pub fn get(&self, git_dir: &Path) -> RemoteOp {
+        self.ops
+            .lock()
+            .unwrap()
+            .get(git_dir)
+            .cloned()
+            .unwrap_or_else(|| RemoteOp::new().with_timeout(DEFAULT_TIMEOUT))
+    }
+}
+
+static GLOBAL_REMOTE_OPS: LazyLock<RemoteOps> = LazyLock::new(Default::default);
+
+/// Get the runners used by [`RepoActionsExt`](crate::RepoActionsExt).
+pub fn get_ops() -> &'static RemoteOps {
+    &GLOBAL_REMOTE_OPS
+}
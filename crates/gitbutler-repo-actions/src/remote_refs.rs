@@ -0,0 +1,98 @@
+//! A short-lived cache of the refs advertised by remotes, so that repeated previews don't have to
+//! connect to the remote each time.
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+
+/// A reference as advertised by a remote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdvertisedRef {
+    /// The full name of the reference on the remote, like `refs/heads/main` or `HEAD`.
+    pub name: String,
+    /// The object the reference points to.
+    pub target: git2::Oid,
+    /// The name of the reference this one points to if it's symbolic, like `refs/heads/main` for `HEAD`.
+    pub symref_target: Option<String>,
+}
+
+/// Remembers the refs advertised by remotes for a limited time, keyed by repository and remote name.
+///
+/// Entries must be [invalidated](Self::invalidate()) whenever the remote is known to have changed,
+/// like after a push or fetch, and expire after the TTL otherwise to avoid acting on stale data.
+#[derive(Debug)]
+pub struct RemoteRefsCache {
+    ttl: Mutex<Duration>,
+    entries: Mutex<HashMap<(PathBuf, String), (Instant, Vec<AdvertisedRef>)>>,
+}
+
+impl RemoteRefsCache {
+    /// The time for which advertisements are kept by default, short enough to not be noticeable.
+    pub const DEFAULT_TTL: Duration = Duration::from_secs(3);
+
+    /// Create a new cache which keeps advertisements for `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        RemoteRefsCache {
+            ttl: Mutex::new(ttl),
+            entries: Mutex::default(),
+        }
+    }
+
+    /// Keep advertisements for `ttl` from now on, which also applies to the ones already cached.
+    /// Setting it to zero disables the cache.
+    pub fn set_ttl(&self, ttl: Duration) {
+        *self.ttl.lock().unwrap() = ttl;
+    }
+
+    /// Return the refs advertised by `remote_name` of the repository at `git_dir`, calling `list`
+    /// to obtain them if they aren't cached or are older than the TTL.
+    pub fn get_or_list(
+        &self,
+        git_dir: &Path,
+        remote_name: &str,
+        list: impl FnOnce() -> Result<Vec<AdvertisedRef>>,
+    ) -> Result<Vec<AdvertisedRef>> {
+        let ttl = *self.ttl.lock().unwrap();
+        let key = (git_dir.to_owned(), remote_name.to_owned());
+        if let Some((listed_at, refs)) = self.entries.lock().unwrap().get(&key) {
+            if listed_at.elapsed() < ttl {
+                return Ok(refs.clone());
+            }
+        }
+
+        // Don't hold the lock while talking to the remote, which may take a while.
+        let refs = list()?;
+        if !ttl.is_zero() {
+            self.entries
+                .lock()
+                .unwrap()
+                .insert(key, (Instant::now(), refs.clone()));
+        }
+        Ok(refs)
+    }
+
+    /// Forget the advertisement of `remote_name` of the repository at `git_dir`.
+    pub fn invalidate(&self, git_dir: &Path, remote_name: &str) {
+        self.entries
+            .lock()
+            .unwrap()
+            .remove(&(git_dir.to_owned(), remote_name.to_owned()));
+    }
+}
+
+impl Default for RemoteRefsCache {
+    fn default() -> Self {
+        RemoteRefsCache::new(Self::DEFAULT_TTL)
+    }
+}
+
+static GLOBAL_REMOTE_REFS_CACHE: LazyLock<RemoteRefsCache> = LazyLock::new(Default::default);
+
+/// Get the cache used by [`RepoActionsExt::remote_refs()`](crate::RepoActionsExt::remote_refs()).
+pub fn get_cache() -> &'static RemoteRefsCache {
+    &GLOBAL_REMOTE_REFS_CACHE
+}
@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{str::FromStr, time::Duration};
 
 use anyhow::{anyhow, Context, Result};
 use gitbutler_command_context::CommandContext;
@@ -8,14 +8,30 @@ use gitbutler_project::AuthKey;
 use gitbutler_reference::{Refname, RemoteRefname};
 use gitbutler_stack::{Stack, StackId};
 
-use crate::askpass;
+use crate::{
+    askpass, fetch, parse_refspec, proxy, remote_messages, remote_op,
+    remote_refs::{self, AdvertisedRef},
+    PushError, PushOutcome,
+};
 use gitbutler_repo::{
     credentials,
     logging::{LogUntil, RepositoryExt as _},
     RepositoryExt,
 };
 pub trait RepoActionsExt {
-    fn fetch(&self, remote_name: &str, askpass: Option<String>) -> Result<()>;
+    /// Fetch `refspecs` from the remote named `remote_name`, or all of its branches into
+    /// `refs/remotes/<remote_name>/*` if there are none.
+    ///
+    /// Each refspec is validated with [`parse_refspec()`] first, so malformed ones fail with
+    /// [`Code::Validation`] before the remote is contacted.
+    fn fetch(
+        &self,
+        remote_name: &str,
+        refspecs: Vec<String>,
+        askpass: Option<String>,
+    ) -> Result<()>;
+    /// Push `head` to `branch`, and return the messages the remote printed while doing so,
+    /// like the URL to create a pull request, or that it was up to date already.
     fn push(
         &self,
         head: git2::Oid,
@@ -23,7 +39,7 @@ pub trait RepoActionsExt {
         with_force: bool,
         refspec: Option<String>,
         askpass_broker: Option<Option<StackId>>,
-    ) -> Result<()>;
+    ) -> Result<PushOutcome>;
     fn commit(
         &self,
         message: &str,
@@ -40,6 +56,13 @@ pub trait RepoActionsExt {
         branch_name: &str,
         askpass: Option<Option<StackId>>,
     ) -> Result<()>;
+    /// Return the refs advertised by the remote named `remote_name`, which are cached for a short
+    /// time by [`remote_refs::get_cache()`] so that repeated previews don't connect each time.
+    /// The time is configured by the `remoteRefsCacheTtlSeconds` setting.
+    fn remote_refs(&self, remote_name: &str) -> Result<Vec<AdvertisedRef>>;
+    /// Return the short name of the branch `HEAD` points to on the remote named `remote_name`,
+    /// or `None` if the remote doesn't advertise it.
+    fn remote_default_branch(&self, remote_name: &str) -> Result<Option<String>>;
 }
 
 impl RepoActionsExt for CommandContext {
@@ -65,13 +88,13 @@ impl RepoActionsExt for CommandContext {
             RemoteRefname::from_str(&format!("refs/remotes/{remote_name}/{branch_name}",))?;
 
         match self.push(commit_id, &refname, false, None, askpass) {
-            Ok(()) => Ok(()),
+            Ok(_outcome) => Ok(()),
             Err(e) => Err(anyhow::anyhow!(e.to_string())),
         }?;
 
         let empty_refspec = Some(format!(":refs/heads/{}", branch_name));
         match self.push(commit_id, &refname, false, empty_refspec, askpass) {
-            Ok(()) => Ok(()),
+            Ok(_outcome) => Ok(()),
             Err(e) => Err(anyhow::anyhow!(e.to_string())),
         }?;
 
@@ -159,7 +182,7 @@ impl RepoActionsExt for CommandContext {
         with_force: bool,
         refspec: Option<String>,
         askpass_broker: Option<Option<StackId>>,
-    ) -> Result<()> {
+    ) -> Result<PushOutcome> {
         let refspec = refspec.unwrap_or_else(|| {
             if with_force {
                 format!("+{}:refs/heads/{}", head, branch.branch())
@@ -167,6 +190,7 @@ impl RepoActionsExt for CommandContext {
                 format!("{}:refs/heads/{}", head, branch.branch())
             }
         });
+        let op = remote_op::get_ops().get(self.repo().path());
 
         // NOTE(qix-): This is a nasty hack, however the codebase isn't structured
         // NOTE(qix-): in a way that allows us to really incorporate new backends
@@ -176,82 +200,149 @@ impl RepoActionsExt for CommandContext {
         if self.project().preferred_key == AuthKey::SystemExecutable {
             let path = self.project().worktree_path();
             let remote = branch.remote().to_string();
-            return std::thread::spawn(move || {
-                tokio::runtime::Runtime::new()
-                    .unwrap()
-                    .block_on(gitbutler_git::push(
-                        path,
-                        gitbutler_git::tokio::TokioExecutor,
-                        &remote,
-                        gitbutler_git::RefSpec::parse(refspec).unwrap(),
-                        with_force,
-                        handle_git_prompt_push,
-                        askpass_broker,
-                    ))
-            })
-            .join()
-            .unwrap()
-            .map_err(Into::into);
+            return op
+                .run(|guard| -> Result<PushOutcome> {
+                    let (up_to_date, stderr) = guard
+                        .block_on(move || async move {
+                            gitbutler_git::push(
+                                path,
+                                gitbutler_git::tokio::TokioExecutor,
+                                &remote,
+                                gitbutler_git::RefSpec::parse(refspec).unwrap(),
+                                with_force,
+                                handle_git_prompt_push,
+                                askpass_broker,
+                            )
+                            .await
+                        })?
+                        .map_err(PushError::from)?;
+                    remote_refs::get_cache().invalidate(self.repo().path(), branch.remote());
+                    if up_to_date {
+                        return Ok(PushOutcome::UpToDate);
+                    }
+                    // Unlike the sideband, this also contains what `git` printed itself.
+                    let remote_output: String = stderr
+                        .lines()
+                        .filter(|line| line.starts_with("remote:"))
+                        .map(|line| format!("{line}\n"))
+                        .collect();
+                    Ok(PushOutcome::Pushed(remote_messages(&remote_output)))
+                })
+                .map_err(Into::into);
         }
 
-        let auth_flows = credentials::help(self, branch.remote())?;
-        for (mut remote, callbacks) in auth_flows {
-            let mut update_refs_error: Option<git2::Error> = None;
-            for callback in callbacks {
-                let mut cbs: git2::RemoteCallbacks = callback.into();
-                if self.project().omit_certificate_check.unwrap_or(false) {
-                    cbs.certificate_check(|_, _| Ok(git2::CertificateCheckStatus::CertificateOk));
-                }
-                cbs.push_update_reference(|_reference: &str, status: Option<&str>| {
-                    if let Some(status) = status {
-                        update_refs_error = Some(git2::Error::from_str(status));
-                        return Err(git2::Error::from_str(status));
-                    };
-                    Ok(())
-                });
-
-                let push_result = remote.push(
-                    &[refspec.as_str()],
-                    Some(&mut git2::PushOptions::new().remote_callbacks(cbs)),
-                );
-                match push_result {
-                    Ok(()) => {
-                        tracing::info!(
-                            project_id = %self.project().id,
-                            remote = %branch.remote(),
-                            %head,
-                            branch = branch.branch(),
-                            "pushed git branch"
-                        );
-                        return Ok(());
+        op.run(|guard| -> Result<PushOutcome> {
+            let mut proxy_auth_failed = false;
+            let auth_flows = credentials::help(self, branch.remote())?;
+            for (mut remote, callbacks) in auth_flows {
+                for callback in callbacks {
+                    let mut update_refs_error: Option<(String, String)> = None;
+                    let mut up_to_date = false;
+                    let mut sideband = String::new();
+                    let mut cbs: git2::RemoteCallbacks = callback.into();
+                    guard.install(&mut cbs);
+                    if self.project().omit_certificate_check.unwrap_or(false) {
+                        cbs.certificate_check(|_, _| {
+                            Ok(git2::CertificateCheckStatus::CertificateOk)
+                        });
                     }
-                    Err(err) => match err.class() {
-                        git2::ErrorClass::Net | git2::ErrorClass::Http => {
-                            tracing::warn!(project_id = %self.project().id, ?err, "push failed due to network");
-                            continue;
+                    cbs.push_update_reference(|reference: &str, status: Option<&str>| {
+                        if let Some(status) = status {
+                            update_refs_error = Some((reference.to_owned(), status.to_owned()));
+                            return Err(git2::Error::from_str(status));
+                        };
+                        Ok(())
+                    });
+                    // Replaces the one installed by the guard, which is why it's checked here.
+                    cbs.push_negotiation(|updates| {
+                        if !guard.proceed() {
+                            return Err(git2::Error::from_str(remote_op::ABORTED));
+                        }
+                        // There is nothing to transfer if the remote already has what we push.
+                        if !updates.is_empty()
+                            && updates.iter().all(|update| update.src() == update.dst())
+                        {
+                            up_to_date = true;
+                            return Err(git2::Error::from_str("remote is up to date"));
                         }
-                        _ => match err.code() {
-                            git2::ErrorCode::Auth => {
-                                tracing::warn!(project_id = %self.project().id, ?err, "push failed due to auth");
+                        Ok(())
+                    });
+                    cbs.sideband_progress(|data| {
+                        sideband.push_str(&String::from_utf8_lossy(data));
+                        guard.proceed()
+                    });
+
+                    let push_result = remote.push(
+                        &[refspec.as_str()],
+                        Some(
+                            git2::PushOptions::new()
+                                .remote_callbacks(cbs)
+                                .proxy_options(proxy::proxy_options(self.repo(), branch.remote())?),
+                        ),
+                    );
+                    match push_result {
+                        Ok(()) => {
+                            tracing::info!(
+                                project_id = %self.project().id,
+                                remote = %branch.remote(),
+                                %head,
+                                branch = branch.branch(),
+                                "pushed git branch"
+                            );
+                            remote_refs::get_cache()
+                                .invalidate(self.repo().path(), branch.remote());
+                            return Ok(PushOutcome::Pushed(remote_messages(&sideband)));
+                        }
+                        Err(_) if up_to_date => return Ok(PushOutcome::UpToDate),
+                        Err(err) => match err.class() {
+                            git2::ErrorClass::Net | git2::ErrorClass::Http => {
+                                proxy_auth_failed |= proxy::is_proxy_auth_error(&err);
+                                tracing::warn!(project_id = %self.project().id, ?err, "push failed due to network");
                                 continue;
                             }
-                            _ => {
-                                if let Some(update_refs_err) = update_refs_error {
-                                    return Err(update_refs_err).context(err);
+                            _ => match err.code() {
+                                git2::ErrorCode::Auth => {
+                                    tracing::warn!(project_id = %self.project().id, ?err, "push failed due to auth");
+                                    continue;
                                 }
-                                return Err(err.into());
-                            }
+                                _ => {
+                                    if let Some((refname, reason)) = update_refs_error {
+                                        return Err(
+                                            PushError::rejected(refname, reason, &sideband).into()
+                                        );
+                                    }
+                                    return Err(PushError::Other(err.into()).into());
+                                }
+                            },
                         },
-                    },
+                    }
                 }
             }
-        }
 
-        Err(anyhow!("authentication failed").context(Code::ProjectGitAuth))
+            if proxy_auth_failed {
+                return Err(proxy::proxy_auth_error());
+            }
+            Err(PushError::AuthenticationFailed.into())
+        })
+        .map_err(Into::into)
     }
 
-    fn fetch(&self, remote_name: &str, askpass: Option<String>) -> Result<()> {
-        let refspec = format!("+refs/heads/*:refs/remotes/{}/*", remote_name);
+    fn fetch(
+        &self,
+        remote_name: &str,
+        refspecs: Vec<String>,
+        askpass: Option<String>,
+    ) -> Result<()> {
+        let refspecs = if refspecs.is_empty() {
+            vec![format!("+refs/heads/*:refs/remotes/{}/*", remote_name)]
+        } else {
+            refspecs
+        };
+        let parsed_refspecs = refspecs
+            .iter()
+            .map(|spec| parse_refspec(spec))
+            .collect::<Result<Vec<_>>>()?;
+        let op = remote_op::get_ops().get(self.repo().path());
 
         // NOTE(qix-): This is a nasty hack, however the codebase isn't structured
         // NOTE(qix-): in a way that allows us to really incorporate new backends
@@ -261,59 +352,166 @@ impl RepoActionsExt for CommandContext {
         if self.project().preferred_key == AuthKey::SystemExecutable {
             let path = self.project().worktree_path();
             let remote = remote_name.to_string();
-            return std::thread::spawn(move || {
-                tokio::runtime::Runtime::new()
-                    .unwrap()
-                    .block_on(gitbutler_git::fetch(
-                        path,
-                        gitbutler_git::tokio::TokioExecutor,
-                        &remote,
-                        gitbutler_git::RefSpec::parse(refspec).unwrap(),
-                        handle_git_prompt_fetch,
-                        askpass,
-                    ))
-            })
-            .join()
-            .unwrap()
-            .map_err(Into::into);
+            return op
+                .run(|guard| -> Result<()> {
+                    guard.block_on(move || async move {
+                        for refspec in parsed_refspecs {
+                            gitbutler_git::fetch(
+                                &path,
+                                gitbutler_git::tokio::TokioExecutor,
+                                &remote,
+                                refspec,
+                                handle_git_prompt_fetch,
+                                askpass.clone(),
+                            )
+                            .await
+                            .map_err(|err| fetch::classify(err.into()))?;
+                        }
+                        Ok::<_, anyhow::Error>(())
+                    })?
+                })
+                .map_err(anyhow::Error::from)
+                .inspect(|()| {
+                    remote_refs::get_cache().invalidate(self.repo().path(), remote_name)
+                });
         }
 
-        let auth_flows = credentials::help(self, remote_name)?;
-        for (mut remote, callbacks) in auth_flows {
-            for callback in callbacks {
-                let mut fetch_opts = git2::FetchOptions::new();
-                let mut cbs: git2::RemoteCallbacks = callback.into();
-                if self.project().omit_certificate_check.unwrap_or(false) {
-                    cbs.certificate_check(|_, _| Ok(git2::CertificateCheckStatus::CertificateOk));
-                }
-                fetch_opts.remote_callbacks(cbs);
-                fetch_opts.prune(git2::FetchPrune::On);
-
-                match remote.fetch(&[&refspec], Some(&mut fetch_opts), None) {
-                    Ok(()) => {
-                        tracing::info!(project_id = %self.project().id, %refspec, "git fetched");
-                        return Ok(());
+        op.run(|guard| -> Result<()> {
+            let mut proxy_auth_failed = false;
+            let auth_flows = credentials::help(self, remote_name)?;
+            for (mut remote, callbacks) in auth_flows {
+                for callback in callbacks {
+                    let mut fetch_opts = git2::FetchOptions::new();
+                    let mut cbs: git2::RemoteCallbacks = callback.into();
+                    guard.install(&mut cbs);
+                    if self.project().omit_certificate_check.unwrap_or(false) {
+                        cbs.certificate_check(|_, _| {
+                            Ok(git2::CertificateCheckStatus::CertificateOk)
+                        });
                     }
-                    Err(err) => match err.class() {
-                        git2::ErrorClass::Net | git2::ErrorClass::Http => {
-                            tracing::warn!(project_id = %self.project().id, ?err, "fetch failed due to network");
-                            continue;
+                    fetch_opts.remote_callbacks(cbs);
+                    fetch_opts.prune(git2::FetchPrune::On);
+                    fetch_opts.proxy_options(proxy::proxy_options(self.repo(), remote_name)?);
+
+                    match remote.fetch(&refspecs, Some(&mut fetch_opts), None) {
+                        Ok(()) => {
+                            tracing::info!(
+                                project_id = %self.project().id,
+                                ?refspecs,
+                                "git fetched"
+                            );
+                            remote_refs::get_cache().invalidate(self.repo().path(), remote_name);
+                            return Ok(());
                         }
-                        _ => match err.code() {
-                            git2::ErrorCode::Auth => {
-                                tracing::warn!(project_id = %self.project().id, ?err, "fetch failed due to auth");
+                        Err(err) => match err.class() {
+                            git2::ErrorClass::Net | git2::ErrorClass::Http => {
+                                // Other credentials won't change the mind of the remote.
+                                if let Some(message) = fetch::server_refusal(err.message()) {
+                                    return Err(fetch::server_refused_error(message));
+                                }
+                                proxy_auth_failed |= proxy::is_proxy_auth_error(&err);
+                                tracing::warn!(project_id = %self.project().id, ?err, "fetch failed due to network");
                                 continue;
                             }
-                            _ => {
-                                return Err(err.into());
-                            }
+                            _ => match err.code() {
+                                git2::ErrorCode::Auth => {
+                                    tracing::warn!(project_id = %self.project().id, ?err, "fetch failed due to auth");
+                                    continue;
+                                }
+                                _ => {
+                                    return Err(err.into());
+                                }
+                            },
                         },
-                    },
+                    }
                 }
             }
-        }
 
-        Err(anyhow!("authentication failed")).context(Code::ProjectGitAuth)
+            if proxy_auth_failed {
+                return Err(proxy::proxy_auth_error());
+            }
+            Err(anyhow!("authentication failed")).context(Code::ProjectGitAuth)
+        })
+        .map_err(Into::into)
+    }
+
+    fn remote_refs(&self, remote_name: &str) -> Result<Vec<AdvertisedRef>> {
+        let cache = remote_refs::get_cache();
+        cache.set_ttl(Duration::from_secs(
+            self.app_settings().remote_refs_cache_ttl_seconds,
+        ));
+        cache.get_or_list(self.repo().path(), remote_name, || {
+            let op = remote_op::get_ops().get(self.repo().path());
+            op.run(|guard| -> Result<Vec<AdvertisedRef>> {
+                let mut proxy_auth_failed = false;
+                let auth_flows = credentials::help(self, remote_name)?;
+                for (mut remote, callbacks) in auth_flows {
+                    for callback in callbacks {
+                        let mut cbs: git2::RemoteCallbacks = callback.into();
+                        guard.install(&mut cbs);
+                        if self.project().omit_certificate_check.unwrap_or(false) {
+                            cbs.certificate_check(|_, _| {
+                                Ok(git2::CertificateCheckStatus::CertificateOk)
+                            });
+                        }
+                        let proxy_options = proxy::proxy_options(self.repo(), remote_name)?;
+                        match remote.connect_auth(
+                            git2::Direction::Fetch,
+                            Some(cbs),
+                            Some(proxy_options),
+                        ) {
+                            Ok(connection) => {
+                                return Ok(connection
+                                    .list()?
+                                    .iter()
+                                    .map(|head| AdvertisedRef {
+                                        name: head.name().to_owned(),
+                                        target: head.oid(),
+                                        symref_target: head.symref_target().map(ToOwned::to_owned),
+                                    })
+                                    .collect());
+                            }
+                            Err(err) => match err.class() {
+                                git2::ErrorClass::Net | git2::ErrorClass::Http => {
+                                    proxy_auth_failed |= proxy::is_proxy_auth_error(&err);
+                                    tracing::warn!(project_id = %self.project().id, ?err, "listing remote refs failed due to network");
+                                    continue;
+                                }
+                                _ => match err.code() {
+                                    git2::ErrorCode::Auth => {
+                                        tracing::warn!(project_id = %self.project().id, ?err, "listing remote refs failed due to auth");
+                                        continue;
+                                    }
+                                    _ => {
+                                        return Err(err.into());
+                                    }
+                                },
+                            },
+                        }
+                    }
+                }
+
+                if proxy_auth_failed {
+                    return Err(proxy::proxy_auth_error());
+                }
+                Err(anyhow!("authentication failed")).context(Code::ProjectGitAuth)
+            })
+            .map_err(Into::into)
+        })
+    }
+
+    fn remote_default_branch(&self, remote_name: &str) -> Result<Option<String>> {
+        Ok(self
+            .remote_refs(remote_name)?
+            .into_iter()
+            .find(|advertised| advertised.name == "HEAD")
+            .and_then(|head| head.symref_target)
+            .map(|target| {
+                target
+                    .strip_prefix("refs/heads/")
+                    .map(ToOwned::to_owned)
+                    .unwrap_or(target)
+            }))
     }
 }
 
@@ -0,0 +1,212 @@
+use std::time::Duration;
+
+use but_settings::AppSettings;
+use gitbutler_command_context::CommandContext;
+use gitbutler_error::error::{AnyhowContextExt, Code};
+use gitbutler_project::{AuthKey, Project};
+use gitbutler_repo_actions::{remote_op, RemoteOp, RepoActionsExt as _};
+use gitbutler_testsupport::{empty_bare_repository, test_repository};
+
+/// Return a repository with an `origin` remote that has the branches `main` and `other`,
+/// along with the ids they point to.
+fn repository_with_remote() -> (CommandContext, [git2::Oid; 2], Vec<tempfile::TempDir>) {
+    let (remote, remote_tmp) = empty_bare_repository();
+    let signature = git2::Signature::now("test", "test@example.com").unwrap();
+    let tree = remote
+        .find_tree(remote.treebuilder(None).unwrap().write().unwrap())
+        .unwrap();
+    let ids = ["main", "other"].map(|name| {
+        remote
+            .commit(
+                Some(&format!("refs/heads/{name}")),
+                &signature,
+                &signature,
+                name,
+                &tree,
+                &[],
+            )
+            .unwrap()
+    });
+
+    let (repo, tmp) = test_repository();
+    repo.remote("origin", remote_tmp.path().to_str().unwrap())
+        .unwrap();
+    let project = Project {
+        path: repo.workdir().unwrap().to_path_buf(),
+        preferred_key: AuthKey::GitCredentialsHelper,
+        ..Default::default()
+    };
+    let ctx = CommandContext::open(&project, AppSettings::default()).unwrap();
+    (ctx, ids, vec![remote_tmp, tmp])
+}
+
+#[test]
+fn single_branch_refspec() {
+    let (ctx, [main, _other], _tmp) = repository_with_remote();
+
+    ctx.fetch(
+        "origin",
+        vec!["+refs/heads/main:refs/remotes/origin/main".into()],
+        None,
+    )
+    .unwrap();
+
+    let repo = ctx.repo();
+    assert_eq!(
+        repo.refname_to_id("refs/remotes/origin/main").ok(),
+        Some(main)
+    );
+    assert!(
+        repo.find_reference("refs/remotes/origin/other").is_err(),
+        "only the requested branch is fetched"
+    );
+}
+
+#[test]
+fn all_branches_by_default() {
+    let (ctx, [main, other], _tmp) = repository_with_remote();
+
+    ctx.fetch("origin", vec![], None).unwrap();
+
+    let repo = ctx.repo();
+    assert_eq!(
+        repo.refname_to_id("refs/remotes/origin/main").ok(),
+        Some(main)
+    );
+    assert_eq!(
+        repo.refname_to_id("refs/remotes/origin/other").ok(),
+        Some(other)
+    );
+}
+
+#[test]
+fn malformed_refspec_is_refused() {
+    let (ctx, _ids, _tmp) = repository_with_remote();
+
+    let err = ctx
+        .fetch(
+            "origin",
+            vec![
+                "+refs/heads/main:refs/remotes/origin/main".into(),
+                "a:b:c".into(),
+            ],
+            None,
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::Validation)
+    );
+    assert!(
+        ctx.repo()
+            .find_reference("refs/remotes/origin/main")
+            .is_err(),
+        "nothing is fetched if one of the refspecs is invalid"
+    );
+}
+
+#[test]
+fn exceeding_the_timeout_aborts_the_transfer() {
+    let (ctx, _ids, _tmp) = repository_with_remote();
+    remote_op::get_ops().set(
+        ctx.repo().path(),
+        RemoteOp::new().with_timeout(Duration::ZERO),
+    );
+
+    let err = ctx.fetch("origin", vec![], None).unwrap_err();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::ProjectGitTimeout),
+        "the transfer progress is reported while receiving the objects"
+    );
+    assert!(
+        ctx.repo()
+            .find_reference("refs/remotes/origin/main")
+            .is_err(),
+        "nothing is fetched"
+    );
+}
+
+#[test]
+fn exceeding_the_timeout_aborts_the_git_executable() {
+    let (ctx, _ids, _tmp) = repository_with_remote();
+    let project = Project {
+        preferred_key: AuthKey::SystemExecutable,
+        ..ctx.project().clone()
+    };
+    let ctx = CommandContext::open(&project, AppSettings::default()).unwrap();
+    remote_op::get_ops().set(
+        ctx.repo().path(),
+        RemoteOp::new().with_timeout(Duration::ZERO),
+    );
+
+    let err = ctx.fetch("origin", vec![], None).unwrap_err();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::ProjectGitTimeout)
+    );
+    assert!(ctx
+        .repo()
+        .find_reference("refs/remotes/origin/main")
+        .is_err());
+}
+
+mod server_refusal {
+    use gitbutler_error::error::{AnyhowContextExt, Code};
+    use gitbutler_repo_actions::fetch::{server_refusal, server_refused_error};
+
+    #[test]
+    fn refusals_during_negotiation() {
+        for (text, expected) in [
+            (
+                // `git` receiving an `ERR` packet
+                "fatal: remote error: upload-pack: not our ref 0123456789abcdef",
+                "upload-pack: not our ref 0123456789abcdef",
+            ),
+            (
+                // `git2` receiving an `ERR` packet
+                "remote error: access denied or repository not exported: /shallow.git",
+                "access denied or repository not exported: /shallow.git",
+            ),
+            (
+                "Cloning into 'repo'...\nfatal: Server does not support shallow clients",
+                "Server does not support shallow clients",
+            ),
+            (
+                "fatal: Server does not support --deepen",
+                "Server does not support --deepen",
+            ),
+            (
+                "error: Server does not allow request for unadvertised object 0123456789abcdef",
+                "Server does not allow request for unadvertised object 0123456789abcdef",
+            ),
+        ] {
+            assert_eq!(server_refusal(text).as_deref(), Some(expected), "{text}");
+        }
+    }
+
+    #[test]
+    fn transport_failures_are_not_refusals() {
+        for text in [
+            "failed to connect to github.com: Connection refused",
+            "fatal: unable to access 'https://example.com/repo.git/': Could not resolve host: example.com",
+            "fatal: the remote end hung up unexpectedly",
+            "too many redirects or authentication replays",
+        ] {
+            assert_eq!(server_refusal(text), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn error_is_classified_with_the_message() {
+        let err = server_refused_error("shallow fetches are disabled".into());
+        let ctx = err.custom_context().expect("classified");
+        assert_eq!(ctx.code, Code::ProjectGitServerRefused);
+        assert!(
+            ctx.message
+                .as_deref()
+                .is_some_and(|msg| msg.contains("shallow fetches are disabled")),
+            "the message of the remote is shown to the user"
+        );
+    }
+}
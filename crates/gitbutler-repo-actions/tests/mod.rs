@@ -0,0 +1,7 @@
+mod fetch;
+mod proxy;
+mod push_error;
+mod refspec;
+mod remote_messages;
+mod remote_op;
+mod remote_refs;
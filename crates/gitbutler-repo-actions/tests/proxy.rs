@@ -0,0 +1,106 @@
+use gitbutler_repo_actions::{proxy, ProxyConfig};
+
+fn repo() -> anyhow::Result<(tempfile::TempDir, git2::Repository)> {
+    let tmp = tempfile::tempdir()?;
+    let repo = git2::Repository::init(tmp.path())?;
+    Ok((tmp, repo))
+}
+
+#[test]
+fn auto_picks_up_configured_proxy() -> anyhow::Result<()> {
+    let (_tmp, repo) = repo()?;
+    assert_eq!(
+        proxy::proxy(&repo)?,
+        ProxyConfig::Auto,
+        "this is the default"
+    );
+
+    repo.config()?
+        .set_str("remote.origin.proxy", "http://proxy.example.com:3128")?;
+    assert_eq!(
+        ProxyConfig::Auto.url(&repo, "origin")?.as_deref(),
+        Some("http://proxy.example.com:3128")
+    );
+    assert_eq!(
+        ProxyConfig::None.url(&repo, "origin")?,
+        None,
+        "a direct connection ignores the configuration"
+    );
+    Ok(())
+}
+
+#[test]
+fn set_proxy_round_trips() -> anyhow::Result<()> {
+    let (_tmp, repo) = repo()?;
+    for config in [
+        ProxyConfig::Specified("http://proxy.example.com:3128".into()),
+        ProxyConfig::None,
+        ProxyConfig::Auto,
+    ] {
+        proxy::set_proxy(&repo, &config)?;
+        assert_eq!(proxy::proxy(&repo)?, config);
+    }
+    Ok(())
+}
+
+#[test]
+fn specified_proxy_is_used_by_auto_as_well() -> anyhow::Result<()> {
+    let (_tmp, repo) = repo()?;
+    proxy::set_proxy(
+        &repo,
+        &ProxyConfig::Specified("http://proxy.example.com:3128".into()),
+    )?;
+    assert_eq!(
+        ProxyConfig::Auto.url(&repo, "origin")?.as_deref(),
+        Some("http://proxy.example.com:3128"),
+        "it's stored in `http.proxy`, which is Git's own configuration"
+    );
+
+    proxy::set_proxy(&repo, &ProxyConfig::None)?;
+    assert_eq!(
+        ProxyConfig::Auto.url(&repo, "origin")?,
+        None,
+        "an empty proxy disables proxying, without falling back to the environment"
+    );
+    Ok(())
+}
+
+#[test]
+fn only_rejected_proxy_credentials_are_auth_errors() {
+    let http_error = |message: &str| {
+        git2::Error::new(
+            git2::ErrorCode::GenericError,
+            git2::ErrorClass::Http,
+            message,
+        )
+    };
+    for message in [
+        "proxy authentication required, but no callback set",
+        "proxy returned unexpected status: 407",
+        "unexpected http status code: 407",
+    ] {
+        assert!(
+            proxy::is_proxy_auth_error(&http_error(message)),
+            "{message}"
+        );
+    }
+    for message in [
+        "failed to connect to proxy",
+        "failed to resolve address for proxy.example.com: Name or service not known",
+        "proxy returned unexpected status: 502",
+        "remote authentication required but no callback set",
+    ] {
+        assert!(
+            !proxy::is_proxy_auth_error(&http_error(message)),
+            "{message}"
+        );
+    }
+    assert!(
+        !proxy::is_proxy_auth_error(&git2::Error::new(
+            git2::ErrorCode::GenericError,
+            git2::ErrorClass::Net,
+            "proxy authentication required",
+        )),
+        "only errors of the HTTP transport are considered"
+    );
+}
@@ -0,0 +1,104 @@
+use gitbutler_error::error::{AnyhowContextExt, Code};
+use gitbutler_repo_actions::PushError;
+
+#[test]
+fn authentication_failed() {
+    let ctx = PushError::AuthenticationFailed.into_context();
+    assert_eq!(ctx.code, Code::ProjectGitAuth);
+    assert!(ctx.message.is_some(), "there is a message for the user");
+}
+
+#[test]
+fn ref_not_found() {
+    let ctx = PushError::RefNotFound("refs/heads/feature".into()).into_context();
+    assert_eq!(ctx.code, Code::Validation);
+    assert_eq!(
+        ctx.message.as_deref(),
+        Some("The reference 'refs/heads/feature' to push could not be found")
+    );
+}
+
+#[test]
+fn rejected() {
+    let ctx = PushError::Rejected {
+        refname: "refs/heads/main".into(),
+        reason: "pre-receive hook declined".into(),
+    }
+    .into_context();
+    assert_eq!(ctx.code, Code::Unknown);
+    assert_eq!(
+        ctx.message.as_deref(),
+        Some("The remote rejected the update of 'refs/heads/main': pre-receive hook declined")
+    );
+}
+
+#[test]
+fn rejected_for_size_limit_with_path() {
+    let remote_messages = "remote: error: Trace: 0123456789abcdef\n\
+        remote: error: See https://gh.io/lfs for more information.\n\
+        remote: error: File assets/video.mp4 is 123.45 MB; this exceeds GitHub's file size limit of 100.00 MB\n\
+        remote: error: GH001: Large files detected. You may want to try Git Large File Storage.\n";
+    let err = PushError::rejected(
+        "refs/heads/main".into(),
+        "pre-receive hook declined".into(),
+        remote_messages,
+    );
+    assert!(matches!(
+        &err,
+        PushError::SizeLimitExceeded { path: Some(path) } if path == "assets/video.mp4"
+    ));
+    let ctx = err.into_context();
+    assert_eq!(ctx.code, Code::ProjectGitSizeLimit);
+    assert!(ctx
+        .message
+        .as_deref()
+        .is_some_and(|msg| msg.contains("assets/video.mp4")));
+}
+
+#[test]
+fn rejected_for_size_limit_without_path() {
+    let err = PushError::rejected(
+        "refs/heads/main".into(),
+        "pack exceeds maximum allowed size".into(),
+        "",
+    );
+    assert!(matches!(err, PushError::SizeLimitExceeded { path: None }));
+    assert_eq!(err.into_context().code, Code::ProjectGitSizeLimit);
+}
+
+#[test]
+fn rejected_for_unknown_reason_stays_generic() {
+    let err = PushError::rejected(
+        "refs/heads/main".into(),
+        "pre-receive hook declined".into(),
+        "remote: error: you are not allowed to push to a protected branch\n",
+    );
+    assert!(matches!(
+        err,
+        PushError::Rejected { ref refname, ref reason }
+            if refname == "refs/heads/main" && reason == "pre-receive hook declined"
+    ));
+}
+
+#[test]
+fn other_is_unclassified() {
+    let ctx = PushError::Other(anyhow::anyhow!("something else")).into_context();
+    assert_eq!(ctx.code, Code::Unknown);
+    assert_eq!(ctx.message, None);
+}
+
+#[test]
+fn conversion_attaches_context() {
+    let err: anyhow::Error = PushError::AuthenticationFailed.into();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::ProjectGitAuth)
+    );
+}
+
+#[test]
+fn conversion_of_other_keeps_the_original_error() {
+    let err: anyhow::Error = PushError::Other(anyhow::anyhow!("original")).into();
+    assert!(err.custom_context().is_none());
+    assert_eq!(err.to_string(), "original");
+}
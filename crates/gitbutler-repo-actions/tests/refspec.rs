@@ -0,0 +1,63 @@
+use gitbutler_error::error::{AnyhowContextExt, Code};
+use gitbutler_repo_actions::parse_refspec;
+
+#[test]
+fn valid() {
+    let refspec = parse_refspec("refs/heads/main:refs/heads/feature").unwrap();
+    assert!(!refspec.update_non_fastforward);
+    assert_eq!(refspec.source.as_deref(), Some("refs/heads/main"));
+    assert_eq!(refspec.destination.as_deref(), Some("refs/heads/feature"));
+
+    let refspec = parse_refspec("main").unwrap();
+    assert_eq!(refspec.source.as_deref(), Some("main"));
+    assert_eq!(
+        refspec.destination.as_deref(),
+        Some("main"),
+        "the destination defaults to the source"
+    );
+
+    let refspec = parse_refspec(":refs/heads/gone").unwrap();
+    assert_eq!(refspec.source, None, "deletions have no source");
+    assert_eq!(refspec.destination.as_deref(), Some("refs/heads/gone"));
+
+    let hash = "0123456789abcdef0123456789abcdef01234567";
+    let refspec = parse_refspec(&format!("{hash}:refs/heads/main")).unwrap();
+    assert_eq!(refspec.source.as_deref(), Some(hash));
+}
+
+#[test]
+fn force() {
+    let refspec = parse_refspec("+refs/heads/*:refs/remotes/origin/*").unwrap();
+    assert!(refspec.update_non_fastforward);
+    assert_eq!(refspec.source.as_deref(), Some("refs/heads/*"));
+    assert_eq!(
+        refspec.destination.as_deref(),
+        Some("refs/remotes/origin/*")
+    );
+}
+
+#[test]
+fn malformed() {
+    for spec in [
+        "",
+        ":",
+        "a:b:c",
+        "refs/heads/a..b:refs/heads/c",
+        "refs/heads/main:refs/heads/with space",
+        "refs/heads/*:refs/heads/main",
+        "refs/heads/main:refs/remotes/origin/*",
+        "refs/*/*:refs/remotes/*/*",
+        "0123456789abcdef0123456789abcdef01234567",
+    ] {
+        let err = parse_refspec(spec).unwrap_err();
+        assert_eq!(
+            err.custom_context().map(|ctx| ctx.code),
+            Some(Code::Validation),
+            "{spec:?}"
+        );
+        assert!(
+            format!("{err:#}").contains(spec),
+            "the message mentions the refspec: {err:#}"
+        );
+    }
+}
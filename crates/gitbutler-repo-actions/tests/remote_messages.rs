@@ -0,0 +1,65 @@
+use std::os::unix::fs::PermissionsExt as _;
+
+use but_settings::AppSettings;
+use gitbutler_command_context::CommandContext;
+use gitbutler_project::{AuthKey, Project};
+use gitbutler_reference::RemoteRefname;
+use gitbutler_repo_actions::{remote_messages, PushOutcome, RepoActionsExt as _};
+use gitbutler_testsupport::{empty_bare_repository, test_repository};
+
+#[test]
+fn sideband_data_without_escapes_and_progress() {
+    let sideband = "Resolving deltas:  50% (1/2)\rResolving deltas: 100% (2/2)\rResolving deltas: 100% (2/2), done.\n\
+        \n\
+        \x1b[1;33mCreate a pull request for 'feature' on GitHub by visiting:\x1b[0m\n     \
+        https://github.com/org/repo/pull/new/feature\n";
+    assert_eq!(
+        remote_messages(sideband),
+        [
+            "Create a pull request for 'feature' on GitHub by visiting:",
+            "https://github.com/org/repo/pull/new/feature"
+        ]
+    );
+}
+
+#[test]
+fn messages_printed_by_a_hook_of_the_remote() -> anyhow::Result<()> {
+    let (remote, remote_tmp) = empty_bare_repository();
+    let hooks = remote.path().join("hooks");
+    std::fs::create_dir_all(&hooks)?;
+    let hook = hooks.join("pre-receive");
+    std::fs::write(
+        &hook,
+        "#!/bin/sh\necho 'Visit https://example.com/pull/new'\n",
+    )?;
+    std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755))?;
+
+    let (repo, _tmp) = test_repository();
+    repo.remote("origin", remote_tmp.path().to_str().unwrap())?;
+    let head = repo.head()?.peel_to_commit()?.id();
+    // `git2` pushes to local remotes without running their hooks, so push with `git`.
+    let project = Project {
+        path: repo.workdir().unwrap().to_path_buf(),
+        preferred_key: AuthKey::SystemExecutable,
+        ..Default::default()
+    };
+    let ctx = CommandContext::open(&project, AppSettings::default())?;
+
+    let outcome = ctx.push(
+        head,
+        &RemoteRefname::new("origin", "main"),
+        false,
+        None,
+        None,
+    )?;
+    let PushOutcome::Pushed(messages) = outcome else {
+        panic!("the remote branch didn't exist yet, got {outcome:?}");
+    };
+    assert_eq!(
+        messages,
+        ["Visit https://example.com/pull/new"],
+        "the hook output is captured without the `remote:` prefix"
+    );
+    assert_eq!(remote.refname_to_id("refs/heads/main")?, head);
+    Ok(())
+}
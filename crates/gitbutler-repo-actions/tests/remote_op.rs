@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+use anyhow::anyhow;
+use gitbutler_error::error::{AnyhowContextExt, Code};
+use gitbutler_repo_actions::{
+    remote_op::{CancellationToken, RemoteOpGuard},
+    RemoteError, RemoteOp,
+};
+
+/// Report progress every 10ms like a transfer callback would, failing like `git2` does
+/// once a callback asks to abort.
+fn slow_transfer(guard: &RemoteOpGuard, reports: usize) -> anyhow::Result<usize> {
+    for _ in 0..reports {
+        std::thread::sleep(Duration::from_millis(10));
+        if !guard.proceed() {
+            return Err(anyhow!("callback returned an error"));
+        }
+    }
+    Ok(reports)
+}
+
+#[test]
+fn stalled_operation_times_out() {
+    let op = RemoteOp::new().with_timeout(Duration::from_millis(50));
+
+    let err = op.run(|guard| slow_transfer(guard, 1000)).unwrap_err();
+    assert!(matches!(err, RemoteError::TimedOut(timeout) if timeout == Duration::from_millis(50)));
+    let err = anyhow::Error::from(err);
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::ProjectGitTimeout)
+    );
+}
+
+#[test]
+fn fast_operation_completes() {
+    let op = RemoteOp::new().with_timeout(Duration::from_secs(60));
+
+    assert_eq!(op.run(|guard| slow_transfer(guard, 2)).unwrap(), 2);
+    let err = op
+        .run(|_guard| Err::<(), _>(anyhow!("connection refused")))
+        .unwrap_err();
+    assert!(
+        matches!(&err, RemoteError::Other(err) if err.to_string() == "connection refused"),
+        "errors of the operation itself are passed on"
+    );
+}
+
+#[test]
+fn cancellation() {
+    let token = CancellationToken::default();
+    let op = RemoteOp::new().with_cancellation(token.clone());
+    token.cancel();
+
+    let err = op.run(|guard| slow_transfer(guard, 1000)).unwrap_err();
+    assert!(matches!(err, RemoteError::Cancelled));
+    assert!(op.cancellation().is_cancelled());
+    let err = anyhow::Error::from(err);
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::Cancelled)
+    );
+}
+
+#[test]
+fn installed_into_a_fetch() -> anyhow::Result<()> {
+    let source_dir = tempfile::tempdir()?;
+    let source = git2::Repository::init(source_dir.path())?;
+    let signature = git2::Signature::now("test", "test@example.com")?;
+    let tree = source.find_tree(source.treebuilder(None)?.write()?)?;
+    let commit = source.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])?;
+    let target_dir = tempfile::tempdir()?;
+    let target = git2::Repository::init(target_dir.path())?;
+
+    let op = RemoteOp::new().with_timeout(Duration::from_secs(60));
+    op.run(|guard| {
+        let mut callbacks = git2::RemoteCallbacks::new();
+        guard.install(&mut callbacks);
+        let mut remote = target.remote_anonymous(source_dir.path().to_str().unwrap())?;
+        remote.fetch(
+            &["+refs/heads/*:refs/remotes/source/*"],
+            Some(git2::FetchOptions::new().remote_callbacks(callbacks)),
+            None,
+        )
+    })?;
+    let branch = source.head()?.shorthand().unwrap().to_owned();
+    assert_eq!(
+        target
+            .refname_to_id(&format!("refs/remotes/source/{branch}"))
+            .ok(),
+        Some(commit)
+    );
+    Ok(())
+}
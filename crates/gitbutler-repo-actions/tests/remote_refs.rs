@@ -0,0 +1,80 @@
+use std::{cell::Cell, path::Path, time::Duration};
+
+use gitbutler_repo_actions::remote_refs::{AdvertisedRef, RemoteRefsCache};
+
+fn advertisement() -> Vec<AdvertisedRef> {
+    vec![AdvertisedRef {
+        name: "refs/heads/main".into(),
+        target: git2::Oid::from_str("9ad8d6f2e6e5b3d1d8c0f5c9bb0f4d79d8e6b9a1").unwrap(),
+        symref_target: None,
+    }]
+}
+
+#[test]
+fn previews_within_ttl_hit_the_cache() -> anyhow::Result<()> {
+    let cache = RemoteRefsCache::new(Duration::from_secs(60));
+    let listings = Cell::new(0);
+    let list = || {
+        listings.set(listings.get() + 1);
+        Ok(advertisement())
+    };
+    let git_dir = Path::new("/repo/.git");
+
+    assert_eq!(cache.get_or_list(git_dir, "origin", list)?, advertisement());
+    assert_eq!(cache.get_or_list(git_dir, "origin", list)?, advertisement());
+    assert_eq!(
+        listings.get(),
+        1,
+        "the second preview is served from the cache"
+    );
+
+    cache.get_or_list(git_dir, "upstream", list)?;
+    cache.get_or_list(Path::new("/other/.git"), "origin", list)?;
+    assert_eq!(
+        listings.get(),
+        3,
+        "each repository and remote is cached separately"
+    );
+
+    cache.invalidate(git_dir, "origin");
+    cache.get_or_list(git_dir, "origin", list)?;
+    assert_eq!(listings.get(), 4, "invalidation forces a new listing");
+    Ok(())
+}
+
+#[test]
+fn expired_or_disabled() -> anyhow::Result<()> {
+    let cache = RemoteRefsCache::new(Duration::ZERO);
+    let listings = Cell::new(0);
+    let list = || {
+        listings.set(listings.get() + 1);
+        Ok(advertisement())
+    };
+    let git_dir = Path::new("/repo/.git");
+
+    cache.get_or_list(git_dir, "origin", list)?;
+    cache.get_or_list(git_dir, "origin", list)?;
+    assert_eq!(listings.get(), 2, "a zero TTL disables the cache");
+
+    cache.set_ttl(Duration::from_millis(10));
+    cache.get_or_list(git_dir, "origin", list)?;
+    std::thread::sleep(Duration::from_millis(20));
+    cache.get_or_list(git_dir, "origin", list)?;
+    assert_eq!(listings.get(), 4, "expired entries are listed again");
+    Ok(())
+}
+
+#[test]
+fn failures_are_not_cached() {
+    let cache = RemoteRefsCache::default();
+    let git_dir = Path::new("/repo/.git");
+    assert!(cache
+        .get_or_list(git_dir, "origin", || Err(anyhow::anyhow!("offline")))
+        .is_err());
+    assert_eq!(
+        cache
+            .get_or_list(git_dir, "origin", || Ok(advertisement()))
+            .unwrap(),
+        advertisement()
+    );
+}
@@ -0,0 +1,40 @@
+//! Borrow objects from other repositories, so forks and temporary repositories sharing a base
+//! don't have to copy them.
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use gitbutler_error::error::Code;
+
+/// Open the repository at `path` so that objects are also read from each of `alternates`,
+/// which are the `objects` directories of other repositories, like `other/.git/objects`.
+///
+/// Unlike `.git/objects/info/alternates`, this only affects the returned instance and
+/// nothing is written to disk.
+/// Fails with [`Code::Validation`] if one of the `alternates` isn't a directory.
+pub fn open_with_alternates(path: &Path, alternates: &[PathBuf]) -> Result<git2::Repository> {
+    for alternate in alternates {
+        if !alternate.is_dir() {
+            return Err(anyhow!(
+                "The alternate object directory '{}' doesn't exist",
+                alternate.display()
+            )
+            .context(Code::Validation));
+        }
+    }
+    let repo = git2::Repository::open(path)
+        .with_context(|| format!("failed to open repository at '{}'", path.display()))?;
+    {
+        let odb = repo.odb()?;
+        for alternate in alternates {
+            let alternate = alternate.to_str().ok_or_else(|| {
+                anyhow!(
+                    "The alternate object directory '{}' isn't valid UTF-8",
+                    alternate.display()
+                )
+                .context(Code::Validation)
+            })?;
+            odb.add_disk_alternate(alternate)?;
+        }
+    }
+    Ok(repo)
+}
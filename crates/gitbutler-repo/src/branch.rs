@@ -0,0 +1,444 @@
+//! Operations on local branches, i.e. references in `refs/heads/`.
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Context, Result};
+use bstr::ByteSlice;
+use gitbutler_commit::{commit_ext::CommitExt as _, commit_headers::HasCommitHeaders as _};
+use gitbutler_error::error::Code;
+
+use crate::{
+    commit_message::{self, Identity},
+    logging::{CommitSummary, LogUntil, RepositoryExt as _},
+    RepositoryExt as _,
+};
+
+/// Rename the local branch `old` to `new`, keeping its reflog and its upstream configuration
+/// in `branch.<name>.*`. If `old` is checked out, `HEAD` will follow the rename.
+///
+/// It's refused if `new` isn't a valid branch name, or with [`Code::ProjectRefExists`] if it already exists.
+pub fn rename(repo: &git2::Repository, old: &str, new: &str) -> Result<()> {
+    if !git2::Branch::name_is_valid(new)? {
+        return Err(anyhow!("'{new}' isn't a valid branch name").context(Code::Validation));
+    }
+    let mut branch = find_local(repo, old)?;
+    if repo.find_branch(new, git2::BranchType::Local).is_ok() {
+        return Err(
+            anyhow!("Cannot rename '{old}' as branch '{new}' already exists")
+                .context(Code::ProjectRefExists),
+        );
+    }
+    branch.rename(new, false)?;
+    Ok(())
+}
+
+/// Create the local branch `name` pointing to `target`, without checking it out.
+///
+/// It's refused if `name` isn't a valid branch name, or with [`Code::ProjectRefExists`] if it
+/// already exists, unless `force` is set to move the existing branch to `target` instead.
+pub fn create(repo: &git2::Repository, name: &str, target: git2::Oid, force: bool) -> Result<()> {
+    if !git2::Branch::name_is_valid(name)? {
+        return Err(anyhow!("'{name}' isn't a valid branch name").context(Code::Validation));
+    }
+    if !force && repo.find_branch(name, git2::BranchType::Local).is_ok() {
+        return Err(anyhow!("Branch '{name}' already exists").context(Code::ProjectRefExists));
+    }
+    let commit = repo.find_commit(target)?;
+    repo.branch(name, &commit, force)?;
+    Ok(())
+}
+
+/// Options for [`checkout()`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CheckoutOptions {
+    /// If `true`, local changes are overwritten by the checkout.
+    /// Otherwise, the checkout is refused if it would overwrite any of them.
+    pub force: bool,
+}
+
+/// The prefixes of the names of local branches that GitButler manages itself, like `gitbutler/workspace`.
+pub const INTERNAL_NAMESPACES: &[&str] = &["gitbutler/"];
+
+/// Options for [`list()`].
+#[derive(Debug, Clone, Copy)]
+pub struct ListOptions<'a> {
+    /// If `true`, branches in one of the `internal_namespaces` are listed as well.
+    /// Otherwise, only the branches of the user are listed.
+    pub include_internal: bool,
+    /// The prefixes of the names of internal branches, [`INTERNAL_NAMESPACES`] by default.
+    pub internal_namespaces: &'a [&'a str],
+}
+
+impl Default for ListOptions<'_> {
+    fn default() -> Self {
+        ListOptions {
+            include_internal: false,
+            internal_namespaces: INTERNAL_NAMESPACES,
+        }
+    }
+}
+
+/// Return the names of all local branches, sorted, without the internal ones unless
+/// `options.include_internal` is set.
+pub fn list(repo: &git2::Repository, options: ListOptions<'_>) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    for branch in repo.branches(Some(git2::BranchType::Local))? {
+        let (branch, _) = branch?;
+        let Some(name) = branch.name()? else {
+            continue;
+        };
+        let is_internal = options
+            .internal_namespaces
+            .iter()
+            .any(|namespace| name.starts_with(namespace));
+        if options.include_internal || !is_internal {
+            names.push(name.to_owned());
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Return the names of all local branches whose history contains `commit`, sorted, along with
+/// remote-tracking branches like `origin/main` if `include_remote` is `true`.
+///
+/// The commit-graph is used if present, which keeps this fast even with many branches.
+pub fn containing(
+    repo: &git2::Repository,
+    commit: git2::Oid,
+    include_remote: bool,
+) -> Result<Vec<String>> {
+    let branch_type = (!include_remote).then_some(git2::BranchType::Local);
+    let mut names = Vec::new();
+    for branch in repo.branches(branch_type)? {
+        let (branch, _) = branch?;
+        // Symbolic references like `origin/HEAD` just repeat another branch.
+        let Some(tip) = branch.get().target() else {
+            continue;
+        };
+        if tip == commit || repo.graph_descendant_of(tip, commit)? {
+            if let Some(name) = branch.name()? {
+                names.push(name.to_owned());
+            }
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Make the local branch `name` the current branch by checking out its tree and pointing `HEAD` to it.
+///
+/// If `name` is a remote-tracking branch like `origin/feature`, a local branch `feature` tracking it
+/// is created and checked out instead.
+/// Unless `options.force` is set, the checkout fails with [`Code::ProjectCheckoutWouldOverwrite`]
+/// if local changes would be overwritten, leaving the worktree and `HEAD` untouched.
+pub fn checkout(repo: &git2::Repository, name: &str, options: CheckoutOptions) -> Result<()> {
+    let (branch, tracked) = match repo.find_branch(name, git2::BranchType::Local) {
+        Ok(branch) => (branch, None),
+        Err(err) if err.code() == git2::ErrorCode::NotFound => {
+            let remote_branch =
+                repo.find_branch(name, git2::BranchType::Remote)
+                    .map_err(|err| match err.code() {
+                        git2::ErrorCode::NotFound => {
+                            anyhow!("There is no branch named '{name}'").context(Code::Validation)
+                        }
+                        _ => err.into(),
+                    })?;
+            let local_name = local_name_for_remote_tracking_branch(repo, &remote_branch)?;
+            (remote_branch, Some(local_name))
+        }
+        Err(err) => return Err(err.into()),
+    };
+    let commit = branch.get().peel_to_commit()?;
+    let tree = commit.tree()?;
+    let mut checkout = repo.checkout_tree_builder(&tree);
+    if options.force {
+        checkout.force();
+    }
+    checkout.checkout()?;
+
+    let branch = match tracked {
+        None => branch,
+        Some(local_name) => {
+            let mut local_branch = repo.branch(&local_name, &commit, false)?;
+            local_branch.set_upstream(Some(name))?;
+            local_branch
+        }
+    };
+    repo.set_head(
+        branch
+            .get()
+            .name()
+            .context("branch names are always valid UTF-8")?,
+    )?;
+    Ok(())
+}
+
+/// Return the commits reachable from the upstream of the local branch `name` but not from the branch
+/// itself, newest first, i.e. what pulling would bring in.
+///
+/// Only what's known locally is considered, so the remote should be fetched first for this to be
+/// up to date, e.g. with `RepoActionsExt::fetch()`.
+pub fn incoming_commits(repo: &git2::Repository, name: &str) -> Result<Vec<CommitSummary>> {
+    let branch = find_local(repo, name)?;
+    let upstream = branch.upstream().map_err(|err| match err.code() {
+        git2::ErrorCode::NotFound => {
+            anyhow!("The branch '{name}' has no upstream").context(Code::Validation)
+        }
+        _ => err.into(),
+    })?;
+    let local_id = branch.get().peel_to_commit()?.id();
+    let upstream_id = upstream.get().peel_to_commit()?.id();
+    repo.log_summaries(upstream_id, LogUntil::Commit(local_id), true)
+}
+
+/// Return the commits reachable from `branch_tip` but not from its merge base with `target_tip`,
+/// newest first, i.e. what a pull request of the branch into the target would contain.
+///
+/// If the branch doesn't share any history with the target, all of its commits are returned.
+pub fn own_commits(
+    repo: &git2::Repository,
+    branch_tip: git2::Oid,
+    target_tip: git2::Oid,
+) -> Result<Vec<CommitSummary>> {
+    let until = match repo.merge_base(branch_tip, target_tip) {
+        Ok(merge_base) => LogUntil::Commit(merge_base),
+        Err(err) if err.code() == git2::ErrorCode::NotFound => LogUntil::End,
+        Err(err) => return Err(err.into()),
+    };
+    repo.log_summaries(branch_tip, until, true)
+}
+
+/// The branch on a remote that a local branch is configured to track.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpstreamRef {
+    /// The name of the remote, like `origin`, as stored in `branch.<name>.remote`.
+    pub remote: String,
+    /// The name of the branch on the remote, like `main`, as stored in `branch.<name>.merge`.
+    pub remote_branch: String,
+}
+
+/// Return the upstream configured for the local branch `name`, or `None` if it has none.
+///
+/// Unlike [`incoming_commits()`], this only reads the configuration, so the remote-tracking branch
+/// doesn't have to exist yet, like before the branch was pushed for the first time.
+pub fn upstream(repo: &git2::Repository, name: &str) -> Result<Option<UpstreamRef>> {
+    find_local(repo, name)?;
+    let config = repo.config()?.snapshot()?;
+    let get = |key: &str| match config.get_string(&format!("branch.{name}.{key}")) {
+        Ok(value) => Ok(Some(value)),
+        Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(None),
+        Err(err) => Err(err),
+    };
+    let (Some(remote), Some(merge)) = (get("remote")?, get("merge")?) else {
+        return Ok(None);
+    };
+    let remote_branch = merge
+        .strip_prefix("refs/heads/")
+        .map(ToOwned::to_owned)
+        .unwrap_or(merge);
+    Ok(Some(UpstreamRef {
+        remote,
+        remote_branch,
+    }))
+}
+
+/// Configure the local branch `name` to track `remote_branch` of `remote`, like `main` of `origin`.
+///
+/// Neither the remote-tracking branch nor the branch on the remote have to exist, so this can be used
+/// to decide where a branch is pushed to for the first time, but `remote` must be a known remote.
+pub fn set_upstream(
+    repo: &git2::Repository,
+    name: &str,
+    remote: &str,
+    remote_branch: &str,
+) -> Result<()> {
+    find_local(repo, name)?;
+    repo.find_remote(remote).map_err(|err| match err.code() {
+        git2::ErrorCode::NotFound => {
+            anyhow!("There is no remote named '{remote}'").context(Code::Validation)
+        }
+        _ => err.into(),
+    })?;
+    let mut config = repo.config()?;
+    config.set_str(&format!("branch.{name}.remote"), remote)?;
+    config.set_str(
+        &format!("branch.{name}.merge"),
+        &format!("refs/heads/{remote_branch}"),
+    )?;
+    Ok(())
+}
+
+/// Options for [`rewrite_identity()`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RewriteIdentityOptions {
+    /// If `true`, commits that were already pushed to the upstream of the branch are rewritten as well.
+    /// Otherwise, only the commits that aren't reachable from the upstream are.
+    pub force: bool,
+}
+
+/// Replace `from` with `to` wherever it's the author or committer of a commit of the local branch
+/// `name`, like when committing with the wrong email, and point the branch to the rewritten commits.
+/// Returns the new tip, which is the old one if no commit had to be changed.
+///
+/// Dates, messages and trees are kept, so the worktree doesn't change even if `name` is checked out.
+/// Published history is left alone unless `options.force` is set, and all history is rewritten
+/// if there is no upstream.
+pub fn rewrite_identity(
+    repo: &git2::Repository,
+    name: &str,
+    from: &Identity,
+    to: &Identity,
+    options: RewriteIdentityOptions,
+) -> Result<git2::Oid> {
+    let mut branch = find_local(repo, name)?;
+    let tip = branch.get().peel_to_commit()?.id();
+    let mut walk = repo.revwalk()?;
+    walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+    walk.push(tip)?;
+    if !options.force {
+        match branch.upstream() {
+            Ok(upstream) => walk.hide(upstream.get().peel_to_commit()?.id())?,
+            Err(err) if err.code() == git2::ErrorCode::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    // Return the replacement of `signature`, or `None` if it's not to be replaced.
+    let replace = |signature: &git2::Signature<'_>| -> Result<Option<git2::Signature<'static>>> {
+        if from.name != signature.name_bytes() || from.email != signature.email_bytes() {
+            return Ok(None);
+        }
+        Ok(Some(git2::Signature::new(
+            &to.name.to_str_lossy(),
+            &to.email.to_str_lossy(),
+            &signature.when(),
+        )?))
+    };
+    let mut rewritten = HashMap::<git2::Oid, git2::Oid>::new();
+    for id in walk {
+        let commit = repo.find_commit(id?)?;
+        let (author, committer) = (commit.author(), commit.committer());
+        let new_author = replace(&author)?;
+        let new_committer = replace(&committer)?;
+        let parents_rewritten = commit.parent_ids().any(|id| rewritten.contains_key(&id));
+        if new_author.is_none() && new_committer.is_none() && !parents_rewritten {
+            continue;
+        }
+        let parents = commit
+            .parent_ids()
+            .map(|id| repo.find_commit(rewritten.get(&id).copied().unwrap_or(id)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let new_id = repo.commit_with_signature(
+            None,
+            new_author.as_ref().unwrap_or(&author),
+            new_committer.as_ref().unwrap_or(&committer),
+            &commit.message_bstr().to_str_lossy(),
+            &commit.tree()?,
+            &parents.iter().collect::<Vec<_>>(),
+            commit.gitbutler_headers(),
+        )?;
+        rewritten.insert(commit.id(), new_id);
+    }
+
+    let Some(new_tip) = rewritten.get(&tip).copied() else {
+        return Ok(tip);
+    };
+    branch
+        .get_mut()
+        .set_target(new_tip, "GitButler: rewrite identity")?;
+    Ok(new_tip)
+}
+
+/// Add a `Signed-off-by` trailer for `identity` to the messages of all commits of the local branch
+/// `name` that aren't reachable from its upstream, like `git rebase --signoff` would, and point the
+/// branch to the rewritten commits. Returns the new tip, which is the old one if all commits were
+/// signed off by `identity` already.
+///
+/// Commits already signed off by `identity` keep their message, so running this again doesn't add
+/// another trailer. All history is rewritten if there is no upstream.
+pub fn add_signoff(repo: &git2::Repository, name: &str, identity: &Identity) -> Result<git2::Oid> {
+    let mut branch = find_local(repo, name)?;
+    let tip = branch.get().peel_to_commit()?.id();
+    let mut walk = repo.revwalk()?;
+    walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+    walk.push(tip)?;
+    match branch.upstream() {
+        Ok(upstream) => walk.hide(upstream.get().peel_to_commit()?.id())?,
+        Err(err) if err.code() == git2::ErrorCode::NotFound => {}
+        Err(err) => return Err(err.into()),
+    }
+
+    let mut rewritten = HashMap::<git2::Oid, git2::Oid>::new();
+    for id in walk {
+        let commit = repo.find_commit(id?)?;
+        let new_message = commit_message::with_signoff(commit.message_bstr(), identity);
+        let parents_rewritten = commit.parent_ids().any(|id| rewritten.contains_key(&id));
+        if new_message.is_none() && !parents_rewritten {
+            continue;
+        }
+        let message = new_message.unwrap_or_else(|| commit.message_bstr().to_owned());
+        let parents = commit
+            .parent_ids()
+            .map(|id| repo.find_commit(rewritten.get(&id).copied().unwrap_or(id)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let new_id = repo.commit_with_signature(
+            None,
+            &commit.author(),
+            &commit.committer(),
+            &message.to_str_lossy(),
+            &commit.tree()?,
+            &parents.iter().collect::<Vec<_>>(),
+            commit.gitbutler_headers(),
+        )?;
+        rewritten.insert(commit.id(), new_id);
+    }
+
+    let Some(new_tip) = rewritten.get(&tip).copied() else {
+        return Ok(tip);
+    };
+    branch
+        .get_mut()
+        .set_target(new_tip, "GitButler: add sign-off")?;
+    Ok(new_tip)
+}
+
+/// Return the name of the local branch to create for `remote_branch`, which is named like
+/// the branch on the remote.
+fn local_name_for_remote_tracking_branch(
+    repo: &git2::Repository,
+    remote_branch: &git2::Branch<'_>,
+) -> Result<String> {
+    let refname = remote_branch
+        .get()
+        .name()
+        .context("branch names are always valid UTF-8")?;
+    let remote_name = repo.branch_remote_name(refname)?;
+    let remote_name = remote_name
+        .as_str()
+        .context("remote names are always valid UTF-8")?;
+    let local_name = refname
+        .strip_prefix("refs/remotes/")
+        .and_then(|name| name.strip_prefix(remote_name))
+        .and_then(|name| name.strip_prefix('/'))
+        .with_context(|| format!("'{refname}' must be a branch of remote '{remote_name}'"))?;
+    if repo
+        .find_branch(local_name, git2::BranchType::Local)
+        .is_ok()
+    {
+        return Err(anyhow!(
+            "Cannot check out '{refname}' as local branch '{local_name}' already exists"
+        )
+        .context(Code::Validation));
+    }
+    Ok(local_name.to_owned())
+}
+
+fn find_local<'repo>(repo: &'repo git2::Repository, name: &str) -> Result<git2::Branch<'repo>> {
+    repo.find_branch(name, git2::BranchType::Local)
+        .map_err(|err| match err.code() {
+            git2::ErrorCode::NotFound => {
+                anyhow!("There is no local branch named '{name}'").context(Code::Validation)
+            }
+            _ => err.into(),
+        })
+}
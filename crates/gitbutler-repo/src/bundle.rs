@@ -0,0 +1,98 @@
+use std::{collections::HashMap, path::Path};
+
+use anyhow::{anyhow, Result};
+use gitbutler_error::error::Code;
+
+use crate::git_cli;
+
+/// Write all `refs` along with the history they point to into a bundle file at `path`,
+/// to allow to move work between machines that don't share a remote.
+///
+/// `refs` may be full reference names or names that are resolvable as short names, like `main`.
+/// All of them must exist, or nothing is written.
+pub fn create_bundle(repo: &git2::Repository, refs: &[String], path: &Path) -> Result<()> {
+    if refs.is_empty() {
+        return Err(
+            anyhow!("At least one reference is needed to create a bundle")
+                .context(Code::Validation),
+        );
+    }
+    let mut full_names = Vec::with_capacity(refs.len());
+    for name in refs {
+        let reference = repo.resolve_reference_from_short_name(name).map_err(|_| {
+            anyhow!("Cannot bundle '{name}' as no such reference exists").context(Code::Validation)
+        })?;
+        full_names.push(
+            reference
+                .name()
+                .ok_or_else(|| anyhow!("reference '{name}' isn't valid UTF-8"))?
+                .to_owned(),
+        );
+    }
+
+    let mut cmd = git_cli::git(repo);
+    cmd.args(["bundle", "create", "--quiet"])
+        .arg(path)
+        .args(&full_names);
+    git_cli::run(cmd)?;
+    Ok(())
+}
+
+/// The result of [`apply_bundle()`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AppliedBundle {
+    /// The names of the references that were created or fast-forwarded by the bundle.
+    pub updated: Vec<String>,
+    /// The names of the references that were left as they are as the bundle doesn't contain
+    /// their history, i.e. they are ahead of the bundle or diverged from it.
+    pub rejected: Vec<String>,
+}
+
+/// Fetch all references contained in the bundle at `path` into `repo` under their own names,
+/// and return which references were created or changed by it, and which were rejected.
+///
+/// Existing references are only fast-forwarded, so local commits are never lost. Note that updating
+/// the currently checked out branch is refused, just like `git fetch` does.
+pub fn apply_bundle(repo: &git2::Repository, path: &Path) -> Result<AppliedBundle> {
+    let mut cmd = git_cli::git(repo);
+    cmd.args(["bundle", "list-heads"]).arg(path);
+    let heads = git_cli::run(cmd)
+        .map_err(|err| err.context(format!("'{}' isn't a valid bundle", path.display())))
+        .map_err(|err| err.context(Code::Validation))?;
+    let heads: Vec<(git2::Oid, String)> = heads
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .map(|(id, name)| (id, name.trim()))
+        .filter(|(_id, name)| name.starts_with("refs/"))
+        .map(|(id, name)| Ok((git2::Oid::from_str(id)?, name.to_owned())))
+        .collect::<Result<_>>()?;
+    if heads.is_empty() {
+        return Ok(AppliedBundle::default());
+    }
+
+    let previous_targets: HashMap<_, _> = heads
+        .iter()
+        .map(|(_id, name)| (name.as_str(), repo.refname_to_id(name).ok()))
+        .collect();
+
+    let mut cmd = git_cli::git(repo);
+    cmd.args(["fetch", "--quiet"])
+        .arg(path)
+        .args(heads.iter().map(|(_id, name)| format!("{name}:{name}")));
+    // Fetching fails if any reference can't be fast-forwarded, but still updates all others.
+    let fetch_result = git_cli::run(cmd);
+
+    let mut applied = AppliedBundle::default();
+    for (id, name) in &heads {
+        let target = repo.refname_to_id(name).ok();
+        if target != previous_targets[name.as_str()] {
+            applied.updated.push(name.clone());
+        } else if target != Some(*id) {
+            applied.rejected.push(name.clone());
+        }
+    }
+    match fetch_result {
+        Err(err) if applied.rejected.is_empty() => Err(err),
+        _ => Ok(applied),
+    }
+}
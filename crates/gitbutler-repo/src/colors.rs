@@ -0,0 +1,141 @@
+//! Read the colors the user configured for `git diff`, so the UI can optionally match them.
+use anyhow::{anyhow, Result};
+use gitbutler_error::error::Code;
+use serde::Serialize;
+
+/// The colors of the parts of a diff, as configured with `color.diff.*`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffColors {
+    /// The color of added lines, from `color.diff.new`.
+    pub added: Color,
+    /// The color of removed lines, from `color.diff.old`.
+    pub removed: Color,
+    /// The color of unchanged lines, from `color.diff.context`, or its older name `color.diff.plain`.
+    pub context: Color,
+    /// The color of the file headers, from `color.diff.meta`.
+    pub meta: Color,
+}
+
+impl Default for DiffColors {
+    /// The colors Git uses if none are configured.
+    fn default() -> Self {
+        DiffColors {
+            added: Color::foreground(ColorValue::Named("green".into())),
+            removed: Color::foreground(ColorValue::Named("red".into())),
+            context: Color::default(),
+            meta: Color {
+                attributes: vec!["bold".into()],
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// A color as configured in Git, like `bold red` or `#ff0000 black`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Color {
+    /// The color of the text, or `None` to not change it.
+    pub foreground: Option<ColorValue>,
+    /// The color behind the text, or `None` to not change it.
+    pub background: Option<ColorValue>,
+    /// Attributes like `bold`, `ul` or `nobold`, in the order they were configured.
+    pub attributes: Vec<String>,
+}
+
+impl Color {
+    fn foreground(value: ColorValue) -> Self {
+        Color {
+            foreground: Some(value),
+            ..Default::default()
+        }
+    }
+}
+
+/// A single color, see `git help config` for details.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ColorValue {
+    /// One of the named colors like `red`, `brightblue`, `normal` or `default`.
+    Named(String),
+    /// One of the 256 colors of the ANSI palette.
+    Ansi(u8),
+    /// A 24-bit color, configured as `#rrggbb` or `#rgb`.
+    Rgb(u8, u8, u8),
+}
+
+const COLOR_NAMES: &[&str] = &[
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+const ATTRIBUTES: &[&str] = &["bold", "dim", "ul", "blink", "reverse", "italic", "strike"];
+
+/// Parse `value` like Git does, with the first color being the foreground and the second one the
+/// background.
+///
+/// Fails with [`Code::Validation`] if `value` contains unknown words or more than two colors.
+pub fn parse_color(value: &str) -> Result<Color> {
+    let mut color = Color::default();
+    for word in value.split_whitespace() {
+        let word = word.to_ascii_lowercase();
+        if let Some(parsed) = parse_color_value(&word) {
+            if color.foreground.is_none() {
+                color.foreground = Some(parsed);
+            } else if color.background.is_none() {
+                color.background = Some(parsed);
+            } else {
+                return Err(anyhow!(
+                    "'{word}' is a third color in '{value}', but at most two are allowed"
+                )
+                .context(Code::Validation));
+            }
+        } else if word == "reset" || is_attribute(&word) {
+            color.attributes.push(word);
+        } else {
+            return Err(
+                anyhow!("'{word}' in '{value}' is neither a color nor an attribute")
+                    .context(Code::Validation),
+            );
+        }
+    }
+    Ok(color)
+}
+
+fn parse_color_value(word: &str) -> Option<ColorValue> {
+    if word == "normal" || word == "default" {
+        return Some(ColorValue::Named(word.into()));
+    }
+    if COLOR_NAMES.contains(&word.strip_prefix("bright").unwrap_or(word)) {
+        return Some(ColorValue::Named(word.into()));
+    }
+    if let Some(hex) = word.strip_prefix('#') {
+        if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |digits: &str| u8::from_str_radix(digits, 16).ok();
+        return match hex.len() {
+            6 => Some(ColorValue::Rgb(
+                channel(&hex[0..2])?,
+                channel(&hex[2..4])?,
+                channel(&hex[4..6])?,
+            )),
+            // Each digit is repeated, so `#f80` is `#ff8800`.
+            3 => Some(ColorValue::Rgb(
+                channel(&hex[0..1])? * 0x11,
+                channel(&hex[1..2])? * 0x11,
+                channel(&hex[2..3])? * 0x11,
+            )),
+            _ => None,
+        };
+    }
+    word.parse().ok().map(ColorValue::Ansi)
+}
+
+fn is_attribute(word: &str) -> bool {
+    let word = word
+        .strip_prefix("no-")
+        .or_else(|| word.strip_prefix("no"))
+        .unwrap_or(word);
+    ATTRIBUTES.contains(&word)
+}
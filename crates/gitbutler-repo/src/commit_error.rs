@@ -0,0 +1,81 @@
+use std::fmt;
+
+use gitbutler_error::error::{AnyhowContextExt, Code, Context};
+
+/// The ways creating a commit can fail that the frontend may want to react to.
+///
+/// Each variant knows its own classification, see [`CommitError::into_context()`], which is
+/// attached when converting into an `anyhow::Error`.
+#[derive(Debug)]
+pub enum CommitError {
+    /// The commit would have the same tree as its parent, so there is nothing to commit.
+    EmptyTree,
+    /// There is no author configured in Git.
+    MissingIdentity,
+    /// A hook refused the commit, with its output.
+    HookRejected(String),
+    /// Some of the files to commit are still conflicted.
+    Conflict,
+    /// Anything we don't know how to classify, passed on as is.
+    Other(anyhow::Error),
+}
+
+impl CommitError {
+    /// Return the context to attach to this error so the frontend can classify it.
+    pub fn into_context(&self) -> Context {
+        match self {
+            CommitError::EmptyTree => Context::new_static(
+                Code::Validation,
+                "There are no changes to commit in the selected files",
+            ),
+            CommitError::MissingIdentity => Context::new_static(
+                Code::AuthorMissing,
+                "No author is configured in Git - set `user.name` and `user.email` to be able to commit",
+            ),
+            CommitError::HookRejected(output) => {
+                Context::new(format!("A hook rejected the commit:\n{output}"))
+                    .with_code(Code::Validation)
+            }
+            CommitError::Conflict => Context::new_static(
+                Code::CommitMergeConflictFailure,
+                "Conflicted files have to be resolved before they can be committed",
+            ),
+            CommitError::Other(_) => Context::default(),
+        }
+    }
+}
+
+impl fmt::Display for CommitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommitError::EmptyTree => f.write_str("nothing to commit"),
+            CommitError::MissingIdentity => f.write_str("author is missing"),
+            CommitError::HookRejected(output) => write!(f, "hook rejected the commit: {output}"),
+            CommitError::Conflict => f.write_str("files to commit are conflicted"),
+            CommitError::Other(err) => fmt::Display::fmt(err, f),
+        }
+    }
+}
+
+impl From<CommitError> for anyhow::Error {
+    fn from(err: CommitError) -> Self {
+        let context = err.into_context();
+        match err {
+            // Keep the original chain, there is nothing we could add to it.
+            CommitError::Other(err) => err,
+            err => anyhow::Error::msg(err.to_string()).context(context),
+        }
+    }
+}
+
+/// Classify errors by the code they were already given, so failures of the operations a commit is
+/// built from, like [`signatures()`](crate::RepositoryExt::signatures()), are reported consistently.
+impl From<anyhow::Error> for CommitError {
+    fn from(err: anyhow::Error) -> Self {
+        match err.custom_context().map(|ctx| ctx.code) {
+            Some(Code::AuthorMissing) => CommitError::MissingIdentity,
+            Some(Code::CommitMergeConflictFailure) => CommitError::Conflict,
+            _ => CommitError::Other(err),
+        }
+    }
+}
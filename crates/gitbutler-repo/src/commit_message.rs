@@ -33,11 +33,19 @@ impl CommitMessage {
     }
 
     pub fn new(commit: gix::objs::CommitRef<'_>) -> Self {
-        let message_ref = commit.message();
+        Self::from_message_ref(commit.message())
+    }
+
+    /// Parse `message`, the complete message of a commit.
+    pub fn from_bytes(message: &[u8]) -> Self {
+        Self::from_message_ref(gix::objs::commit::MessageRef::from_bytes(message))
+    }
+
+    fn from_message_ref(message_ref: gix::objs::commit::MessageRef<'_>) -> Self {
         let body_ref = message_ref.body();
 
         CommitMessage {
-            title: commit.message().title.to_owned(),
+            title: message_ref.title.to_owned(),
             body: body_ref
                 .map(|body_ref| body_ref.without_trailer().as_bstr().to_owned())
                 .unwrap_or_default(),
@@ -52,3 +60,80 @@ impl CommitMessage {
         }
     }
 }
+
+/// A person as written in trailers like `Co-authored-by: Name <email>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Identity {
+    pub name: BString,
+    pub email: BString,
+}
+
+impl Identity {
+    /// Parse `Name <email>`, or return `None` if there is no email in angle brackets.
+    fn from_trailer_value(value: &[u8]) -> Option<Self> {
+        let value = value.trim();
+        let email_start = value.rfind_byte(b'<')?;
+        let email = value[email_start + 1..].strip_suffix(b">")?;
+        Some(Identity {
+            name: value[..email_start].trim().into(),
+            email: email.trim().into(),
+        })
+    }
+
+    /// Format as `Name <email>` for use in trailers.
+    fn to_trailer_value(&self) -> BString {
+        let mut out = self.name.clone();
+        out.push_str(" <");
+        out.push_str(&self.email);
+        out.push_str(">");
+        out
+    }
+}
+
+/// Trailer interpretation
+impl CommitMessage {
+    /// Return everyone mentioned in `Co-authored-by` trailers, in order.
+    pub fn co_authors(&self) -> Vec<Identity> {
+        self.trailer_values("Co-authored-by")
+            .filter_map(Identity::from_trailer_value)
+            .collect()
+    }
+
+    /// Return `true` if there is at least one `Signed-off-by` trailer.
+    pub fn is_signed_off(&self) -> bool {
+        self.trailer_values("Signed-off-by").next().is_some()
+    }
+
+    /// Return `true` if there is a `Signed-off-by` trailer for `identity`.
+    pub fn is_signed_off_by(&self, identity: &Identity) -> bool {
+        self.trailer_values("Signed-off-by")
+            .filter_map(Identity::from_trailer_value)
+            .any(|signer| signer == *identity)
+    }
+
+    /// Trailer tokens are case-insensitive.
+    fn trailer_values<'a>(&'a self, token: &'a str) -> impl Iterator<Item = &'a BString> + 'a {
+        self.trailers
+            .iter()
+            .filter(move |(key, _)| key.eq_ignore_ascii_case(token.as_bytes()))
+            .map(|(_, value)| value)
+    }
+}
+
+/// Return `message` with a `Signed-off-by` trailer for `identity` appended to its trailers, like
+/// `git commit --signoff` does, or `None` if it's already signed off by `identity`.
+pub fn with_signoff(message: &[u8], identity: &Identity) -> Option<BString> {
+    if CommitMessage::from_bytes(message).is_signed_off_by(identity) {
+        return None;
+    }
+    let has_trailers = gix::objs::commit::MessageRef::from_bytes(message)
+        .body()
+        .is_some_and(|body| body.trailers().next().is_some());
+    let mut out = BString::from(message.trim_end());
+    // Trailers must be in the last paragraph, which is only started if there are none yet.
+    out.push_str(if has_trailers { "\n" } else { "\n\n" });
+    out.push_str("Signed-off-by: ");
+    out.push_str(identity.to_trailer_value());
+    out.push_str("\n");
+    Some(out)
+}
@@ -48,7 +48,11 @@ impl Config<'_> {
             .and_then(|s| Vec::from(Cow::into_owned(s)).into_string().ok()))
     }
 
-    fn get_string(&self, key: &str) -> Result<Option<String>> {
+    /// Return the value of `key` as seen by Git, or `None` if it isn't set.
+    ///
+    /// Like with `git config`, files included with `include.path` are read, and so are the ones
+    /// of `includeIf` if their `gitdir:` or `onbranch:` condition matches this repository.
+    pub fn get_string(&self, key: &str) -> Result<Option<String>> {
         let config = self.git_repository.config()?;
         match config.get_string(key) {
             Ok(value) => Ok(Some(value)),
@@ -1,4 +1,9 @@
-use std::{path::PathBuf, str::FromStr, vec};
+use std::{
+    path::PathBuf,
+    str::FromStr,
+    sync::{LazyLock, Mutex},
+    vec,
+};
 
 use anyhow::Context;
 use gitbutler_command_context::CommandContext;
@@ -68,6 +73,97 @@ impl From<Credential> for git2::RemoteCallbacks<'_> {
     }
 }
 
+/// A credential to use for all remotes whose URL matches `pattern`, like a key for work repositories
+/// that must not be offered to other hosts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CredentialProfile {
+    /// Either a host like `github.com`, which matches all of its repositories, or a glob that is
+    /// matched against the host followed by the path, like `github.com/work-org/*`, where `*`
+    /// matches any amount of characters.
+    pub pattern: String,
+    /// The credential to use instead of the ones derived from the project's preferred key.
+    pub credential: Credential,
+}
+
+impl CredentialProfile {
+    fn matches(&self, url: &Url) -> bool {
+        let Some(host) = url.host.as_deref() else {
+            return false;
+        };
+        if !self.pattern.contains(['/', '*']) {
+            return self.pattern.eq_ignore_ascii_case(host);
+        }
+        let path = url.path.to_string();
+        glob_matches(
+            &self.pattern,
+            &format!("{host}/{}", path.trim_start_matches('/')),
+        )
+    }
+}
+
+/// The profiles consulted by [`help()`] before falling back to the project's preferred key.
+///
+/// If multiple profiles match a URL, the one registered first wins.
+#[derive(Debug, Default)]
+pub struct CredentialProfiles {
+    profiles: Mutex<Vec<CredentialProfile>>,
+}
+
+impl CredentialProfiles {
+    /// Add `profile`, replacing the one with the same pattern.
+    pub fn register(&self, profile: CredentialProfile) {
+        let mut profiles = self.profiles.lock().unwrap();
+        match profiles
+            .iter_mut()
+            .find(|existing| existing.pattern == profile.pattern)
+        {
+            Some(existing) => *existing = profile,
+            None => profiles.push(profile),
+        }
+    }
+
+    /// Remove the profile with `pattern`, returning it if it was registered.
+    pub fn remove(&self, pattern: &str) -> Option<CredentialProfile> {
+        let mut profiles = self.profiles.lock().unwrap();
+        let idx = profiles
+            .iter()
+            .position(|profile| profile.pattern == pattern)?;
+        Some(profiles.remove(idx))
+    }
+
+    /// Return the credential of the first profile matching `url`.
+    pub fn find(&self, url: &Url) -> Option<Credential> {
+        self.profiles
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|profile| profile.matches(url))
+            .map(|profile| profile.credential.clone())
+    }
+}
+
+static GLOBAL_CREDENTIAL_PROFILES: LazyLock<CredentialProfiles> = LazyLock::new(Default::default);
+
+/// Get the profiles used by [`help()`].
+pub fn get_profiles() -> &'static CredentialProfiles {
+    &GLOBAL_CREDENTIAL_PROFILES
+}
+
+/// Return `true` if `text` matches `pattern` as a whole, where `*` matches any amount of characters.
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let Some((prefix, rest)) = pattern.split_once('*') else {
+        return pattern == text;
+    };
+    let Some(text) = text.strip_prefix(prefix) else {
+        return false;
+    };
+    // Try all positions for the `*`, there are only few of them in practice.
+    text.char_indices()
+        .map(|(idx, _)| idx)
+        .chain(Some(text.len()))
+        .any(|idx| glob_matches(rest, &text[idx..]))
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum HelpError {
     #[error("no url set for remote")]
@@ -93,14 +189,18 @@ pub fn help<'a>(
         return Ok(vec![(remote, vec![Credential::Noop])]);
     }
 
+    if let Some(credential) = get_profiles().find(&remote_url) {
+        let remote = match &credential {
+            Credential::Noop => remote,
+            Credential::Ssh(_) => ssh_remote(ctx, remote, &remote_url)?,
+            Credential::Https(_) => https_remote(ctx, remote, &remote_url)?,
+        };
+        return Ok(vec![(remote, vec![credential])]);
+    }
+
     match &ctx.project().preferred_key {
         AuthKey::Local { private_key_path } => {
-            let ssh_remote = if remote_url.scheme == Scheme::Ssh {
-                Ok(remote)
-            } else {
-                let ssh_url = remote_url.as_ssh()?;
-                ctx.repo().remote_anonymous(&ssh_url.to_string())
-            }?;
+            let ssh_remote = ssh_remote(ctx, remote, &remote_url)?;
 
             Ok(vec![(
                 ssh_remote,
@@ -111,12 +211,7 @@ pub fn help<'a>(
             )])
         }
         AuthKey::GitCredentialsHelper => {
-            let https_remote = if remote_url.scheme == Scheme::Https {
-                Ok(remote)
-            } else {
-                let url = remote_url.as_https()?;
-                ctx.repo().remote_anonymous(&url.to_string())
-            }?;
+            let https_remote = https_remote(ctx, remote, &remote_url)?;
             let flow = https_flow(ctx, &remote_url)?
                 .into_iter()
                 .map(Credential::Https)
@@ -132,6 +227,32 @@ pub fn help<'a>(
     }
 }
 
+/// Return `remote`, or an anonymous remote with the SSH version of `remote_url` if it uses another scheme.
+fn ssh_remote<'a>(
+    ctx: &'a CommandContext,
+    remote: git2::Remote<'a>,
+    remote_url: &Url,
+) -> Result<git2::Remote<'a>, HelpError> {
+    if remote_url.scheme == Scheme::Ssh {
+        return Ok(remote);
+    }
+    let ssh_url = remote_url.as_ssh()?;
+    Ok(ctx.repo().remote_anonymous(&ssh_url.to_string())?)
+}
+
+/// Return `remote`, or an anonymous remote with the HTTPS version of `remote_url` if it uses another scheme.
+fn https_remote<'a>(
+    ctx: &'a CommandContext,
+    remote: git2::Remote<'a>,
+    remote_url: &Url,
+) -> Result<git2::Remote<'a>, HelpError> {
+    if remote_url.scheme == Scheme::Https {
+        return Ok(remote);
+    }
+    let url = remote_url.as_https()?;
+    Ok(ctx.repo().remote_anonymous(&url.to_string())?)
+}
+
 fn https_flow(ctx: &CommandContext, remote_url: &Url) -> Result<Vec<HttpsCredential>, HelpError> {
     let mut flow = vec![];
 
@@ -0,0 +1,148 @@
+//! Throw away uncommitted changes in the worktree, either of whole files or of single hunks.
+use std::{
+    cell::Cell,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use git2::{ApplyLocation, ApplyOptions};
+use gitbutler_diff::GitHunk;
+use gitbutler_error::error::Code;
+
+/// Options for [`discard()`] and [`discard_hunk()`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DiscardOptions {
+    /// If `true`, conflicted paths are discarded as well, which resolves the conflict by restoring
+    /// them to their state in `HEAD`.
+    /// Otherwise, the operation is refused if any of the paths is conflicted.
+    pub force: bool,
+}
+
+/// Restore each of `paths` in the worktree of `repo` to its state in the index, or in `HEAD` if it's
+/// not in the index, and delete them if they are untracked.
+///
+/// Nothing is changed if one of the paths is conflicted and `options.force` isn't set.
+pub fn discard(repo: &git2::Repository, paths: &[PathBuf], options: DiscardOptions) -> Result<()> {
+    let mut index = repo.index()?;
+    let mut from_index = Vec::new();
+    let mut from_head = Vec::new();
+    let mut untracked = Vec::new();
+    let mut conflicted = Vec::new();
+    for path in paths {
+        let status = status(repo, path, options)?;
+        if status.contains(git2::Status::WT_NEW) {
+            untracked.push(path);
+        } else if status.contains(git2::Status::CONFLICTED) {
+            conflicted.push(path);
+        } else if index.get_path(path, 0).is_some() {
+            from_index.push(path);
+        } else {
+            from_head.push(path);
+        }
+    }
+    if !conflicted.is_empty() {
+        for path in &conflicted {
+            index.conflict_remove(path)?;
+        }
+        index.write()?;
+        from_head.extend(conflicted);
+    }
+
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| anyhow!("Can't discard changes in a bare repository"))?;
+    for path in untracked {
+        let path = workdir.join(path);
+        if path.is_dir() {
+            std::fs::remove_dir_all(path)?;
+        } else {
+            std::fs::remove_file(path)?;
+        }
+    }
+    if !from_index.is_empty() {
+        repo.checkout_index(None, Some(&mut checkout_paths(&from_index)))?;
+    }
+    if !from_head.is_empty() {
+        repo.checkout_head(Some(&mut checkout_paths(&from_head)))?;
+    }
+    Ok(())
+}
+
+/// Undo `hunk` of the changes between the index and the worktree of `repo` to the file at `path`,
+/// by applying it in reverse to the worktree.
+///
+/// Fails if `hunk` is binary or if it isn't one of the current changes, and like [`discard()`],
+/// if `path` is conflicted and `options.force` isn't set.
+pub fn discard_hunk(
+    repo: &git2::Repository,
+    path: &Path,
+    hunk: &GitHunk,
+    options: DiscardOptions,
+) -> Result<()> {
+    status(repo, path, options)?;
+    let reversed = gitbutler_diff::reverse_hunk(hunk).ok_or_else(|| {
+        anyhow!(
+            "Can't discard a single hunk of the binary file '{}'",
+            path.display()
+        )
+        .context(Code::Validation)
+    })?;
+
+    let index = repo.index()?;
+    let mut diff_opts = git2::DiffOptions::new();
+    diff_opts
+        .reverse(true)
+        .ignore_submodules(true)
+        .context_lines(3)
+        .pathspec(path)
+        .disable_pathspec_match(true);
+    let diff = repo.diff_index_to_workdir(Some(&index), Some(&mut diff_opts))?;
+
+    let found = Cell::new(false);
+    let mut apply_opts = ApplyOptions::new();
+    apply_opts.hunk_callback(|cb_hunk| {
+        let matches = cb_hunk.is_some_and(|cb_hunk| &reversed == cb_hunk);
+        if matches {
+            found.set(true);
+        }
+        matches
+    });
+    repo.apply(&diff, ApplyLocation::WorkDir, Some(&mut apply_opts))?;
+    if !found.get() {
+        return Err(anyhow!(
+            "The hunk to discard isn't among the changes of '{}'",
+            path.display()
+        )
+        .context(Code::Validation));
+    }
+    Ok(())
+}
+
+/// Return the status of `path`, failing if it's conflicted and that isn't allowed by `options`.
+fn status(repo: &git2::Repository, path: &Path, options: DiscardOptions) -> Result<git2::Status> {
+    let status = repo.status_file(path).map_err(|err| match err.code() {
+        git2::ErrorCode::NotFound => anyhow!(
+            "'{}' is neither tracked nor present in the worktree",
+            path.display()
+        )
+        .context(Code::Validation),
+        _ => err.into(),
+    })?;
+    if status.contains(git2::Status::CONFLICTED) && !options.force {
+        return Err(anyhow!(
+            "Refusing to discard changes of the conflicted file '{}'",
+            path.display()
+        )
+        .context(Code::Validation));
+    }
+    Ok(status)
+}
+
+fn checkout_paths(paths: &[&PathBuf]) -> git2::build::CheckoutBuilder<'static> {
+    let mut checkout = git2::build::CheckoutBuilder::new();
+    checkout.force().disable_pathspecs(true);
+    for path in paths {
+        checkout.path(path.as_path());
+    }
+    checkout
+}
@@ -0,0 +1,55 @@
+//! Export commits as patch files, to contribute to projects that accept patches by mail.
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use gitbutler_error::error::Code;
+
+use crate::git_cli;
+
+/// Options for [`format_patch()`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FormatPatchOptions {
+    /// If `true`, also write `0000-cover-letter.patch` with a summary of the series and
+    /// placeholders for its subject and description, to be filled in before sending.
+    pub cover_letter: bool,
+}
+
+/// Write each commit in `range`, which excludes `range.start` and includes `range.end` like the
+/// `start..end` notation of Git does, as numbered patch file into `dir`, oldest first, and return
+/// their paths in that order.
+///
+/// Each file starts with the `From`, `Date` and `Subject` headers of the commit, and the subject is
+/// prefixed with `[PATCH n/m]`, like `git format-patch` does. Merge commits are skipped.
+pub fn format_patch(
+    repo: &git2::Repository,
+    range: Range<git2::Oid>,
+    dir: &Path,
+    options: FormatPatchOptions,
+) -> Result<Vec<PathBuf>> {
+    let mut cmd = git_cli::git(repo);
+    cmd.args(["format-patch", "--numbered", "--output-directory"])
+        .arg(dir);
+    if options.cover_letter {
+        cmd.arg("--cover-letter");
+    }
+    cmd.arg(format!("{}..{}", range.start, range.end));
+    let paths: Vec<_> = git_cli::run(cmd)?.lines().map(PathBuf::from).collect();
+    if paths.is_empty() {
+        return Err(anyhow!(
+            "There are no commits between {} and {} to export",
+            range.start,
+            range.end
+        )
+        .context(Code::Validation));
+    }
+    // Paths are printed as passed, so relative paths are relative to the worktree.
+    Ok(match repo.workdir() {
+        Some(workdir) if dir.is_relative() => {
+            paths.into_iter().map(|path| workdir.join(path)).collect()
+        }
+        _ => paths,
+    })
+}
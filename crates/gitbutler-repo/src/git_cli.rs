@@ -0,0 +1,91 @@
+use anyhow::{bail, Context, Result};
+use bstr::ByteSlice;
+
+/// Return a `git` invocation that operates on `repo`, for the few things neither `git2` nor `gix`
+/// can do for us.
+pub(crate) fn git(repo: &git2::Repository) -> std::process::Command {
+    let mut cmd = std::process::Command::new(gix::path::env::exe_invocation());
+    cmd.arg("--git-dir").arg(repo.path());
+    if let Some(workdir) = repo.workdir() {
+        cmd.arg("--work-tree").arg(workdir).current_dir(workdir);
+    }
+    cmd
+}
+
+/// Run `cmd` to completion and return its `stdout`, or fail with its `stderr` if it didn't succeed.
+pub(crate) fn run(mut cmd: std::process::Command) -> Result<String> {
+    let output = cmd
+        .output()
+        .with_context(|| format!("failed to spawn {cmd:?}"))?;
+    if !output.status.success() {
+        bail!(
+            "{cmd:?} failed with {}: {}",
+            output.status,
+            output.stderr.to_str_lossy().trim()
+        );
+    }
+    Ok(output.stdout.to_str_lossy().into_owned())
+}
+
+/// Run `cmd` to completion while passing each line of progress it prints to `stderr` to `progress`,
+/// and kill it if `should_interrupt` becomes `true`.
+pub(crate) fn run_interruptible(
+    mut cmd: std::process::Command,
+    progress: &mut dyn FnMut(&str),
+    should_interrupt: &std::sync::atomic::AtomicBool,
+) -> Result<()> {
+    use std::io::Read;
+    use std::sync::atomic::Ordering;
+
+    let mut child = cmd
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .with_context(|| format!("failed to spawn {cmd:?}"))?;
+    let mut stderr = child.stderr.take().expect("configured to be piped");
+    let (tx, rx) = std::sync::mpsc::channel();
+    // Progress lines are terminated by `\r` while they update, and by `\n` once they are done.
+    std::thread::spawn(move || {
+        let mut buf = [0; 512];
+        let mut line = Vec::new();
+        while let Ok(read @ 1..) = stderr.read(&mut buf) {
+            for &byte in &buf[..read] {
+                if byte == b'\r' || byte == b'\n' {
+                    if !line.is_empty() && tx.send(std::mem::take(&mut line)).is_err() {
+                        return;
+                    }
+                } else {
+                    line.push(byte);
+                }
+            }
+        }
+        if !line.is_empty() {
+            tx.send(line).ok();
+        }
+    });
+
+    let mut last_line = Vec::new();
+    loop {
+        if should_interrupt.load(Ordering::Relaxed) {
+            child.kill().ok();
+            child.wait().ok();
+            bail!("{cmd:?} was interrupted");
+        }
+        match rx.recv_timeout(std::time::Duration::from_millis(50)) {
+            Ok(line) => {
+                progress(line.to_str_lossy().as_ref());
+                last_line = line;
+            }
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        bail!(
+            "{cmd:?} failed with {status}: {}",
+            last_line.to_str_lossy().trim()
+        );
+    }
+    Ok(())
+}
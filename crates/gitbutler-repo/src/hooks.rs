@@ -7,7 +7,7 @@ use gitbutler_command_context::CommandContext;
 use gitbutler_diff::GitHunk;
 use serde::Serialize;
 
-use crate::staging;
+use crate::{staging, CommitError};
 
 #[derive(Serialize, PartialEq, Debug, Clone)]
 pub struct MessageData {
@@ -28,6 +28,16 @@ pub enum HookResult {
     Failure(ErrorData),
 }
 
+impl HookResult {
+    /// Turn a failure into [`CommitError::HookRejected`] with the output of the hook.
+    pub fn into_commit_result(self) -> Result<(), CommitError> {
+        match self {
+            HookResult::Success | HookResult::NotConfigured => Ok(()),
+            HookResult::Failure(ErrorData { error }) => Err(CommitError::HookRejected(error)),
+        }
+    }
+}
+
 /// Message hook result indicating either success, message, or failure.
 ///
 /// A message hook can optionally mutate the message, so this special type is
@@ -1,11 +1,17 @@
 pub mod rebase;
 
 mod commands;
+mod commit_error;
 pub use commands::{FileInfo, RepoCommands};
+pub use commit_error::CommitError;
 pub use remote::GitRemote;
 
 mod repository_ext;
-pub use repository_ext::RepositoryExt;
+pub use repository_ext::{
+    CheckoutProgress, CheckoutWouldOverwrite, CommitInsights, DirtyWorktree, IdentitySource,
+    PathState, RefUpdate, RemoteTrackingInfo, RepoSize, RepositoryExt, BLOB_STREAM_CHUNK_SIZE,
+    UPSTREAM_PATCH_ID_WINDOW,
+};
 
 pub mod credentials;
 
@@ -22,6 +28,20 @@ pub mod logging;
 
 pub mod commit_message;
 
+pub mod alternates;
+pub mod branch;
+pub mod bundle;
+pub mod colors;
+pub mod discard;
+pub mod format_patch;
+mod git_cli;
+pub mod maintenance;
+pub mod mutation;
+pub mod operation;
+pub mod secrets;
+pub mod sparse_checkout;
+pub mod submodules;
+
 use gitbutler_oxidize::gix_to_git2_signature;
 pub const GITBUTLER_COMMIT_AUTHOR_NAME: &str = "GitButler";
 pub const GITBUTLER_COMMIT_AUTHOR_EMAIL: &str = "gitbutler@gitbutler.com";
@@ -1,4 +1,8 @@
 use anyhow::{Context, Result};
+use bstr::BString;
+use gitbutler_oxidize::git2_to_gix_object_id;
+
+use crate::commit_message::{CommitMessage, Identity};
 
 pub trait RepositoryExt {
     fn l(&self, from: git2::Oid, to: LogUntil, include_all_parents: bool)
@@ -10,6 +14,32 @@ pub trait RepositoryExt {
         to: LogUntil,
         include_all_parents: bool,
     ) -> Result<Vec<git2::Commit>>;
+    /// Like [`log()`](Self::log()), but return a summary of each commit.
+    fn log_summaries(
+        &self,
+        from: git2::Oid,
+        to: LogUntil,
+        include_all_parents: bool,
+    ) -> Result<Vec<CommitSummary>>;
+    /// Return a summary of all commits that aren't reachable from any reference or `HEAD` anymore,
+    /// but are still mentioned in a reflog, along with their unreachable ancestors, newest first.
+    ///
+    /// These are typically left behind by resets and rebases and can be used for recovery.
+    /// Commits only found by walking the object database aren't considered.
+    fn dangling_commits(&self) -> Result<Vec<CommitSummary>>;
+}
+
+/// What's needed to show a commit in a list of commits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitSummary {
+    pub id: git2::Oid,
+    /// The first line of the commit message.
+    pub title: BString,
+    pub author: Identity,
+    /// Everyone mentioned in `Co-authored-by` trailers of the message.
+    pub co_authors: Vec<Identity>,
+    /// `true` if the message has a `Signed-off-by` trailer.
+    pub signed_off: bool,
 }
 
 impl RepositoryExt for git2::Repository {
@@ -102,6 +132,74 @@ impl RepositoryExt for git2::Repository {
             .collect::<Result<Vec<_>, _>>()
             .context("failed to collect commits")
     }
+
+    fn log_summaries(
+        &self,
+        from: git2::Oid,
+        to: LogUntil,
+        include_all_parents: bool,
+    ) -> Result<Vec<CommitSummary>> {
+        let repo = gix::open(self.path())?;
+        self.l(from, to, include_all_parents)?
+            .into_iter()
+            .map(|id| commit_summary(&repo, id))
+            .collect()
+    }
+
+    fn dangling_commits(&self) -> Result<Vec<CommitSummary>> {
+        let mut reflog_names = vec!["HEAD".to_owned()];
+        for reference in self.references().context("failed to list references")? {
+            if let Some(name) = reference?.name() {
+                reflog_names.push(name.to_owned());
+            }
+        }
+
+        let mut revwalk = self.revwalk().context("failed to create revwalk")?;
+        revwalk.set_sorting(git2::Sort::TIME)?;
+        let mut has_candidates = false;
+        for name in &reflog_names {
+            for entry in self.reflog(name)?.iter() {
+                for id in [entry.id_old(), entry.id_new()] {
+                    // Reflogs may still mention commits that were pruned already.
+                    if !id.is_zero() && self.find_commit(id).is_ok() {
+                        revwalk.push(id)?;
+                        has_candidates = true;
+                    }
+                }
+            }
+        }
+        if !has_candidates {
+            return Ok(Vec::new());
+        }
+        revwalk.hide_glob("refs/*")?;
+        if let Ok(head) = self.head() {
+            if let Some(id) = head.target() {
+                revwalk.hide(id)?;
+            }
+        }
+
+        let repo = gix::open(self.path())?;
+        revwalk
+            .map(|id| commit_summary(&repo, id?))
+            .collect::<Result<Vec<_>>>()
+            .context("failed to collect dangling commits")
+    }
+}
+
+fn commit_summary(repo: &gix::Repository, id: git2::Oid) -> Result<CommitSummary> {
+    let commit = repo.find_commit(git2_to_gix_object_id(id))?;
+    let commit = commit.decode()?;
+    let message = CommitMessage::new(commit.clone());
+    Ok(CommitSummary {
+        id,
+        co_authors: message.co_authors(),
+        signed_off: message.is_signed_off(),
+        title: message.title,
+        author: Identity {
+            name: commit.author.name.to_owned(),
+            email: commit.author.email.to_owned(),
+        },
+    })
 }
 
 type OidFilter = dyn Fn(&git2::Commit) -> Result<bool>;
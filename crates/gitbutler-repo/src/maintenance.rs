@@ -0,0 +1,59 @@
+//! Keep repositories healthy, without the user having to run `git gc`.
+use std::sync::atomic::AtomicBool;
+
+use anyhow::Result;
+
+use crate::git_cli;
+
+/// The kind of maintenance to perform with [`maintenance()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaintenanceKind {
+    /// Write the commit-graph file for all reachable commits, which speeds up history traversal.
+    CommitGraph,
+    /// Pack loose objects and remove them, then combine small pack-files into larger ones,
+    /// without rewriting all objects like `git gc` would.
+    IncrementalRepack,
+}
+
+/// Perform the maintenance task `kind` on `repo`, passing each line of progress to `progress`.
+///
+/// The task is aborted with an error once `should_interrupt` is `true`. It is safe to do so,
+/// as all files are written atomically.
+pub fn maintenance(
+    repo: &git2::Repository,
+    kind: MaintenanceKind,
+    progress: &mut dyn FnMut(&str),
+    should_interrupt: &AtomicBool,
+) -> Result<()> {
+    let mut cmd = git_cli::git(repo);
+    match kind {
+        MaintenanceKind::CommitGraph => {
+            cmd.args(["commit-graph", "write", "--reachable", "--progress"]);
+        }
+        MaintenanceKind::IncrementalRepack => {
+            cmd.args(["maintenance", "run", "--task=loose-objects"]);
+            git_cli::run_interruptible(cmd, progress, should_interrupt)?;
+            if !has_packs(repo)? {
+                return Ok(());
+            }
+            cmd = git_cli::git(repo);
+            cmd.args(["maintenance", "run", "--task=incremental-repack"]);
+        }
+    }
+    git_cli::run_interruptible(cmd, progress, should_interrupt)
+}
+
+/// The incremental repack fails if there are no packs yet, which happens if there are no objects.
+fn has_packs(repo: &git2::Repository) -> Result<bool> {
+    let packs = match std::fs::read_dir(repo.path().join("objects").join("pack")) {
+        Ok(packs) => packs,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(err) => return Err(err.into()),
+    };
+    for entry in packs {
+        if entry?.path().extension().is_some_and(|ext| ext == "pack") {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
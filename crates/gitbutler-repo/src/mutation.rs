@@ -0,0 +1,75 @@
+//! Tell interested parties, like caches of statuses or diffs, about changes made to a repository
+//! through this crate, so they don't have to be invalidated by each operation.
+//!
+//! Only changes made by [`RepositoryExt`](crate::RepositoryExt) are observed, changes made through
+//! `git2` or `gix` directly, or by other programs, go unnoticed.
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+/// The kind of change made to a repository.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MutationKind {
+    /// A commit was created and a reference was updated to point to it.
+    Commit,
+    /// A tree was checked out into the worktree and index.
+    Checkout,
+    /// References were updated or deleted.
+    RefUpdate,
+}
+
+/// A function to call after a repository was changed.
+pub type MutationObserver = Box<dyn Fn(MutationKind) + Send + Sync>;
+
+/// Identifies an observer registered with [`on_mutation()`], to [remove](remove_observer()) it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObserverId(u64);
+
+type Observers = Vec<(ObserverId, PathBuf, Arc<MutationObserver>)>;
+
+static OBSERVERS: Mutex<Observers> = Mutex::new(Vec::new());
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Call `observer` after each change made to `repo`, or to any other instance of the same repository,
+/// until it's [removed](remove_observer()).
+///
+/// It's called on the thread that made the change, after the change was made, and should return quickly.
+pub fn on_mutation(repo: &git2::Repository, observer: MutationObserver) -> ObserverId {
+    let id = ObserverId(NEXT_ID.fetch_add(1, Ordering::Relaxed));
+    OBSERVERS
+        .lock()
+        .unwrap()
+        .push((id, repo.path().to_owned(), Arc::new(observer)));
+    id
+}
+
+/// Stop calling the observer registered as `id`. Does nothing if it was already removed.
+pub fn remove_observer(id: ObserverId) {
+    OBSERVERS
+        .lock()
+        .unwrap()
+        .retain(|(observer_id, _, _)| *observer_id != id);
+}
+
+/// Call all observers of `repo` with `kind`.
+pub(crate) fn notify(repo: &git2::Repository, kind: MutationKind) {
+    let observers = observers_of(repo.path());
+    for observer in observers {
+        observer(kind);
+    }
+}
+
+/// Don't hold the lock while calling observers, so they can register or remove observers themselves.
+fn observers_of(git_dir: &Path) -> Vec<Arc<MutationObserver>> {
+    OBSERVERS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(_, observed, _)| observed == git_dir)
+        .map(|(_, _, observer)| Arc::clone(observer))
+        .collect()
+}
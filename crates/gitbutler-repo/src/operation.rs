@@ -0,0 +1,106 @@
+//! Learn where an operation that was paused for the user to resolve conflicts stands, so it can be resumed.
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+/// The kind of operation in progress, see [`OperationProgress`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationKind {
+    Rebase,
+    Merge,
+    CherryPick,
+    Revert,
+}
+
+/// How far an operation paused in the middle of it has progressed, as returned by [`operation_progress()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperationProgress {
+    pub kind: OperationKind,
+    /// The one-based number of the step the operation stopped at.
+    pub step: usize,
+    /// The amount of steps of the operation.
+    ///
+    /// For cherry-picks and reverts of multiple commits, Git doesn't remember the steps that are done,
+    /// so `step` is `1` and this is the amount of remaining steps, including the current one.
+    pub total: usize,
+    /// The commit that was being applied when the operation stopped, if known.
+    pub stopped_at: Option<git2::Oid>,
+}
+
+/// Return the progress of the rebase, merge, cherry-pick or revert `repo` is in the middle of,
+/// or `None` if there is no such operation in progress.
+pub fn operation_progress(repo: &git2::Repository) -> Result<Option<OperationProgress>> {
+    use git2::RepositoryState as S;
+    let git_dir = repo.path();
+    let progress = match repo.state() {
+        S::RebaseMerge | S::RebaseInteractive => {
+            let dir = git_dir.join("rebase-merge");
+            OperationProgress {
+                kind: OperationKind::Rebase,
+                step: read_number(&dir.join("msgnum"))?,
+                total: read_number(&dir.join("end"))?,
+                stopped_at: read_commit(repo, &dir.join("stopped-sha")),
+            }
+        }
+        S::Rebase | S::ApplyMailboxOrRebase => {
+            let dir = git_dir.join("rebase-apply");
+            OperationProgress {
+                kind: OperationKind::Rebase,
+                step: read_number(&dir.join("next"))?,
+                total: read_number(&dir.join("last"))?,
+                stopped_at: read_commit(repo, &dir.join("original-commit")),
+            }
+        }
+        S::Merge => single_step(repo, OperationKind::Merge, "MERGE_HEAD"),
+        S::CherryPick => single_step(repo, OperationKind::CherryPick, "CHERRY_PICK_HEAD"),
+        S::Revert => single_step(repo, OperationKind::Revert, "REVERT_HEAD"),
+        S::CherryPickSequence | S::RevertSequence => {
+            let (kind, head) = if repo.state() == S::CherryPickSequence {
+                (OperationKind::CherryPick, "CHERRY_PICK_HEAD")
+            } else {
+                (OperationKind::Revert, "REVERT_HEAD")
+            };
+            let todo = std::fs::read_to_string(git_dir.join("sequencer/todo"))
+                .context("failed to read the steps of the sequencer")?;
+            OperationProgress {
+                total: todo
+                    .lines()
+                    .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+                    .count()
+                    .max(1),
+                ..single_step(repo, kind, head)
+            }
+        }
+        S::Clean | S::Bisect | S::ApplyMailbox => return Ok(None),
+    };
+    Ok(Some(progress))
+}
+
+fn single_step(repo: &git2::Repository, kind: OperationKind, head: &str) -> OperationProgress {
+    OperationProgress {
+        kind,
+        step: 1,
+        total: 1,
+        stopped_at: read_commit(repo, &repo.path().join(head)),
+    }
+}
+
+fn read_number(path: &Path) -> Result<usize> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read '{}'", path.display()))?;
+    content
+        .trim()
+        .parse()
+        .with_context(|| format!("'{}' doesn't contain a number", path.display()))
+}
+
+/// Read the possibly abbreviated commit hash from the first line of the file at `path`.
+fn read_commit(repo: &git2::Repository, path: &Path) -> Option<git2::Oid> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let hash = content.lines().next()?.trim();
+    repo.revparse_single(hash)
+        .ok()?
+        .peel_to_commit()
+        .ok()
+        .map(|commit| commit.id())
+}
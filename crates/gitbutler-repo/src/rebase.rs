@@ -4,7 +4,7 @@ use crate::{
     logging::{LogUntil, RepositoryExt as _},
     RepositoryExt as _,
 };
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use bstr::ByteSlice;
 use gitbutler_cherry_pick::{ConflictedTreeKey, GixRepositoryExt, RepositoryExt};
 use gitbutler_command_context::{gix_repository_for_merging, CommandContext};
@@ -12,6 +12,7 @@ use gitbutler_commit::{
     commit_ext::CommitExt,
     commit_headers::{CommitHeadersV2, HasCommitHeaders},
 };
+use gitbutler_error::error::Code;
 use gitbutler_oxidize::{gix_to_git2_oid, GixRepositoryExt as _, ObjectIdExt as _, OidExt as _};
 use serde::{Deserialize, Serialize};
 use tracing::instrument;
@@ -110,6 +111,200 @@ pub fn cherry_rebase_group(
     Ok(new_head_id)
 }
 
+/// The commits created by [`split_commit()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SplitCommit {
+    /// The commit with the first part of the change, on top of the parent of the original commit.
+    pub first: git2::Oid,
+    /// The commit with the rest of the change on top of `first`, with the tree of the original commit.
+    pub second: git2::Oid,
+    /// The tip of the branch after rebasing all descendants of the original commit onto `second`.
+    pub new_tip: git2::Oid,
+}
+
+/// Split `commit_id` into two commits, the first of which has the tree `first_tree_id` on top of the
+/// parent of `commit_id`, while the second one adds the remaining changes on top of it.
+/// Both keep the author and message of the original commit.
+///
+/// `tip` is the tip of the branch containing `commit_id`, and all commits between them are rebased
+/// onto the second commit. `tip` may also be `commit_id` itself. No reference is updated.
+pub fn split_commit(
+    repository: &git2::Repository,
+    commit_id: git2::Oid,
+    first_tree_id: git2::Oid,
+    tip: git2::Oid,
+) -> Result<SplitCommit> {
+    let commit = repository.find_commit(commit_id)?;
+    if commit.parent_count() != 1 || commit.is_conflicted() {
+        return Err(anyhow!(
+            "Only commits with a single parent and without conflicts can be split"
+        )
+        .context(Code::Validation));
+    }
+    if tip != commit_id && !repository.graph_descendant_of(tip, commit_id)? {
+        return Err(
+            anyhow!("Commit {commit_id} isn't part of the branch at {tip}")
+                .context(Code::Validation),
+        );
+    }
+    let parent = commit.parent(0)?;
+    let first_tree = repository.find_tree(first_tree_id)?;
+    let (_, committer) = repository.signatures()?;
+    let message = commit.message_bstr().to_str_lossy();
+
+    let first = crate::RepositoryExt::commit_with_signature(
+        repository,
+        None,
+        &commit.author(),
+        &committer,
+        &message,
+        &first_tree,
+        &[&parent],
+        // The original change-id stays with the second commit, which has the same tree.
+        Some(CommitHeadersV2::new()),
+    )
+    .context("failed to create first commit")?;
+    let second = crate::RepositoryExt::commit_with_signature(
+        repository,
+        None,
+        &commit.author(),
+        &committer,
+        &message,
+        &commit.tree()?,
+        &[&repository.find_commit(first)?],
+        commit.gitbutler_headers(),
+    )
+    .context("failed to create second commit")?;
+
+    let descendants = repository.l(tip, LogUntil::Commit(commit_id), false)?;
+    let new_tip = if descendants.is_empty() {
+        second
+    } else {
+        cherry_rebase_group(repository, second, &descendants, false, false)?
+    };
+    Ok(SplitCommit {
+        first,
+        second,
+        new_tip,
+    })
+}
+
+/// Change the message of `commit_id` to `message`, keeping its tree, parents, author and headers,
+/// and rebase all commits between it and `tip`, the tip of the branch containing it, onto the new commit.
+///
+/// Returns the new tip, which is the new commit if `tip` is `commit_id`. No reference is updated.
+/// As no tree changes, the rebase can't conflict.
+pub fn reword_commit(
+    repository: &git2::Repository,
+    commit_id: git2::Oid,
+    message: &str,
+    tip: git2::Oid,
+) -> Result<git2::Oid> {
+    if message.trim().is_empty() {
+        return Err(anyhow!("The commit message must not be empty").context(Code::Validation));
+    }
+    if tip != commit_id && !repository.graph_descendant_of(tip, commit_id)? {
+        return Err(
+            anyhow!("Commit {commit_id} isn't part of the branch at {tip}")
+                .context(Code::Validation),
+        );
+    }
+    let commit = repository.find_commit(commit_id)?;
+    let (_, committer) = repository.signatures()?;
+    let parents = commit.parents().collect::<Vec<_>>();
+    let reworded = crate::RepositoryExt::commit_with_signature(
+        repository,
+        None,
+        &commit.author(),
+        &committer,
+        message,
+        &commit.tree()?,
+        &parents.iter().collect::<Vec<_>>(),
+        commit.gitbutler_headers(),
+    )
+    .context("failed to create reworded commit")?;
+
+    let descendants = repository.l(tip, LogUntil::Commit(commit_id), false)?;
+    if descendants.is_empty() {
+        return Ok(reworded);
+    }
+    cherry_rebase_group(repository, reworded, &descendants, false, false)
+}
+
+/// The result of [`reorder_commits()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReorderOutcome {
+    /// All commits were rebased into the new order, with this being the new tip.
+    Reordered(git2::Oid),
+    /// Putting `commit` into its new place conflicts in these paths, sorted, so nothing was reordered.
+    Conflicted {
+        commit: git2::Oid,
+        paths: Vec<PathBuf>,
+    },
+}
+
+/// Rebase the commits between `base` and `tip`, the tip of a branch, into `new_order`, which lists
+/// each of them exactly once, child-most first, like the branch lists its commits.
+///
+/// The commits are picked in their new order on top of `base` and keep their author, message and headers.
+/// Commits that end up with the same parent are kept as they are.
+/// No reference is updated, and the first conflict stops the rebase, so the branch can be left
+/// as it is.
+pub fn reorder_commits(
+    repository: &git2::Repository,
+    base: git2::Oid,
+    tip: git2::Oid,
+    new_order: &[git2::Oid],
+) -> Result<ReorderOutcome> {
+    let commits = repository.l(tip, LogUntil::Commit(base), false)?;
+    let is_permutation = commits.len() == new_order.len()
+        && commits.iter().collect::<HashSet<_>>() == new_order.iter().collect::<HashSet<_>>();
+    if !is_permutation {
+        return Err(anyhow!(
+            "The new order must contain each commit between {base} and {tip} exactly once"
+        )
+        .context(Code::Validation));
+    }
+
+    let gix_repo = gix_repository_for_merging(repository.path())?;
+    let conflict_kind = gix::merge::tree::TreatAsUnresolved::forced_resolution();
+    let mut head = repository.find_commit(base)?;
+    for id in new_order.iter().rev() {
+        let to_rebase = repository.find_commit(*id)?;
+        if to_rebase.parent_ids().len() == 1 && head.id() == to_rebase.parent_id(0)? {
+            head = to_rebase;
+            continue;
+        }
+        let mut cherrypick_result = gix_repo
+            .cherry_pick_gitbutler(&head, &to_rebase)
+            .context("failed to cherry pick")?;
+        if cherrypick_result.has_unresolved_conflicts(conflict_kind) {
+            let mut paths: Vec<_> = cherrypick_result
+                .conflicts
+                .iter()
+                .filter(|c| c.is_unresolved(conflict_kind))
+                .map(|c| gix::path::from_bstr(c.ours.location()).into_owned())
+                .collect();
+            paths.sort();
+            paths.dedup();
+            return Ok(ReorderOutcome::Conflicted {
+                commit: to_rebase.id(),
+                paths,
+            });
+        }
+        let tree_id = cherrypick_result.tree.write()?;
+        // Commits that become empty are kept, as the user only asked to move them.
+        head = commit_unconflicted_cherry_result(
+            repository,
+            head,
+            to_rebase,
+            gix_to_git2_oid(tree_id),
+            true,
+        )?;
+    }
+    Ok(ReorderOutcome::Reordered(head.id()))
+}
+
 fn commit_unconflicted_cherry_result<'repository>(
     repository: &'repository git2::Repository,
     head: git2::Commit<'repository>,
@@ -302,6 +497,110 @@ fn extract_conflicted_files(
     Ok(out)
 }
 
+/// Whether two branches can be combined, as returned by [`can_integrate()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrationReport {
+    /// The branches can be merged without conflicts.
+    Clean,
+    /// Merging the branches conflicts in these paths, sorted.
+    Conflicted(Vec<PathBuf>),
+    /// One branch already contains the other, so there is nothing to integrate.
+    AlreadyIntegrated,
+}
+
+/// Find out if the branches at `a_tip` and `b_tip` can be merged without conflicts, without writing
+/// any objects. The merge is performed like [`merge_commits()`] does it, but without auto-resolution.
+pub fn can_integrate(
+    repository: &git2::Repository,
+    a_tip: git2::Oid,
+    b_tip: git2::Oid,
+) -> Result<IntegrationReport> {
+    let merge_base = repository
+        .merge_base(a_tip, b_tip)
+        .context("The branches have no common history")?;
+    if merge_base == a_tip || merge_base == b_tip {
+        return Ok(IntegrationReport::AlreadyIntegrated);
+    }
+
+    let base_tree =
+        repository.find_real_tree(&repository.find_commit(merge_base)?, Default::default())?;
+    let a_tree = repository.find_real_tree(&repository.find_commit(a_tip)?, Default::default())?;
+    let b_tree = repository.find_real_tree(&repository.find_commit(b_tip)?, Default::default())?;
+    let gix_repo = gix_repository_for_merging(repository.path())?.with_object_memory();
+    let merge_result = gix_repo.merge_trees(
+        base_tree.id().to_gix(),
+        a_tree.id().to_gix(),
+        b_tree.id().to_gix(),
+        gix_repo.default_merge_labels(),
+        gix_repo.tree_merge_options()?,
+    )?;
+
+    let treat_as_unresolved = gix::merge::tree::TreatAsUnresolved::git();
+    let mut paths: Vec<_> = merge_result
+        .conflicts
+        .iter()
+        .filter(|c| c.is_unresolved(treat_as_unresolved))
+        .map(|c| gix::path::from_bstr(c.ours.location()).into_owned())
+        .collect();
+    if paths.is_empty() {
+        return Ok(IntegrationReport::Clean);
+    }
+    paths.sort();
+    paths.dedup();
+    Ok(IntegrationReport::Conflicted(paths))
+}
+
+/// Find all paths that would conflict when rebasing the commits of the branch at `branch_tip`,
+/// starting at its merge-base with `onto`, on top of `onto`. They are returned sorted.
+///
+/// Each commit is picked like [`cherry_rebase_group()`] does it, continuing with the auto-resolution
+/// after a conflict, so conflicts of later commits are found as well. No object is written.
+pub fn preflight_rebase(
+    repository: &git2::Repository,
+    branch_tip: git2::Oid,
+    onto: git2::Oid,
+) -> Result<Vec<PathBuf>> {
+    let merge_base = repository
+        .merge_base(branch_tip, onto)
+        .context("The branch has no common history with the commit to rebase onto")?;
+    let ids_to_rebase = repository.l(branch_tip, LogUntil::Commit(merge_base), false)?;
+
+    let gix_repo = gix_repository_for_merging(repository.path())?.with_object_memory();
+    let conflict_kind = gix::merge::tree::TreatAsUnresolved::forced_resolution();
+    let mut head_tree = repository
+        .find_real_tree(&repository.find_commit(onto)?, Default::default())?
+        .id()
+        .to_gix();
+    let mut paths = Vec::new();
+    for id in ids_to_rebase.iter().rev() {
+        let to_rebase = repository.find_commit(*id)?;
+        let base_tree = if to_rebase.is_conflicted() {
+            repository.find_real_tree(&to_rebase, ConflictedTreeKey::Base)?
+        } else {
+            repository.find_real_tree(&to_rebase.parent(0)?, Default::default())?
+        };
+        let their_tree = repository.find_real_tree(&to_rebase, ConflictedTreeKey::Theirs)?;
+        let mut merge_result = gix_repo.merge_trees(
+            base_tree.id().to_gix(),
+            head_tree,
+            their_tree.id().to_gix(),
+            gix_repo.default_merge_labels(),
+            gix_repo.merge_options_force_ours()?,
+        )?;
+        paths.extend(
+            merge_result
+                .conflicts
+                .iter()
+                .filter(|c| c.is_unresolved(conflict_kind))
+                .map(|c| gix::path::from_bstr(c.ours.location()).into_owned()),
+        );
+        head_tree = merge_result.tree.write()?.detach();
+    }
+    paths.sort();
+    paths.dedup();
+    Ok(paths)
+}
+
 /// Merge two commits together
 ///
 /// The `target_commit` and `incoming_commit` must have a common ancestor.
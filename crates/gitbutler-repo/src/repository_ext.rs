@@ -1,10 +1,16 @@
+use crate::colors::{parse_color, Color, DiffColors};
+use crate::commit_message::Identity;
+use crate::mutation::{self, MutationKind};
+use crate::CommitError;
 use crate::Config;
 use crate::SignaturePurpose;
 use anyhow::{anyhow, bail, Context, Result};
 use bstr::{BStr, BString};
 use git2::Tree;
+use gitbutler_cherry_pick::RepositoryExt as _;
 use gitbutler_commit::commit_headers::CommitHeadersV2;
 use gitbutler_config::git::{GbConfig, GitConfig};
+use gitbutler_diff::ChangeType;
 use gitbutler_error::error::Code;
 use gitbutler_oxidize::{
     git2_signature_to_gix_signature, git2_to_gix_object_id, gix_to_git2_oid, gix_to_git2_signature,
@@ -12,8 +18,14 @@ use gitbutler_oxidize::{
 use gitbutler_reference::{Refname, RemoteRefname};
 use gix::objs::WriteTo;
 use gix::status::index_worktree;
+use std::cell::RefCell;
 use std::collections::HashSet;
+use std::io::Read as _;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::str;
+use std::time::{Duration, SystemTime};
 use tracing::instrument;
 
 /// Extension trait for `git2::Repository`.
@@ -30,6 +42,14 @@ pub trait RepositoryExt {
     /// gets merged.
     fn merge_base_octopussy(&self, ids: &[git2::Oid]) -> Result<git2::Oid>;
     fn signatures(&self) -> Result<(git2::Signature, git2::Signature)>;
+    /// Return `true` if an author identity with non-empty name and email is configured,
+    /// so that creating commits with [`signatures()`](Self::signatures()) will work.
+    fn has_identity(&self) -> bool;
+    /// Return the identity configured in `user.name` and `user.email`, along with the most specific
+    /// configuration level that provides it, to explain how commits will be attributed.
+    ///
+    /// Environment variables like `GIT_AUTHOR_NAME` aren't considered.
+    fn identity_source(&self) -> Result<IdentitySource>;
 
     fn remote_branches(&self) -> Result<Vec<RemoteRefname>>;
     fn remotes_as_string(&self) -> Result<Vec<String>>;
@@ -37,6 +57,11 @@ pub trait RepositoryExt {
     /// Returns the computed signature.
     fn sign_buffer(&self, buffer: &[u8]) -> Result<BString>;
     fn checkout_tree_builder<'a>(&'a self, tree: &'a git2::Tree<'a>) -> CheckoutTreeBuidler<'a>;
+    /// Write the files of `tree` into `dir`, which is created if needed, like for previewing
+    /// a branch in a scratch folder. Files in `dir` that are also in `tree` are overwritten.
+    ///
+    /// The worktree, index and `HEAD` of the repository aren't touched.
+    fn checkout_to(&self, tree: git2::Oid, dir: &Path) -> Result<()>;
     fn maybe_find_branch_by_refname(&self, name: &Refname) -> Result<Option<git2::Branch>>;
     /// Add all untracked and modified files in the worktree to
     /// the object database, and create a tree from it.
@@ -48,6 +73,16 @@ pub trait RepositoryExt {
     /// or if the HEAD branch has no commits.
     fn create_wd_tree(&self, untracked_limit_in_bytes: u64) -> Result<Tree>;
 
+    /// Write the index as currently stored on disk into a tree, which is the tree a commit
+    /// would have if it was created now, and return its id. Nothing else is changed.
+    ///
+    /// Fails with [`Code::CommitMergeConflictFailure`] if the index has conflicts.
+    fn write_tree(&self) -> Result<git2::Oid>;
+
+    /// Like [`write_tree()`](Self::write_tree()), but with all changes in the worktree instead,
+    /// including untracked files, without changing the index. See [`create_wd_tree()`](Self::create_wd_tree()).
+    fn write_workdir_tree(&self) -> Result<git2::Oid>;
+
     /// Returns the `gitbutler/workspace` branch if the head currently points to it, or fail otherwise.
     /// Use it before any modification to the repository, or extra defensively each time the
     /// workspace is needed.
@@ -66,6 +101,247 @@ pub trait RepositoryExt {
         parents: &[&git2::Commit<'_>],
         commit_headers: Option<CommitHeadersV2>,
     ) -> Result<git2::Oid>;
+
+    /// Create a commit of `tree` with all of `parents` in order, the first one being the mainline,
+    /// like when integrating branches with a merge, and with the configured [signatures](Self::signatures()).
+    /// No reference is updated.
+    ///
+    /// Fails with [`Code::Validation`] if there are no `parents`, or if `tree` or one of the
+    /// `parents` doesn't exist.
+    fn create_merge_commit(
+        &self,
+        tree: git2::Oid,
+        parents: &[git2::Oid],
+        message: &str,
+    ) -> Result<git2::Oid>;
+
+    /// Return the status and path of each file changed by `commit`, without computing any hunks.
+    ///
+    /// The commit is compared to its first parent, or to all of its parents if `all_parents` is `true`,
+    /// in which case each path is listed only once with the status seen against the first parent that changed it.
+    /// Root commits are compared to the empty tree.
+    fn commit_files(
+        &self,
+        commit: git2::Oid,
+        all_parents: bool,
+    ) -> Result<Vec<(ChangeType, PathBuf)>>;
+
+    /// Commit the worktree state of only `paths` on top of `HEAD`, with `message`, and update the
+    /// branch `HEAD` points to. All other paths remain as they are in `HEAD`.
+    ///
+    /// `paths` are relative to the worktree, and those that don't exist there anymore are removed.
+    /// Like `git commit --only`, the index is updated to match the committed `paths`, and is left
+    /// alone otherwise.
+    /// Line endings are converted as configured by `core.autocrlf` and `.gitattributes`.
+    /// Failures are classified like [`CommitError`], e.g. if `paths` are conflicted or contain no changes.
+    fn commit_paths(&self, paths: &[PathBuf], message: &str) -> Result<git2::Oid>;
+
+    /// Return the patch-id of `commit`, like `git patch-id --stable`, which is computed from the changes
+    /// of `commit` compared to its first parent, and is the same for all commits with the same changes,
+    /// like cherry-picks.
+    fn patch_id(&self, commit: git2::Oid) -> Result<git2::Oid>;
+
+    /// Return `true` if the changes of `commit` are already contained in `upstream_tip`, either because
+    /// it's reachable from there, or because one of the [`UPSTREAM_PATCH_ID_WINDOW`] most recent
+    /// non-merge commits only reachable from `upstream_tip` has the same [patch-id](Self::patch_id()),
+    /// like after a rebase or cherry-pick.
+    fn is_upstream(&self, commit: git2::Oid, upstream_tip: git2::Oid) -> Result<bool>;
+
+    /// Summarize the changes of `commit` compared to its first parent, or to the empty tree
+    /// if it's a root commit, for display as insights into the repository.
+    fn commit_insights(&self, commit: git2::Oid) -> Result<CommitInsights>;
+
+    /// Return how much space the objects of the repository and its worktree take on disk, to help
+    /// deciding if maintenance or Git LFS are needed.
+    ///
+    /// The worktree is traversed once, without following symlinks or entering `.git` directories,
+    /// and counts ignored files as well.
+    fn on_disk_size(&self) -> Result<RepoSize>;
+
+    /// Return all remote-tracking branches along with the last time they were updated, sorted by name.
+    ///
+    /// The time is taken from the newest entry of the reflog of each branch, and is `None` if
+    /// there is none. Symbolic references like `refs/remotes/origin/HEAD` are skipped.
+    fn remote_tracking_branches(&self) -> Result<Vec<RemoteTrackingInfo>>;
+
+    /// Return whether the worktree-relative `path` is tracked, ignored or untracked, along with
+    /// its change compared to `HEAD` if it's tracked.
+    ///
+    /// Only `path` is looked at, which is much cheaper than a status of the whole worktree.
+    fn path_status(&self, path: &Path) -> Result<PathState>;
+
+    /// Perform all `updates` in a single transaction, so either all of them are applied, or none is.
+    ///
+    /// If any of the references doesn't have the expected value, nothing is changed and the error
+    /// is classified as [`Code::Validation`].
+    fn update_refs(&self, updates: Vec<RefUpdate>) -> Result<()>;
+
+    /// Return the full name and target of all references matching `glob`, like `refs/gitbutler/*`,
+    /// sorted by name. Symbolic references are peeled to the object they point to.
+    ///
+    /// Fails with [`Code::Validation`] if `glob` can't match any valid reference name.
+    fn refs_matching(&self, glob: &str) -> Result<Vec<(String, git2::Oid)>>;
+
+    /// Fail with [`Code::ProjectDirtyWorktree`] if there are changes in the index or worktree
+    /// compared to `HEAD`, for use as precondition of operations that need a clean worktree.
+    /// A [`DirtyWorktree`] with all changed paths can be obtained from the error.
+    ///
+    /// Untracked and ignored files are fine as they aren't affected by switching branches or rebasing.
+    fn require_clean(&self) -> Result<()>;
+
+    /// Remove the lock files like `index.lock` or `refs/heads/main.lock` in the Git directory that
+    /// weren't modified for longer than `max_age`, and return their paths.
+    ///
+    /// These are left behind by crashed `git` processes and make all further operations fail with
+    /// [`Code::ProjectGitLocked`], which is what this is a recovery for.
+    /// `max_age` should be generous, as lock files of processes that are still running are removed as
+    /// well, and lock files modified in the future, for instance due to clock skew, are always kept.
+    fn clear_stale_locks(&self, max_age: Duration) -> Result<Vec<PathBuf>>;
+
+    /// Return the paths of all text files in the index whose content contains CRLF line endings,
+    /// sorted by path, which typically should be normalized with `git add --renormalize`.
+    ///
+    /// Files that look binary, or that are marked as binary or `-text` in `.gitattributes`, are skipped,
+    /// just like conflicted files and symlinks.
+    fn crlf_in_index(&self) -> Result<Vec<PathBuf>>;
+
+    /// Return the paths and sizes of all binary files in the index that are larger than `threshold`
+    /// bytes, sorted by path, which should probably be stored with Git LFS instead.
+    ///
+    /// Files are binary if the first 8000 bytes of their content look binary, or if they are marked
+    /// as binary or `-text` in `.gitattributes`. Files with the `lfs` filter are skipped, just like
+    /// conflicted files and symlinks.
+    fn lfs_candidates(&self, threshold: u64) -> Result<Vec<(PathBuf, u64)>>;
+
+    /// Return the content of the file configured as `commit.template`, to prefill commit messages with,
+    /// or `None` if no template is configured or if it doesn't exist.
+    ///
+    /// Like in Git, a leading `~` is expanded to the home directory, and relative paths are
+    /// relative to the worktree.
+    fn commit_template(&self) -> Result<Option<String>>;
+
+    /// Return the colors configured with `color.diff.*`, using Git's defaults for those that aren't set.
+    ///
+    /// Fails with [`Code::Validation`] if one of them can't be parsed.
+    fn diff_colors(&self) -> Result<DiffColors>;
+
+    /// Return the note attached to `commit` in `notes_ref`, or `None` if there is none.
+    ///
+    /// `notes_ref` defaults to `core.notesRef`, or `refs/notes/commits` if that isn't set.
+    fn read_note(&self, commit: git2::Oid, notes_ref: Option<&str>) -> Result<Option<String>>;
+    /// Attach `content` as note to `commit` in `notes_ref`, replacing the one that may already exist.
+    ///
+    /// `notes_ref` defaults like in [`read_note()`](Self::read_note()).
+    fn write_note(&self, commit: git2::Oid, notes_ref: Option<&str>, content: &str) -> Result<()>;
+
+    /// Create the tag `name` pointing to `target`, annotated with `message` by the current user
+    /// if it's set, or a lightweight tag otherwise, and return the id `refs/tags/<name>` points to.
+    ///
+    /// It's refused with [`Code::ProjectRefExists`] if the tag already exists, unless `force` is set
+    /// to replace it.
+    fn create_tag(
+        &self,
+        name: &str,
+        target: git2::Oid,
+        message: Option<&str>,
+        force: bool,
+    ) -> Result<git2::Oid>;
+
+    /// Return the changes saved in the stash entry at `index`, with `0` being the most recent one,
+    /// compared to the commit that was checked out when stashing, sorted by path.
+    ///
+    /// If untracked files were stashed as well, they are included as added files.
+    fn stash_show(&self, index: usize) -> Result<Vec<gitbutler_diff::FileDiff>>;
+
+    /// Pass the content of the blob `id` to `on_chunk` in chunks of [`BLOB_STREAM_CHUNK_SIZE`] bytes,
+    /// except for the last one, until it returns [`ControlFlow::Break`] or the content ends.
+    ///
+    /// Loose objects are read only as far as needed, which is useful for previewing or sniffing the
+    /// beginning of large files. Objects in packs can't be streamed, and are read as a whole instead.
+    fn read_blob_stream(
+        &self,
+        id: git2::Oid,
+        on_chunk: impl FnMut(&[u8]) -> ControlFlow<()>,
+    ) -> Result<()>;
+}
+
+/// The amount of upstream commits [`RepositoryExt::is_upstream()`] compares patch-ids with at most.
+pub const UPSTREAM_PATCH_ID_WINDOW: usize = 1000;
+
+/// The size of the chunks passed by [`RepositoryExt::read_blob_stream()`].
+pub const BLOB_STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A change to a reference, to be applied with [`RepositoryExt::update_refs()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefUpdate {
+    /// The full name of the reference, like `refs/heads/main`.
+    pub name: String,
+    /// The value the reference is expected to have, or `None` if it must not exist yet.
+    pub expected: Option<git2::Oid>,
+    /// The value to set the reference to, or `None` to delete it.
+    pub new: Option<git2::Oid>,
+}
+
+/// The size of the changes of a commit, as returned by [`RepositoryExt::commit_insights()`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CommitInsights {
+    /// The amount of files that were added, modified or deleted.
+    pub files_changed: usize,
+    /// The amount of added lines, not counting binary files.
+    pub insertions: usize,
+    /// The amount of removed lines, not counting binary files.
+    pub deletions: usize,
+    /// The amount of bytes files grew by, as difference of their blob sizes.
+    pub added_bytes: u64,
+    /// The amount of bytes files shrank by, as difference of their blob sizes.
+    pub removed_bytes: u64,
+}
+
+/// How much space a repository takes on disk, as returned by [`RepositoryExt::on_disk_size()`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RepoSize {
+    /// The size of all files in `objects/pack`, i.e. the pack-files along with their indices.
+    pub packed_bytes: u64,
+    /// The size of all loose objects.
+    pub loose_bytes: u64,
+    /// The size of all files in the worktree, or `0` if the repository is bare.
+    pub worktree_bytes: u64,
+}
+
+/// A remote-tracking branch, as returned by [`RepositoryExt::remote_tracking_branches()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteTrackingInfo {
+    /// The name of the branch, like `refs/remotes/origin/main`.
+    pub name: RemoteRefname,
+    /// The commit the branch points to.
+    pub id: git2::Oid,
+    /// When the branch was last updated, typically by a fetch, or `None` if it has no reflog.
+    pub last_updated: Option<SystemTime>,
+}
+
+/// Where the identity used for commits is configured, as returned by [`RepositoryExt::identity_source()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdentitySource {
+    /// At least part of the identity is configured in the repository itself.
+    Local(Identity),
+    /// The identity is configured for the user, in `~/.gitconfig` or `$XDG_CONFIG_HOME/git/config`.
+    Global(Identity),
+    /// The identity is configured for all users of the system.
+    System(Identity),
+    /// There is no complete identity, so commits can't be created.
+    None,
+}
+
+/// The state of a single path, as returned by [`RepositoryExt::path_status()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathState {
+    /// The path is known to Git, and `status` is how it changed compared to `HEAD`,
+    /// or `None` if it didn't change.
+    Tracked { status: Option<ChangeType> },
+    /// The path isn't tracked, and matches an exclude pattern.
+    Ignored,
+    /// The path isn't tracked, and isn't ignored either.
+    Untracked,
 }
 
 impl RepositoryExt for git2::Repository {
@@ -77,6 +353,19 @@ impl RepositoryExt for git2::Repository {
         }
     }
 
+    fn checkout_to(&self, tree: git2::Oid, dir: &Path) -> Result<()> {
+        let tree = self.find_tree(tree)?;
+        let mut checkout = git2::build::CheckoutBuilder::new();
+        checkout
+            .target_dir(dir)
+            .force()
+            .recreate_missing(true)
+            // The index belongs to the worktree of the repository, not to `dir`.
+            .update_index(false);
+        self.checkout_tree(tree.as_object(), Some(&mut checkout))
+            .with_context(|| format!("failed to check out {} into '{}'", tree.id(), dir.display()))
+    }
+
     fn maybe_find_branch_by_refname(&self, name: &Refname) -> Result<Option<git2::Branch>> {
         let branch = self.find_branch(
             &name.simple_name(),
@@ -118,17 +407,7 @@ impl RepositoryExt for git2::Repository {
         use gix::status::plumbing::index_as_worktree::{Change, EntryStatus};
         use gix::status::tree_index::TrackRenames;
 
-        let repo = gix::open_opts(
-            self.path(),
-            gix::open::Options::default().permissions(gix::open::Permissions {
-                config: gix::open::permissions::Config {
-                    // Whenever we deal with worktree filters, we'd want to have the installation configuration as well.
-                    git_binary: cfg!(windows),
-                    ..Default::default()
-                },
-                ..Default::default()
-            }),
-        )?;
+        let repo = open_for_worktree_filters(self.path())?;
         let (mut pipeline, index) = repo.filter_pipeline(None)?;
         let mut added_worktree_file = |rela_path: &BStr,
                                        head_tree_editor: &mut gix::object::tree::Editor<'_>|
@@ -284,6 +563,22 @@ impl RepositoryExt for git2::Repository {
         Ok(self.find_tree(tree_oid)?)
     }
 
+    fn write_tree(&self) -> Result<git2::Oid> {
+        let mut index = self.index()?;
+        index.read(true).context("failed to read the index")?;
+        if index.has_conflicts() {
+            return Err(anyhow!(
+                "Conflicted files have to be resolved before the index can be written as tree"
+            )
+            .context(Code::CommitMergeConflictFailure));
+        }
+        Ok(index.write_tree()?)
+    }
+
+    fn write_workdir_tree(&self) -> Result<git2::Oid> {
+        Ok(self.create_wd_tree(0)?.id())
+    }
+
     fn workspace_ref_from_head(&self) -> Result<git2::Reference<'_>> {
         let head_ref = self.head().context("BUG: head must point to a reference")?;
         if head_ref.name_bytes() == b"refs/heads/gitbutler/workspace" {
@@ -358,6 +653,7 @@ impl RepositoryExt for git2::Repository {
         // update reference
         if let Some(refname) = update_ref {
             self.reference(&refname.to_string(), oid, true, message)?;
+            mutation::notify(self, MutationKind::Commit);
         }
         Ok(oid)
     }
@@ -390,9 +686,12 @@ impl RepositoryExt for git2::Repository {
         let author = repo
             .author()
             .transpose()?
+            .filter(is_complete_identity)
             .map(gix_to_git2_signature)
             .transpose()?
-            .context("No author is configured in Git")
+            .context(
+                "No author is configured in Git - set `user.name` and `user.email` to be able to commit",
+            )
             .context(Code::AuthorMissing)?;
 
         let config: Config = self.into();
@@ -408,6 +707,637 @@ impl RepositoryExt for git2::Repository {
         Ok((author, committer))
     }
 
+    fn commit_files(
+        &self,
+        commit: git2::Oid,
+        all_parents: bool,
+    ) -> Result<Vec<(ChangeType, PathBuf)>> {
+        let commit = self.find_commit(commit)?;
+        let tree = self.find_real_tree(&commit, Default::default())?;
+        let parent_trees = commit
+            .parents()
+            .take(if all_parents { usize::MAX } else { 1 })
+            .map(|parent| self.find_real_tree(&parent, Default::default()))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut opts = git2::DiffOptions::new();
+        opts.ignore_submodules(true);
+        let mut seen = HashSet::new();
+        let mut files = Vec::new();
+        for parent_tree in parent_trees
+            .iter()
+            .map(Some)
+            .chain(parent_trees.is_empty().then_some(None))
+        {
+            let diff = self.diff_tree_to_tree(parent_tree, Some(&tree), Some(&mut opts))?;
+            for delta in diff.deltas() {
+                let Some(path) = delta.new_file().path().or_else(|| delta.old_file().path()) else {
+                    continue;
+                };
+                if seen.insert(path.to_owned()) {
+                    files.push((delta.status().into(), path.to_owned()));
+                }
+            }
+        }
+        Ok(files)
+    }
+
+    fn commit_paths(&self, paths: &[PathBuf], message: &str) -> Result<git2::Oid> {
+        let head = self.head()?;
+        let head_commit = head.peel_to_commit()?;
+        let head_refname: Refname = head
+            .name()
+            .context("HEAD must point to a valid reference name")?
+            .parse()?;
+
+        let mut index = self.index()?;
+        let is_conflicted =
+            |path: &PathBuf| (1..=3).any(|stage| index.get_path(path, stage).is_some());
+        if paths.iter().any(is_conflicted) {
+            return Err(CommitError::Conflict.into());
+        }
+
+        // Converts line endings as configured by `core.autocrlf` and `.gitattributes`,
+        // just like the checkout does in reverse.
+        let repo = open_for_worktree_filters(self.path())?;
+        let (mut pipeline, gix_index) = repo.filter_pipeline(None)?;
+        let mut tree_editor = repo.edit_tree(git2_to_gix_object_id(head_commit.tree_id()))?;
+        for path in paths {
+            let rela_path = gix::path::to_unix_separators_on_windows(gix::path::into_bstr(path));
+            match pipeline.worktree_file_to_object(rela_path.as_ref(), &gix_index)? {
+                Some((id, kind, _md)) => tree_editor.upsert(rela_path.as_ref(), kind, id)?,
+                None => tree_editor.remove(rela_path.as_ref())?,
+            };
+        }
+        let tree = self.find_tree(gix_to_git2_oid(tree_editor.write()?))?;
+        if tree.id() == head_commit.tree_id() {
+            return Err(CommitError::EmptyTree.into());
+        }
+
+        let (author, committer) = self.signatures().map_err(CommitError::from)?;
+        let commit_id = self.commit_with_signature(
+            Some(&head_refname),
+            &author,
+            &committer,
+            message,
+            &tree,
+            &[&head_commit],
+            None,
+        )?;
+
+        for path in paths {
+            if tree.get_path(path).is_ok() {
+                index.add_path(path)?;
+            } else {
+                index.remove_path(path)?;
+            }
+        }
+        index.write()?;
+        Ok(commit_id)
+    }
+
+    fn patch_id(&self, commit: git2::Oid) -> Result<git2::Oid> {
+        let commit = self.find_commit(commit)?;
+        let tree = self.find_real_tree(&commit, Default::default())?;
+        let parent_tree = match commit.parents().next() {
+            Some(parent) => Some(self.find_real_tree(&parent, Default::default())?),
+            None => None,
+        };
+        let diff = self.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+        Ok(diff.patchid(None)?)
+    }
+
+    fn is_upstream(&self, commit: git2::Oid, upstream_tip: git2::Oid) -> Result<bool> {
+        if commit == upstream_tip || self.graph_descendant_of(upstream_tip, commit)? {
+            return Ok(true);
+        }
+        let patch_id = self.patch_id(commit)?;
+
+        let mut revwalk = self.revwalk().context("failed to create revwalk")?;
+        revwalk.push(upstream_tip)?;
+        revwalk.hide(commit)?;
+        for upstream_commit in revwalk.take(UPSTREAM_PATCH_ID_WINDOW) {
+            let upstream_commit = upstream_commit?;
+            if self.find_commit(upstream_commit)?.parent_count() > 1 {
+                continue;
+            }
+            if self.patch_id(upstream_commit)? == patch_id {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn commit_insights(&self, commit: git2::Oid) -> Result<CommitInsights> {
+        let commit = self.find_commit(commit)?;
+        let tree = self.find_real_tree(&commit, Default::default())?;
+        let parent_tree = match commit.parents().next() {
+            Some(parent) => self.find_real_tree(&parent, Default::default())?,
+            None => self.find_tree(self.treebuilder(None)?.write()?)?,
+        };
+
+        let odb = self.odb()?;
+        let blob_size = |tree: &git2::Tree, path: &Path| -> Result<u64> {
+            match tree.get_path(path) {
+                Ok(entry) if entry.kind() == Some(git2::ObjectType::Blob) => {
+                    Ok(odb.read_header(entry.id())?.0 as u64)
+                }
+                _ => Ok(0),
+            }
+        };
+        let mut insights = CommitInsights::default();
+        for stat in gitbutler_diff::per_file_stats(self, &parent_tree, &tree)? {
+            insights.files_changed += 1;
+            insights.insertions += stat.insertions;
+            insights.deletions += stat.deletions;
+            let old_size = blob_size(&parent_tree, &stat.path)?;
+            let new_size = blob_size(&tree, &stat.path)?;
+            insights.added_bytes += new_size.saturating_sub(old_size);
+            insights.removed_bytes += old_size.saturating_sub(new_size);
+        }
+        Ok(insights)
+    }
+
+    fn on_disk_size(&self) -> Result<RepoSize> {
+        let objects = self.commondir().join("objects");
+        let mut size = RepoSize {
+            packed_bytes: directory_size(&objects.join("pack"), &mut |_| false)?,
+            ..Default::default()
+        };
+        for entry in read_dir_if_exists(&objects)? {
+            let entry = entry?;
+            // Loose objects are stored in directories named after the first two hex digits of their id.
+            let name = entry.file_name();
+            let is_fanout = name.len() == 2
+                && name
+                    .to_str()
+                    .is_some_and(|name| name.bytes().all(|b| b.is_ascii_hexdigit()));
+            if is_fanout && entry.file_type()?.is_dir() {
+                size.loose_bytes += directory_size(&entry.path(), &mut |_| false)?;
+            }
+        }
+        if let Some(workdir) = self.workdir() {
+            size.worktree_bytes =
+                directory_size(workdir, &mut |name| name == std::ffi::OsStr::new(".git"))?;
+        }
+        Ok(size)
+    }
+
+    fn remote_tracking_branches(&self) -> Result<Vec<RemoteTrackingInfo>> {
+        let mut branches = Vec::new();
+        for reference in self.references_glob("refs/remotes/*")? {
+            let reference = reference?;
+            let (Some(name), Some(id)) = (reference.name(), reference.target()) else {
+                continue;
+            };
+            let name: RemoteRefname = name
+                .parse()
+                .with_context(|| format!("failed to parse remote-tracking branch '{name}'"))?;
+            // The newest entry comes first.
+            let last_updated = self.reflog(&name.to_string())?.get(0).map(|entry| {
+                let seconds = entry.committer().when().seconds();
+                SystemTime::UNIX_EPOCH
+                    + Duration::from_secs(u64::try_from(seconds).unwrap_or_default())
+            });
+            branches.push(RemoteTrackingInfo {
+                name,
+                id,
+                last_updated,
+            });
+        }
+        branches.sort_by_key(|branch| branch.name.to_string());
+        Ok(branches)
+    }
+
+    fn path_status(&self, path: &Path) -> Result<PathState> {
+        use git2::Status as S;
+        let status = self.status_file(path).map_err(|err| match err.code() {
+            git2::ErrorCode::NotFound => anyhow!(
+                "'{}' is neither tracked nor present in the worktree",
+                path.display()
+            ),
+            _ => err.into(),
+        })?;
+        Ok(if status.contains(S::IGNORED) {
+            PathState::Ignored
+        } else if status.intersects(S::INDEX_DELETED | S::WT_DELETED) {
+            PathState::Tracked {
+                status: Some(ChangeType::Deleted),
+            }
+        } else if status.contains(S::INDEX_NEW) {
+            PathState::Tracked {
+                status: Some(ChangeType::Added),
+            }
+        } else if status.contains(S::WT_NEW) {
+            PathState::Untracked
+        } else if status.is_empty() {
+            PathState::Tracked { status: None }
+        } else {
+            PathState::Tracked {
+                status: Some(ChangeType::Modified),
+            }
+        })
+    }
+
+    fn update_refs(&self, updates: Vec<RefUpdate>) -> Result<()> {
+        let mut transaction = self.transaction()?;
+        for update in &updates {
+            transaction.lock_ref(&update.name)?;
+        }
+        // Only now that they are locked, the values can't change anymore.
+        for RefUpdate { name, expected, .. } in &updates {
+            let actual = match self.refname_to_id(name) {
+                Ok(id) => Some(id),
+                Err(err) if err.code() == git2::ErrorCode::NotFound => None,
+                Err(err) => return Err(err.into()),
+            };
+            if actual != *expected {
+                let display = |id: Option<git2::Oid>| {
+                    id.map_or_else(|| "nothing".to_owned(), |id| id.to_string())
+                };
+                return Err(anyhow!(
+                    "Reference '{name}' was expected to point to {}, but points to {}",
+                    display(*expected),
+                    display(actual)
+                )
+                .context(Code::Validation));
+            }
+        }
+        for RefUpdate { name, new, .. } in &updates {
+            match new {
+                Some(id) => transaction.set_target(name, *id, None, "GitButler: update refs")?,
+                None => transaction.remove(name)?,
+            }
+        }
+        transaction.commit()?;
+        mutation::notify(self, MutationKind::RefUpdate);
+        Ok(())
+    }
+
+    fn refs_matching(&self, glob: &str) -> Result<Vec<(String, git2::Oid)>> {
+        // Replace the special characters of the glob to validate the rest of the name.
+        if !git2::Reference::is_valid_name(&glob.replace(['*', '?', '[', ']'], "x")) {
+            return Err(anyhow!(
+                "'{glob}' isn't a valid pattern for reference names, like 'refs/heads/*'"
+            )
+            .context(Code::Validation));
+        }
+        let mut refs = Vec::new();
+        for reference in self.references_glob(glob)? {
+            let reference = reference?;
+            let (Some(name), Ok(target)) = (reference.name(), reference.resolve()) else {
+                continue;
+            };
+            if let Some(id) = target.target() {
+                refs.push((name.to_owned(), id));
+            }
+        }
+        refs.sort();
+        Ok(refs)
+    }
+
+    fn require_clean(&self) -> Result<()> {
+        let mut opts = git2::StatusOptions::new();
+        opts.include_untracked(false)
+            .include_ignored(false)
+            .exclude_submodules(true);
+        let paths: Vec<_> = self
+            .statuses(Some(&mut opts))?
+            .iter()
+            .filter(|entry| entry.status() != git2::Status::CURRENT)
+            .filter_map(|entry| entry.path().map(PathBuf::from))
+            .collect();
+        if paths.is_empty() {
+            return Ok(());
+        }
+        Err(anyhow::Error::new(DirtyWorktree { paths }).context(Code::ProjectDirtyWorktree))
+    }
+
+    fn clear_stale_locks(&self, max_age: Duration) -> Result<Vec<PathBuf>> {
+        let mut git_dirs = vec![self.path()];
+        // Linked worktrees have their own index, but share the references.
+        if self.commondir() != self.path() {
+            git_dirs.push(self.commondir());
+        }
+        let mut candidates = Vec::new();
+        for git_dir in git_dirs {
+            collect_lock_files(git_dir, false, &mut candidates)?;
+            collect_lock_files(&git_dir.join("refs"), true, &mut candidates)?;
+        }
+
+        let now = SystemTime::now();
+        let mut removed = Vec::new();
+        for path in candidates {
+            let modified = match std::fs::symlink_metadata(&path).and_then(|m| m.modified()) {
+                Ok(modified) => modified,
+                // The process holding the lock just finished.
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err.into()),
+            };
+            if now
+                .duration_since(modified)
+                .map_or(true, |age| age <= max_age)
+            {
+                continue;
+            }
+            match std::fs::remove_file(&path) {
+                Ok(()) => removed.push(path),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => {
+                    return Err(anyhow::Error::from(err)
+                        .context(format!("failed to remove '{}'", path.display())))
+                }
+            }
+        }
+        removed.sort();
+        Ok(removed)
+    }
+
+    fn crlf_in_index(&self) -> Result<Vec<PathBuf>> {
+        let mut index = self.index()?;
+        index.read(true).context("failed to read the index")?;
+        let mut paths = Vec::new();
+        for entry in index.iter() {
+            let is_conflicted = (entry.flags >> 12) & 0x3 != 0;
+            let is_file = entry.mode & 0o170000 == 0o100000;
+            if is_conflicted || !is_file {
+                continue;
+            }
+            let path = gix::path::try_from_bstr(BStr::new(&entry.path))?.into_owned();
+            let text = self.get_attr(&path, "text", git2::AttrCheckFlags::INDEX_THEN_FILE)?;
+            if matches!(git2::AttrValue::from_string(text), git2::AttrValue::False) {
+                continue;
+            }
+            let blob = self.find_blob(entry.id)?;
+            let stats = gix::filter::plumbing::eol::Stats::from_bytes(blob.content());
+            if !stats.is_binary() && stats.crlf > 0 {
+                paths.push(path);
+            }
+        }
+        Ok(paths)
+    }
+
+    fn lfs_candidates(&self, threshold: u64) -> Result<Vec<(PathBuf, u64)>> {
+        let mut index = self.index()?;
+        index.read(true).context("failed to read the index")?;
+        let odb = self.odb()?;
+        let mut candidates = Vec::new();
+        for entry in index.iter() {
+            let is_conflicted = (entry.flags >> 12) & 0x3 != 0;
+            let is_file = entry.mode & 0o170000 == 0o100000;
+            if is_conflicted || !is_file {
+                continue;
+            }
+            // Only read the header, as most files are small enough to not need their content.
+            let (size, _) = odb.read_header(entry.id)?;
+            let size = size as u64;
+            if size <= threshold {
+                continue;
+            }
+            let path = gix::path::try_from_bstr(BStr::new(&entry.path))?.into_owned();
+            let filter = self.get_attr(&path, "filter", git2::AttrCheckFlags::INDEX_THEN_FILE)?;
+            if filter == Some("lfs") {
+                continue;
+            }
+            let text = self.get_attr(&path, "text", git2::AttrCheckFlags::INDEX_THEN_FILE)?;
+            let mut is_binary =
+                matches!(git2::AttrValue::from_string(text), git2::AttrValue::False);
+            if !is_binary {
+                // Like Git, only sniff the beginning of the content, without reading all of it.
+                self.read_blob_stream(entry.id, |chunk| {
+                    let sniffed = &chunk[..chunk.len().min(8000)];
+                    is_binary = gix::filter::plumbing::eol::Stats::from_bytes(sniffed).is_binary();
+                    ControlFlow::Break(())
+                })?;
+            }
+            if is_binary {
+                candidates.push((path, size));
+            }
+        }
+        Ok(candidates)
+    }
+
+    fn commit_template(&self) -> Result<Option<String>> {
+        let path = match self.config()?.get_path("commit.template") {
+            Ok(path) => path,
+            Err(err) if err.code() == git2::ErrorCode::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let path = match self.workdir() {
+            Some(workdir) if path.is_relative() => workdir.join(path),
+            _ => path,
+        };
+        match std::fs::read(&path) {
+            Ok(content) => Ok(Some(String::from_utf8_lossy(&content).into_owned())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(anyhow::Error::from(err).context(format!(
+                "failed to read the commit template at '{}'",
+                path.display()
+            ))),
+        }
+    }
+
+    fn diff_colors(&self) -> Result<DiffColors> {
+        let config = self.config()?.snapshot()?;
+        let color = |keys: &[&str]| -> Result<Option<Color>> {
+            for key in keys {
+                match config.get_str(key) {
+                    Ok(value) => {
+                        return parse_color(value)
+                            .with_context(|| format!("failed to parse '{key}'"))
+                            .map(Some)
+                    }
+                    Err(err) if err.code() == git2::ErrorCode::NotFound => continue,
+                    Err(err) => return Err(err.into()),
+                }
+            }
+            Ok(None)
+        };
+        let defaults = DiffColors::default();
+        Ok(DiffColors {
+            added: color(&["color.diff.new"])?.unwrap_or(defaults.added),
+            removed: color(&["color.diff.old"])?.unwrap_or(defaults.removed),
+            context: color(&["color.diff.context", "color.diff.plain"])?
+                .unwrap_or(defaults.context),
+            meta: color(&["color.diff.meta"])?.unwrap_or(defaults.meta),
+        })
+    }
+
+    fn read_note(&self, commit: git2::Oid, notes_ref: Option<&str>) -> Result<Option<String>> {
+        match self.find_note(notes_ref, commit) {
+            Ok(note) => Ok(Some(
+                String::from_utf8_lossy(note.message_bytes()).into_owned(),
+            )),
+            Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn write_note(&self, commit: git2::Oid, notes_ref: Option<&str>, content: &str) -> Result<()> {
+        let (author, committer) = self.signatures()?;
+        self.note(&author, &committer, notes_ref, commit, content, true)?;
+        Ok(())
+    }
+
+    fn create_tag(
+        &self,
+        name: &str,
+        target: git2::Oid,
+        message: Option<&str>,
+        force: bool,
+    ) -> Result<git2::Oid> {
+        if !force && self.find_reference(&format!("refs/tags/{name}")).is_ok() {
+            return Err(anyhow!("Tag '{name}' already exists").context(Code::ProjectRefExists));
+        }
+        let target = self.find_object(target, None)?;
+        let id = match message {
+            Some(message) => {
+                let (_, tagger) = self.signatures()?;
+                self.tag(name, &target, &tagger, message, force)?
+            }
+            None => self.tag_lightweight(name, &target, force)?,
+        };
+        Ok(id)
+    }
+
+    fn stash_show(&self, index: usize) -> Result<Vec<gitbutler_diff::FileDiff>> {
+        let entry = self
+            .reflog("refs/stash")?
+            .get(index)
+            .map(|entry| entry.id_new())
+            .ok_or_else(|| {
+                anyhow!("There is no stash entry at index {index}").context(Code::Validation)
+            })?;
+        let stash = self.find_commit(entry)?;
+        let base_tree = stash.parent(0)?.tree()?;
+        let mut files = gitbutler_diff::trees(self, &base_tree, &stash.tree()?, true)?;
+        // The third parent only has the untracked files in its tree.
+        if let Ok(untracked) = stash.parent(2) {
+            let empty_tree = self.find_tree(self.treebuilder(None)?.write()?)?;
+            files.extend(gitbutler_diff::trees(
+                self,
+                &empty_tree,
+                &untracked.tree()?,
+                true,
+            )?);
+        }
+        let mut files: Vec<_> = files.into_values().collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(files)
+    }
+
+    fn read_blob_stream(
+        &self,
+        id: git2::Oid,
+        mut on_chunk: impl FnMut(&[u8]) -> ControlFlow<()>,
+    ) -> Result<()> {
+        let odb = self.odb()?;
+        let Ok((mut reader, _size, kind)) = odb.reader(id) else {
+            let blob = self.find_blob(id)?;
+            for chunk in blob.content().chunks(BLOB_STREAM_CHUNK_SIZE) {
+                if on_chunk(chunk).is_break() {
+                    break;
+                }
+            }
+            return Ok(());
+        };
+        if kind != git2::ObjectType::Blob {
+            bail!("Object {id} is a {kind}, not a blob");
+        }
+        let mut buf = vec![0; BLOB_STREAM_CHUNK_SIZE];
+        loop {
+            let mut filled = 0;
+            while filled < buf.len() {
+                let read = reader.read(&mut buf[filled..])?;
+                if read == 0 {
+                    break;
+                }
+                filled += read;
+            }
+            if filled == 0 || on_chunk(&buf[..filled]).is_break() || filled < buf.len() {
+                return Ok(());
+            }
+        }
+    }
+
+    fn create_merge_commit(
+        &self,
+        tree: git2::Oid,
+        parents: &[git2::Oid],
+        message: &str,
+    ) -> Result<git2::Oid> {
+        if parents.is_empty() {
+            return Err(
+                anyhow!("A merge commit needs at least one parent").context(Code::Validation)
+            );
+        }
+        let tree = self.find_tree(tree).map_err(|err| {
+            anyhow!("The tree {tree} of the merge commit doesn't exist: {err}")
+                .context(Code::Validation)
+        })?;
+        let parents = parents
+            .iter()
+            .map(|parent| {
+                self.find_commit(*parent).map_err(|err| {
+                    anyhow!("The parent {parent} of the merge commit doesn't exist: {err}")
+                        .context(Code::Validation)
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let (author, committer) = self.signatures()?;
+        self.commit_with_signature(
+            None,
+            &author,
+            &committer,
+            message,
+            &tree,
+            &parents.iter().collect::<Vec<_>>(),
+            None,
+        )
+    }
+
+    fn has_identity(&self) -> bool {
+        gix::open(self.path()).ok().is_some_and(|repo| {
+            repo.author()
+                .and_then(Result::ok)
+                .is_some_and(|author| is_complete_identity(&author))
+        })
+    }
+
+    fn identity_source(&self) -> Result<IdentitySource> {
+        let config = self.config()?.snapshot()?;
+        let entry = |key: &str| -> Result<Option<(BString, git2::ConfigLevel)>> {
+            match config.get_entry(key) {
+                Ok(entry) => Ok(Some((entry.value_bytes().into(), entry.level()))),
+                Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(None),
+                Err(err) => Err(err.into()),
+            }
+        };
+        let (Some((name, name_level)), Some((email, email_level))) =
+            (entry("user.name")?, entry("user.email")?)
+        else {
+            return Ok(IdentitySource::None);
+        };
+        if name.is_empty() || email.is_empty() {
+            return Ok(IdentitySource::None);
+        }
+
+        use git2::ConfigLevel as L;
+        let specificity = |level: L| match level {
+            L::ProgramData | L::System => 0,
+            L::XDG | L::Global => 1,
+            _ => 2,
+        };
+        let identity = Identity { name, email };
+        Ok(
+            match specificity(name_level).max(specificity(email_level)) {
+                0 => IdentitySource::System(identity),
+                1 => IdentitySource::Global(identity),
+                _ => IdentitySource::Local(identity),
+            },
+        )
+    }
+
     fn merge_base_octopussy(&self, ids: &[git2::Oid]) -> Result<git2::Oid> {
         if ids.len() < 2 {
             bail!("Merge base octopussy requires at least two commit ids to operate on");
@@ -424,13 +1354,103 @@ impl RepositoryExt for git2::Repository {
     }
 }
 
+/// Return the entries of `dir`, or none if it doesn't exist.
+fn read_dir_if_exists(
+    dir: &Path,
+) -> Result<impl Iterator<Item = std::io::Result<std::fs::DirEntry>>> {
+    Ok(match std::fs::read_dir(dir) {
+        Ok(entries) => Some(entries),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+        Err(err) => return Err(err.into()),
+    }
+    .into_iter()
+    .flatten())
+}
+
+/// Return the size of all files in `dir` and its subdirectories, without following symlinks and
+/// skipping the directories whose name `skip` returns `true` for.
+fn directory_size(dir: &Path, skip: &mut dyn FnMut(&std::ffi::OsStr) -> bool) -> Result<u64> {
+    let mut size = 0;
+    let mut dirs = vec![dir.to_owned()];
+    while let Some(dir) = dirs.pop() {
+        for entry in read_dir_if_exists(&dir)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                if !skip(&entry.file_name()) {
+                    dirs.push(entry.path());
+                }
+            } else {
+                size += entry.metadata()?.len();
+            }
+        }
+    }
+    Ok(size)
+}
+
+/// Add the paths of all `*.lock` files in `dir` to `out`, along with those in its subdirectories
+/// if `recursive` is set.
+fn collect_lock_files(dir: &Path, recursive: bool, out: &mut Vec<PathBuf>) -> Result<()> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+    for entry in entries {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let path = entry.path();
+        if file_type.is_dir() {
+            if recursive {
+                collect_lock_files(&path, recursive, out)?;
+            }
+        } else if file_type.is_file() && path.extension().is_some_and(|ext| ext == "lock") {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Open the repository at `path` for use with worktree filters, which convert line endings and apply
+/// other `.gitattributes` driven transformations when turning worktree files into blobs.
+fn open_for_worktree_filters(path: &Path) -> Result<gix::Repository> {
+    Ok(gix::open_opts(
+        path,
+        gix::open::Options::default().permissions(gix::open::Permissions {
+            config: gix::open::permissions::Config {
+                // Whenever we deal with worktree filters, we'd want to have the installation configuration as well,
+                // as this is where Git for Windows sets `core.autocrlf`.
+                git_binary: cfg!(windows),
+                ..Default::default()
+            },
+            ..Default::default()
+        }),
+    )?)
+}
+
+/// Empty values are as good as no value, as they aren't accepted when creating commits.
+fn is_complete_identity(identity: &gix::actor::SignatureRef<'_>) -> bool {
+    !identity.name.is_empty() && !identity.email.is_empty()
+}
+
 pub struct CheckoutTreeBuidler<'a> {
     repo: &'a git2::Repository,
     tree: &'a git2::Tree<'a>,
     checkout_builder: git2::build::CheckoutBuilder<'a>,
 }
 
-impl CheckoutTreeBuidler<'_> {
+/// The progress of a checkout, as reported to [`CheckoutTreeBuidler::progress()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckoutProgress<'a> {
+    /// The worktree-relative path that was just written, if any.
+    pub path: Option<&'a Path>,
+    /// The amount of steps completed so far, which equals `total` when the checkout is done.
+    pub completed: usize,
+    /// The amount of steps the checkout takes in total.
+    pub total: usize,
+}
+
+impl<'a> CheckoutTreeBuidler<'a> {
     pub fn force(&mut self) -> &mut Self {
         self.checkout_builder.force();
         self
@@ -441,9 +1461,84 @@ impl CheckoutTreeBuidler<'_> {
         self
     }
 
+    /// Call `progress` each time the checkout made progress, for display to the user.
+    /// Without it, no progress is tracked at all.
+    pub fn progress(&mut self, mut progress: impl FnMut(CheckoutProgress<'_>) + 'a) -> &mut Self {
+        self.checkout_builder
+            .progress(move |path, completed, total| {
+                progress(CheckoutProgress {
+                    path,
+                    completed,
+                    total,
+                })
+            });
+        self
+    }
+
+    /// Perform the checkout.
+    ///
+    /// If it's refused as local changes would be overwritten, the error is classified as
+    /// [`Code::ProjectCheckoutWouldOverwrite`] and a [`CheckoutWouldOverwrite`] with all affected paths
+    /// can be obtained from it.
     pub fn checkout(&mut self) -> Result<()> {
-        self.repo
+        let conflicting_paths = Rc::new(RefCell::new(Vec::new()));
+        self.checkout_builder
+            .notify_on(git2::CheckoutNotificationType::CONFLICT)
+            .notify({
+                let conflicting_paths = Rc::clone(&conflicting_paths);
+                move |kind, path, _baseline, _target, _workdir| {
+                    if let Some(path) =
+                        path.filter(|_| kind.contains(git2::CheckoutNotificationType::CONFLICT))
+                    {
+                        conflicting_paths.borrow_mut().push(path.to_owned());
+                    }
+                    true
+                }
+            });
+        match self
+            .repo
             .checkout_tree(self.tree.as_object(), Some(&mut self.checkout_builder))
-            .map_err(Into::into)
+        {
+            Ok(()) => {
+                mutation::notify(self.repo, MutationKind::Checkout);
+                Ok(())
+            }
+            Err(err) if err.code() == git2::ErrorCode::Conflict => {
+                let paths = conflicting_paths.take();
+                if paths.is_empty() {
+                    return Err(err.into());
+                }
+                Err(anyhow::Error::new(CheckoutWouldOverwrite { paths })
+                    .context(Code::ProjectCheckoutWouldOverwrite))
+            }
+            Err(err) => Err(err.into()),
+        }
     }
 }
+
+/// The error produced if a checkout can't proceed as `paths` have local modifications that would be overwritten.
+///
+/// This is different from a merge conflict, as nothing was merged, and stashing or discarding these changes
+/// would allow the checkout to succeed.
+#[derive(Debug, thiserror::Error)]
+#[error("Checkout would overwrite local changes in {}", join_paths(.paths))]
+pub struct CheckoutWouldOverwrite {
+    /// Worktree-relative paths with local changes.
+    pub paths: Vec<PathBuf>,
+}
+
+/// The error produced by [`RepositoryExt::require_clean()`] if `paths` have changes.
+#[derive(Debug, thiserror::Error)]
+#[error("Please commit or stash your changes first, as there are local changes in {}", join_paths(.paths))]
+pub struct DirtyWorktree {
+    /// Worktree-relative paths with changes in the index or the worktree.
+    pub paths: Vec<PathBuf>,
+}
+
+fn join_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,145 @@
+//! Find credentials in files before they are committed or pushed, so the user can be warned.
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use bstr::ByteSlice;
+
+/// A kind of secret to look for, see [`builtin_rules()`].
+#[derive(Debug, Clone)]
+pub struct SecretRule {
+    /// The name to report findings under, like `aws-access-key-id`.
+    pub name: String,
+    /// Matches the secret within a single line.
+    pub pattern: regex::bytes::Regex,
+    /// If set, matches are only reported if the secret, which is the first capture group of `pattern`
+    /// or the whole match, has at least this Shannon entropy in bits per character.
+    /// This avoids reporting placeholders like `token = "xxxxxxxxxxxxxxxxxxxx"`.
+    pub min_entropy: Option<f64>,
+}
+
+impl SecretRule {
+    /// Create a rule named `name` that finds lines matching `pattern`.
+    pub fn new(name: impl Into<String>, pattern: &str) -> Result<Self> {
+        Ok(SecretRule {
+            name: name.into(),
+            pattern: regex::bytes::Regex::new(pattern)
+                .with_context(|| format!("invalid pattern for secrets: {pattern}"))?,
+            min_entropy: None,
+        })
+    }
+
+    /// Only report matches with at least `bits` of entropy per character.
+    pub fn with_min_entropy(mut self, bits: f64) -> Self {
+        self.min_entropy = Some(bits);
+        self
+    }
+
+    fn is_match(&self, line: &[u8]) -> bool {
+        self.pattern.captures_iter(line).any(|captures| {
+            let secret = captures.get(1).or_else(|| captures.get(0));
+            match (self.min_entropy, secret) {
+                (Some(min_entropy), Some(secret)) => entropy(secret.as_bytes()) >= min_entropy,
+                _ => true,
+            }
+        })
+    }
+}
+
+/// A line that looks like it contains a secret, as found by [`scan_secrets()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretFinding {
+    /// The worktree-relative path of the file.
+    pub path: PathBuf,
+    /// The one-based line number.
+    pub line: usize,
+    /// The [name](SecretRule::name) of the rule that matched.
+    pub rule: String,
+}
+
+/// Return the rules for commonly leaked secrets, which can be extended with custom rules.
+pub fn builtin_rules() -> Vec<SecretRule> {
+    let rule = |name, pattern| SecretRule::new(name, pattern).expect("built-in patterns are valid");
+    vec![
+        rule("aws-access-key-id", r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b"),
+        rule(
+            "private-key",
+            r"-----BEGIN (?:[A-Z0-9]+ )*PRIVATE KEY(?: BLOCK)?-----",
+        ),
+        rule("github-token", r"\bgh[pousr]_[A-Za-z0-9]{36}\b"),
+        rule(
+            "generic-token",
+            r#"(?i)(?:api[_-]?key|secret|token|passw(?:or)?d)["']?\s*[:=]\s*["']?([A-Za-z0-9+/_\-]{20,})"#,
+        )
+        .with_min_entropy(3.5),
+    ]
+}
+
+/// Scan the worktree content of each of `paths` in `repo` for secrets matching any of `rules`,
+/// and return the findings in the order of `paths` and lines.
+///
+/// Paths that don't exist in the worktree anymore, and binary files, are skipped.
+pub fn scan_secrets(
+    repo: &git2::Repository,
+    paths: &[PathBuf],
+    rules: &[SecretRule],
+) -> Result<Vec<SecretFinding>> {
+    let workdir = repo
+        .workdir()
+        .context("Can't scan files of a bare repository")?;
+    let mut findings = Vec::new();
+    for path in paths {
+        let Some(content) = read_text(&workdir.join(path))? else {
+            continue;
+        };
+        for (line_idx, line) in content.lines().enumerate() {
+            for rule in rules.iter().filter(|rule| rule.is_match(line)) {
+                findings.push(SecretFinding {
+                    path: path.clone(),
+                    line: line_idx + 1,
+                    rule: rule.name.clone(),
+                });
+            }
+        }
+    }
+    Ok(findings)
+}
+
+/// Return the content of the file at `path`, or `None` if it doesn't exist or is binary.
+fn read_text(path: &Path) -> Result<Option<Vec<u8>>> {
+    let content = match std::fs::read(path) {
+        Ok(content) => content,
+        Err(err)
+            if matches!(
+                err.kind(),
+                std::io::ErrorKind::NotFound | std::io::ErrorKind::IsADirectory
+            ) =>
+        {
+            return Ok(None)
+        }
+        Err(err) => {
+            return Err(
+                anyhow::Error::from(err).context(format!("failed to read '{}'", path.display()))
+            )
+        }
+    };
+    // Git considers files with a NUL byte in the first 8000 bytes binary.
+    let is_binary = content[..content.len().min(8000)].contains(&0);
+    Ok((!is_binary).then_some(content))
+}
+
+/// Return the Shannon entropy of `bytes` in bits per byte.
+fn entropy(bytes: &[u8]) -> f64 {
+    let mut counts = [0usize; 256];
+    for byte in bytes {
+        counts[*byte as usize] += 1;
+    }
+    let len = bytes.len() as f64;
+    counts
+        .iter()
+        .filter(|count| **count > 0)
+        .map(|count| {
+            let probability = *count as f64 / len;
+            -probability * probability.log2()
+        })
+        .sum()
+}
@@ -0,0 +1,54 @@
+//! Control which parts of the tree are materialized in the worktree.
+use anyhow::{anyhow, Result};
+use gitbutler_error::error::Code;
+
+use crate::git_cli;
+
+/// Limit the worktree of `repo` to what matches `patterns`, and re-apply it so that everything else
+/// is removed from disk while remaining in the index.
+///
+/// In `cone` mode, each pattern is a directory which is included recursively, along with all files
+/// in the root of the repository and in the parent directories of each pattern.
+/// Otherwise `patterns` are `.gitignore`-style patterns of what to include.
+pub fn set_sparse_checkout(repo: &git2::Repository, patterns: &[String], cone: bool) -> Result<()> {
+    if cone {
+        if let Some(invalid) = patterns
+            .iter()
+            .find(|pattern| pattern.starts_with('!') || pattern.contains(['*', '?', '[', '\\']))
+        {
+            return Err(anyhow!(
+                "'{invalid}' isn't a directory, which is the only kind of pattern allowed in cone mode"
+            )
+            .context(Code::Validation));
+        }
+    }
+    let mut cmd = git_cli::git(repo);
+    cmd.args(["sparse-checkout", "set"])
+        .arg(if cone { "--cone" } else { "--no-cone" })
+        .arg("--")
+        .args(patterns);
+    git_cli::run(cmd)?;
+    Ok(())
+}
+
+/// Return the patterns previously configured with [`set_sparse_checkout()`], or an empty list if the
+/// worktree isn't sparse.
+///
+/// In cone mode, these are the included directories.
+pub fn sparse_checkout_patterns(repo: &git2::Repository) -> Result<Vec<String>> {
+    let is_sparse = repo
+        .config()?
+        .get_bool("core.sparseCheckout")
+        .unwrap_or(false);
+    if !is_sparse {
+        return Ok(Vec::new());
+    }
+    let mut cmd = git_cli::git(repo);
+    cmd.args(["sparse-checkout", "list"]);
+    Ok(git_cli::run(cmd)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(ToOwned::to_owned)
+        .collect())
+}
@@ -1,9 +1,14 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
+use bstr::ByteSlice;
 use git2::{ApplyLocation, ApplyOptions, Repository};
 use gitbutler_command_context::CommandContext;
 use gitbutler_diff::{ChangeType, FileDiff, GitHunk, Hunk};
+use gitbutler_error::error::Code;
 
 fn stage_tracked_changes(
     ctx: &CommandContext,
@@ -97,3 +102,124 @@ pub fn reset_index(repo: &Repository, tree_id: git2::Oid) -> Result<()> {
     index.read_tree(&tree)?;
     Ok(index.write()?)
 }
+
+/// An entry of the index, as returned by [`index_entries()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexEntry {
+    /// The worktree-relative path of the entry.
+    pub path: PathBuf,
+    /// The id of the blob, or of the commit for submodules.
+    pub id: git2::Oid,
+    pub mode: git2::FileMode,
+    /// `0` for entries that aren't conflicted, otherwise `1` for the base, `2` for ours and `3` for theirs.
+    pub stage: u8,
+}
+
+/// Return all entries of the index of `repo` as currently stored on disk, sorted by path and stage.
+pub fn index_entries(repo: &Repository) -> Result<Vec<IndexEntry>> {
+    let index = fresh_index(repo)?;
+    index
+        .iter()
+        .map(|entry| {
+            Ok(IndexEntry {
+                path: gix::path::try_from_bstr(entry.path.as_bstr())?.into_owned(),
+                id: entry.id,
+                mode: file_mode(entry.mode),
+                stage: ((entry.flags >> 12) & 0x3) as u8,
+            })
+        })
+        .collect()
+}
+
+/// Stage the existing blob `id` with `mode` at the worktree-relative `path`, replacing what's
+/// there, including conflicts, without touching the worktree.
+///
+/// Fails with [`Code::Validation`] if `id` isn't a blob, or if `mode` isn't one of a file or symlink.
+/// Like all index writes, it fails if another process holds the lock on the index.
+pub fn stage_blob(
+    repo: &Repository,
+    path: &Path,
+    id: git2::Oid,
+    mode: git2::FileMode,
+) -> Result<()> {
+    if !matches!(
+        mode,
+        git2::FileMode::Blob | git2::FileMode::BlobExecutable | git2::FileMode::Link
+    ) {
+        return Err(
+            anyhow!("Can only stage files and symlinks, not {mode:?}").context(Code::Validation)
+        );
+    }
+    let blob = repo.find_blob(id).map_err(|err| {
+        anyhow!(
+            "The blob {id} to stage at '{}' doesn't exist: {err}",
+            path.display()
+        )
+        .context(Code::Validation)
+    })?;
+    let path_bytes = gix::path::into_bstr(path).into_owned();
+    let mut index = fresh_index(repo)?;
+    match index.conflict_remove(path) {
+        Ok(()) => {}
+        Err(err) if err.code() == git2::ErrorCode::NotFound => {}
+        Err(err) => return Err(err.into()),
+    }
+    index.add(&git2::IndexEntry {
+        // Zeroed stat information makes Git compare the worktree by content, which differs.
+        ctime: git2::IndexTime::new(0, 0),
+        mtime: git2::IndexTime::new(0, 0),
+        dev: 0,
+        ino: 0,
+        mode: u32::try_from(i32::from(mode))?,
+        uid: 0,
+        gid: 0,
+        file_size: u32::try_from(blob.size()).unwrap_or(u32::MAX),
+        id,
+        flags: path_bytes.len().min(0xfff) as u16,
+        flags_extended: 0,
+        path: path_bytes.into(),
+    })?;
+    write_index(&mut index)
+}
+
+/// Remove all entries at the worktree-relative `path` from the index, including conflicts,
+/// without touching the worktree, which makes it untracked if it exists there.
+///
+/// Fails with [`Code::Validation`] if there is no such entry.
+pub fn remove_index_entry(repo: &Repository, path: &Path) -> Result<()> {
+    let mut index = fresh_index(repo)?;
+    if (0..=3).all(|stage| index.get_path(path, stage).is_none()) {
+        return Err(anyhow!("'{}' isn't in the index", path.display()).context(Code::Validation));
+    }
+    index.remove_path(path)?;
+    write_index(&mut index)
+}
+
+/// Return the index of `repo` with the latest changes made by other processes.
+fn fresh_index(repo: &Repository) -> Result<git2::Index> {
+    let mut index = repo.index()?;
+    index.read(true).context("failed to read the index")?;
+    Ok(index)
+}
+
+/// Write `index`, which is done through `index.lock` so concurrently running `git` processes are respected.
+fn write_index(index: &mut git2::Index) -> Result<()> {
+    index.write().map_err(|err| match err.code() {
+        git2::ErrorCode::Locked => anyhow!(
+            "The index is locked by another process - wait for it to finish, or remove '.git/index.lock' if it crashed"
+        )
+        .context(Code::ProjectGitLocked),
+        _ => err.into(),
+    })
+}
+
+fn file_mode(mode: u32) -> git2::FileMode {
+    use git2::FileMode as M;
+    match mode {
+        0o100755 => M::BlobExecutable,
+        0o120000 => M::Link,
+        0o160000 => M::Commit,
+        0o040000 => M::Tree,
+        _ => M::Blob,
+    }
+}
@@ -0,0 +1,100 @@
+//! Find submodules that are set up incompletely, which makes their state in the worktree confusing.
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::staging::index_entries;
+
+/// A problem with a submodule, as returned by [`submodule_issues()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubmoduleIssue {
+    /// There is a submodule at `path`, but `.gitmodules` doesn't say where it comes from,
+    /// which typically happens when a repository is added with `git add` instead of `git submodule add`.
+    MissingEntry { path: PathBuf },
+    /// The submodule `name` at `path` has an entry in `.gitmodules`, but it lacks the URL to clone from.
+    MissingUrl { name: String, path: PathBuf },
+    /// The submodule `name` at `path` wasn't initialized or cloned yet, so it's an empty directory.
+    Uninitialized { name: String, path: PathBuf },
+    /// The submodule `name` at `path` has `checked_out` instead of the `recorded` commit.
+    OutOfSync {
+        name: String,
+        path: PathBuf,
+        recorded: git2::Oid,
+        checked_out: git2::Oid,
+    },
+}
+
+/// Return the problems of all submodules known to the index of `repo`, sorted by path.
+///
+/// Submodules that are initialized, cloned and have the recorded commit checked out aren't listed.
+pub fn submodule_issues(repo: &git2::Repository) -> Result<Vec<SubmoduleIssue>> {
+    let Some(workdir) = repo.workdir() else {
+        return Ok(Vec::new());
+    };
+    let gitmodules = gitmodules(repo)?;
+    let config = repo.config()?;
+    let mut issues = Vec::new();
+    for entry in index_entries(repo)? {
+        if entry.mode != git2::FileMode::Commit || entry.stage != 0 {
+            continue;
+        }
+        let path = entry.path;
+        let Some((name, url)) = gitmodules
+            .iter()
+            .find(|(_, module_path, _)| *module_path == path)
+            .map(|(name, _, url)| (name.clone(), url))
+        else {
+            issues.push(SubmoduleIssue::MissingEntry { path });
+            continue;
+        };
+        if url.as_ref().is_none_or(|url| url.is_empty()) {
+            issues.push(SubmoduleIssue::MissingUrl { name, path });
+            continue;
+        }
+        let initialized = config.get_string(&format!("submodule.{name}.url")).is_ok();
+        let submodule_repo = git2::Repository::open(workdir.join(&path)).ok();
+        let Some(submodule_repo) = submodule_repo.filter(|_| initialized) else {
+            issues.push(SubmoduleIssue::Uninitialized { name, path });
+            continue;
+        };
+        let checked_out = submodule_repo.head().ok().and_then(|head| head.target());
+        if let Some(checked_out) = checked_out.filter(|id| *id != entry.id) {
+            issues.push(SubmoduleIssue::OutOfSync {
+                name,
+                path,
+                recorded: entry.id,
+                checked_out,
+            });
+        }
+    }
+    Ok(issues)
+}
+
+/// Return the `(name, path, url)` of each submodule in `.gitmodules` of the worktree of `repo`.
+fn gitmodules(repo: &git2::Repository) -> Result<Vec<(String, PathBuf, Option<String>)>> {
+    let Some(path) = repo
+        .workdir()
+        .map(|workdir| workdir.join(".gitmodules"))
+        .filter(|path| path.is_file())
+    else {
+        return Ok(Vec::new());
+    };
+    let config = git2::Config::open(&path)?;
+    let mut modules = Vec::new();
+    let mut entries = config.entries(Some(r"^submodule\..*\.path$"))?;
+    while let Some(entry) = entries.next() {
+        let entry = entry?;
+        let (Some(key), Some(module_path)) = (entry.name(), entry.value()) else {
+            continue;
+        };
+        let Some(name) = key
+            .strip_prefix("submodule.")
+            .and_then(|key| key.strip_suffix(".path"))
+        else {
+            continue;
+        };
+        let url = config.get_string(&format!("submodule.{name}.url")).ok();
+        modules.push((name.to_owned(), PathBuf::from(module_path), url));
+    }
+    Ok(modules)
+}
@@ -0,0 +1,30 @@
+use gitbutler_error::error::{AnyhowContextExt, Code};
+use gitbutler_repo::alternates::open_with_alternates;
+
+#[test]
+fn objects_are_read_from_alternates() {
+    let tmp = tempfile::tempdir().unwrap();
+    let base = git2::Repository::init(tmp.path().join("base")).unwrap();
+    let blob = base.blob(b"only in the base").unwrap();
+    let fork = git2::Repository::init(tmp.path().join("fork")).unwrap();
+    assert!(fork.find_blob(blob).is_err());
+
+    let fork = open_with_alternates(fork.path(), &[base.path().join("objects")]).unwrap();
+    assert_eq!(fork.find_blob(blob).unwrap().content(), b"only in the base");
+    assert!(
+        !fork.path().join("objects/info/alternates").exists(),
+        "nothing is persisted"
+    );
+}
+
+#[test]
+fn missing_alternate_is_refused() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = git2::Repository::init(tmp.path().join("repo")).unwrap();
+
+    let err = open_with_alternates(repo.path(), &[tmp.path().join("missing")]).unwrap_err();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::Validation)
+    );
+}
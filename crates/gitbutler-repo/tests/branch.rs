@@ -0,0 +1,701 @@
+mod rename {
+    use gitbutler_error::error::{AnyhowContextExt, Code};
+    use gitbutler_repo::branch;
+    use gitbutler_testsupport::testing_repository::TestingRepository;
+
+    fn set_upstream(repo: &git2::Repository, name: &str) {
+        let mut config = repo.config().unwrap();
+        config
+            .set_str(&format!("branch.{name}.remote"), "origin")
+            .unwrap();
+        config
+            .set_str(
+                &format!("branch.{name}.merge"),
+                &format!("refs/heads/{name}"),
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn current_branch() {
+        let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+        let repo = &test_repository.repository;
+        let head_id = repo.head().unwrap().target().unwrap();
+        set_upstream(repo, "master");
+
+        branch::rename(repo, "master", "main").unwrap();
+
+        assert_eq!(repo.head().unwrap().name(), Some("refs/heads/main"));
+        assert_eq!(repo.head().unwrap().target(), Some(head_id));
+        assert!(repo.find_branch("master", git2::BranchType::Local).is_err());
+        let config = repo.config().unwrap().snapshot().unwrap();
+        assert_eq!(config.get_str("branch.main.remote").unwrap(), "origin");
+        assert_eq!(
+            config.get_str("branch.main.merge").unwrap(),
+            "refs/heads/master",
+            "the upstream branch isn't renamed along with the local one"
+        );
+        assert!(config.get_str("branch.master.remote").is_err());
+    }
+
+    #[test]
+    fn other_branch() {
+        let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+        let repo = &test_repository.repository;
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.branch("feature", &head, false).unwrap();
+        set_upstream(repo, "feature");
+
+        branch::rename(repo, "feature", "renamed").unwrap();
+
+        assert_eq!(repo.head().unwrap().name(), Some("refs/heads/master"));
+        let config = repo.config().unwrap().snapshot().unwrap();
+        assert_eq!(config.get_str("branch.renamed.remote").unwrap(), "origin");
+        let reflog = repo.reflog("refs/heads/renamed").unwrap();
+        assert!(!reflog.is_empty(), "the reflog moves with the branch");
+    }
+
+    #[test]
+    fn existing_destination_is_refused() {
+        let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+        let repo = &test_repository.repository;
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.branch("feature", &head, false).unwrap();
+
+        let err = branch::rename(repo, "master", "feature").unwrap_err();
+        assert_eq!(
+            err.custom_context().map(|ctx| ctx.code),
+            Some(Code::ProjectRefExists)
+        );
+        assert!(repo.find_branch("master", git2::BranchType::Local).is_ok());
+    }
+
+    #[test]
+    fn unknown_branch_is_refused() {
+        let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+        let err = branch::rename(&test_repository.repository, "missing", "new").unwrap_err();
+        assert_eq!(
+            err.custom_context().map(|ctx| ctx.code),
+            Some(Code::Validation)
+        );
+    }
+}
+
+mod create {
+    use gitbutler_error::error::{AnyhowContextExt, Code};
+    use gitbutler_repo::branch;
+    use gitbutler_testsupport::testing_repository::TestingRepository;
+
+    #[test]
+    fn new_branch() {
+        let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+        let repo = &test_repository.repository;
+        let head_id = repo.head().unwrap().target().unwrap();
+
+        branch::create(repo, "feature", head_id, false).unwrap();
+
+        let branch = repo
+            .find_branch("feature", git2::BranchType::Local)
+            .unwrap();
+        assert_eq!(branch.get().target(), Some(head_id));
+        assert_eq!(
+            repo.head().unwrap().name(),
+            Some("refs/heads/master"),
+            "the new branch isn't checked out"
+        );
+    }
+
+    #[test]
+    fn existing_branch_is_refused_unless_forced() {
+        let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+        let repo = &test_repository.repository;
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        let other = test_repository.commit_tree(Some(&head), &[("file", "b")]);
+        repo.branch("feature", &head, false).unwrap();
+
+        let err = branch::create(repo, "feature", other.id(), false).unwrap_err();
+        assert_eq!(
+            err.custom_context().map(|ctx| ctx.code),
+            Some(Code::ProjectRefExists)
+        );
+        let branch = repo
+            .find_branch("feature", git2::BranchType::Local)
+            .unwrap();
+        assert_eq!(branch.get().target(), Some(head.id()));
+
+        branch::create(repo, "feature", other.id(), true).unwrap();
+        let branch = repo
+            .find_branch("feature", git2::BranchType::Local)
+            .unwrap();
+        assert_eq!(branch.get().target(), Some(other.id()));
+    }
+
+    #[test]
+    fn invalid_name_is_refused() {
+        let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+        let repo = &test_repository.repository;
+        let head_id = repo.head().unwrap().target().unwrap();
+
+        let err = branch::create(repo, "in..valid", head_id, false).unwrap_err();
+        assert_eq!(
+            err.custom_context().map(|ctx| ctx.code),
+            Some(Code::Validation)
+        );
+    }
+}
+
+mod checkout {
+    use gitbutler_error::error::{AnyhowContextExt, Code};
+    use gitbutler_repo::branch::{self, CheckoutOptions};
+    use gitbutler_repo::RepositoryExt as _;
+    use gitbutler_testsupport::testing_repository::TestingRepository;
+
+    /// Return a repository with `master` checked out, and a branch `other` which changes `file`.
+    fn repo_with_other_branch() -> TestingRepository {
+        let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+        {
+            let repo = &test_repository.repository;
+            let head = repo.head().unwrap().peel_to_commit().unwrap();
+            let other = test_repository.commit_tree(Some(&head), &[("file", "b")]);
+            repo.branch("other", &other, false).unwrap();
+            repo.checkout_tree_builder(&head.tree().unwrap())
+                .force()
+                .checkout()
+                .unwrap();
+        }
+        test_repository
+    }
+
+    #[test]
+    fn safe_checkout_refuses_to_overwrite_local_changes_unless_forced() {
+        let test_repository = repo_with_other_branch();
+        let repo = &test_repository.repository;
+        let file = test_repository.tempdir.path().join("file");
+        std::fs::write(&file, "local").unwrap();
+
+        let err = branch::checkout(repo, "other", CheckoutOptions::default()).unwrap_err();
+        assert_eq!(
+            err.custom_context().map(|ctx| ctx.code),
+            Some(Code::ProjectCheckoutWouldOverwrite)
+        );
+        assert_eq!(repo.head().unwrap().name(), Some("refs/heads/master"));
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "local");
+
+        branch::checkout(repo, "other", CheckoutOptions { force: true }).unwrap();
+        assert_eq!(repo.head().unwrap().name(), Some("refs/heads/other"));
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "b");
+    }
+
+    #[test]
+    fn clean_worktree() {
+        let test_repository = repo_with_other_branch();
+        let repo = &test_repository.repository;
+
+        branch::checkout(repo, "other", CheckoutOptions::default()).unwrap();
+        assert_eq!(repo.head().unwrap().name(), Some("refs/heads/other"));
+        assert_eq!(
+            std::fs::read_to_string(test_repository.tempdir.path().join("file")).unwrap(),
+            "b"
+        );
+    }
+
+    #[test]
+    fn remote_tracking_branch_creates_local_tracking_branch() {
+        let test_repository = repo_with_other_branch();
+        let repo = &test_repository.repository;
+        repo.remote("origin", "https://example.com/repo.git")
+            .unwrap();
+        let other_id = repo.refname_to_id("refs/heads/other").unwrap();
+        repo.reference("refs/remotes/origin/feature", other_id, false, "")
+            .unwrap();
+
+        branch::checkout(repo, "origin/feature", CheckoutOptions::default()).unwrap();
+        assert_eq!(repo.head().unwrap().name(), Some("refs/heads/feature"));
+        assert_eq!(repo.head().unwrap().target(), Some(other_id));
+        let local = repo
+            .find_branch("feature", git2::BranchType::Local)
+            .unwrap();
+        assert_eq!(
+            local.upstream().unwrap().get().name(),
+            Some("refs/remotes/origin/feature")
+        );
+    }
+
+    #[test]
+    fn unknown_branch_is_refused() {
+        let test_repository = repo_with_other_branch();
+        let err = branch::checkout(
+            &test_repository.repository,
+            "does-not-exist",
+            CheckoutOptions::default(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.custom_context().map(|ctx| ctx.code),
+            Some(Code::Validation)
+        );
+    }
+}
+
+mod list {
+    use gitbutler_repo::branch;
+    use gitbutler_testsupport::testing_repository::TestingRepository;
+
+    #[test]
+    fn local_branches_sorted_by_name() {
+        let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+        let repo = &test_repository.repository;
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.branch("feature", &head, false).unwrap();
+        repo.reference("refs/remotes/origin/main", head.id(), false, "")
+            .unwrap();
+
+        assert_eq!(
+            branch::list(repo, Default::default()).unwrap(),
+            ["feature", "master"]
+        );
+    }
+
+    #[test]
+    fn internal_branches_only_if_requested() {
+        let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+        let repo = &test_repository.repository;
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.branch("gitbutler/workspace", &head, false).unwrap();
+        repo.branch("internal/cache", &head, false).unwrap();
+
+        assert_eq!(
+            branch::list(repo, Default::default()).unwrap(),
+            ["internal/cache", "master"]
+        );
+        let options = branch::ListOptions {
+            include_internal: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            branch::list(repo, options).unwrap(),
+            ["gitbutler/workspace", "internal/cache", "master"]
+        );
+        let options = branch::ListOptions {
+            internal_namespaces: &["gitbutler/", "internal/"],
+            ..Default::default()
+        };
+        assert_eq!(branch::list(repo, options).unwrap(), ["master"]);
+    }
+}
+
+mod incoming_commits {
+    use gitbutler_error::error::{AnyhowContextExt, Code};
+    use gitbutler_repo::branch;
+    use gitbutler_testsupport::testing_repository::TestingRepository;
+
+    #[test]
+    fn upstream_ahead_by_two_commits() {
+        let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+        let repo = &test_repository.repository;
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        let first =
+            test_repository.commit_tree_with_message(Some(&head), "first", &[("file", "b")]);
+        let second =
+            test_repository.commit_tree_with_message(Some(&first), "second", &[("file", "c")]);
+        repo.remote("origin", "https://example.com/repo.git")
+            .unwrap();
+        repo.reference("refs/remotes/origin/master", second.id(), false, "")
+            .unwrap();
+        repo.find_branch("master", git2::BranchType::Local)
+            .unwrap()
+            .set_upstream(Some("origin/master"))
+            .unwrap();
+
+        let incoming = branch::incoming_commits(repo, "master").unwrap();
+        assert_eq!(
+            incoming
+                .iter()
+                .map(|commit| (commit.id, commit.title.to_string()))
+                .collect::<Vec<_>>(),
+            [
+                (second.id(), "second".to_owned()),
+                (first.id(), "first".to_owned())
+            ],
+            "newest first"
+        );
+
+        repo.reference("refs/heads/master", second.id(), true, "")
+            .unwrap();
+        assert!(
+            branch::incoming_commits(repo, "master").unwrap().is_empty(),
+            "nothing to pull once up to date"
+        );
+    }
+
+    #[test]
+    fn branch_without_upstream_is_refused() {
+        let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+        let err = branch::incoming_commits(&test_repository.repository, "master").unwrap_err();
+        assert_eq!(
+            err.custom_context().map(|ctx| ctx.code),
+            Some(Code::Validation)
+        );
+    }
+}
+
+mod own_commits {
+    use gitbutler_repo::branch;
+    use gitbutler_testsupport::testing_repository::TestingRepository;
+
+    #[test]
+    fn forked_from_target_with_two_commits() {
+        let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+        let repo = &test_repository.repository;
+        let base = repo.head().unwrap().peel_to_commit().unwrap();
+        let target = test_repository.commit_tree_with_message(
+            Some(&base),
+            "target",
+            &[("file", "a"), ("other", "x")],
+        );
+        let first =
+            test_repository.commit_tree_with_message(Some(&base), "first", &[("file", "b")]);
+        let second =
+            test_repository.commit_tree_with_message(Some(&first), "second", &[("file", "c")]);
+
+        let own = branch::own_commits(repo, second.id(), target.id()).unwrap();
+        assert_eq!(
+            own.iter()
+                .map(|commit| (commit.id, commit.title.to_string()))
+                .collect::<Vec<_>>(),
+            [
+                (second.id(), "second".to_owned()),
+                (first.id(), "first".to_owned())
+            ],
+            "newest first, without the commits of the target or the merge base"
+        );
+
+        assert!(
+            branch::own_commits(repo, base.id(), target.id())
+                .unwrap()
+                .is_empty(),
+            "a branch at the merge base has nothing of its own"
+        );
+    }
+
+    #[test]
+    fn unrelated_history() {
+        let test_repository = TestingRepository::open();
+        let repo = &test_repository.repository;
+        let target = test_repository.commit_tree_with_message(None, "target", &[("file", "a")]);
+        let root = test_repository.commit_tree_with_message(None, "root", &[("file", "b")]);
+        let tip = test_repository.commit_tree_with_message(Some(&root), "tip", &[("file", "c")]);
+
+        let own = branch::own_commits(repo, tip.id(), target.id()).unwrap();
+        assert_eq!(
+            own.iter().map(|commit| commit.id).collect::<Vec<_>>(),
+            [tip.id(), root.id()],
+            "without a merge base, all commits are the branch's own"
+        );
+    }
+}
+
+mod containing {
+    use gitbutler_repo::branch;
+    use gitbutler_testsupport::testing_repository::TestingRepository;
+
+    #[test]
+    fn branches_forked_after_the_commit() {
+        let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+        let repo = &test_repository.repository;
+        let initial = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.branch("before", &initial, false).unwrap();
+        let commit = test_repository.commit_tree(Some(&initial), &[("file", "b")]);
+        let later = test_repository.commit_tree(Some(&commit), &[("file", "c")]);
+        repo.reference("refs/heads/master", later.id(), true, "")
+            .unwrap();
+        repo.branch("forked-at", &commit, false).unwrap();
+        repo.branch("forked-later", &later, false).unwrap();
+        repo.reference("refs/remotes/origin/master", later.id(), false, "")
+            .unwrap();
+
+        assert_eq!(
+            branch::containing(repo, commit.id(), false).unwrap(),
+            ["forked-at", "forked-later", "master"],
+            "`before` was forked before the commit"
+        );
+        assert_eq!(
+            branch::containing(repo, commit.id(), true).unwrap(),
+            ["forked-at", "forked-later", "master", "origin/master"]
+        );
+        assert_eq!(
+            branch::containing(repo, later.id(), false).unwrap(),
+            ["forked-later", "master"]
+        );
+    }
+}
+
+mod rewrite_identity {
+    use gitbutler_repo::branch::{self, RewriteIdentityOptions};
+    use gitbutler_repo::commit_message::Identity;
+    use gitbutler_testsupport::testing_repository::TestingRepository;
+
+    fn identity(name: &str, email: &str) -> Identity {
+        Identity {
+            name: name.into(),
+            email: email.into(),
+        }
+    }
+
+    /// Commit on top of `HEAD` as `email`, returning the new commit.
+    fn commit_as(repo: &git2::Repository, email: &str, message: &str) -> git2::Oid {
+        let signature =
+            git2::Signature::new("Jane", email, &git2::Time::new(1_700_000_000, 60)).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &head.tree().unwrap(),
+            &[&head],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn two_of_three_commits() {
+        let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+        let repo = &test_repository.repository;
+        commit_as(repo, "wrong@example.com", "first");
+        commit_as(repo, "right@example.com", "second");
+        let tip = commit_as(repo, "wrong@example.com", "third");
+
+        let new_tip = branch::rewrite_identity(
+            repo,
+            "master",
+            &identity("Jane", "wrong@example.com"),
+            &identity("Jane", "right@example.com"),
+            RewriteIdentityOptions::default(),
+        )
+        .unwrap();
+        assert_ne!(new_tip, tip);
+        assert_eq!(repo.head().unwrap().target(), Some(new_tip));
+
+        let mut commit = repo.find_commit(new_tip).unwrap();
+        for message in ["third", "second", "first"] {
+            assert_eq!(commit.message(), Some(message));
+            assert_eq!(commit.author().email(), Some("right@example.com"));
+            assert_eq!(commit.committer().email(), Some("right@example.com"));
+            assert_eq!(
+                commit.author().when().seconds(),
+                1_700_000_000,
+                "dates are kept"
+            );
+            commit = commit.parent(0).unwrap();
+        }
+        assert_ne!(
+            commit.author().email(),
+            Some("right@example.com"),
+            "other identities are left alone"
+        );
+    }
+
+    #[test]
+    fn published_commits_are_kept() {
+        let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+        let repo = &test_repository.repository;
+        let published = commit_as(repo, "wrong@example.com", "published");
+        commit_as(repo, "wrong@example.com", "local");
+        repo.remote("origin", "https://example.com/repo.git")
+            .unwrap();
+        repo.reference("refs/remotes/origin/master", published, false, "")
+            .unwrap();
+        repo.find_branch("master", git2::BranchType::Local)
+            .unwrap()
+            .set_upstream(Some("origin/master"))
+            .unwrap();
+        let (from, to) = (
+            identity("Jane", "wrong@example.com"),
+            identity("Jane", "right@example.com"),
+        );
+
+        let new_tip = branch::rewrite_identity(
+            repo,
+            "master",
+            &from,
+            &to,
+            RewriteIdentityOptions::default(),
+        )
+        .unwrap();
+        let new_tip = repo.find_commit(new_tip).unwrap();
+        assert_eq!(new_tip.author().email(), Some("right@example.com"));
+        assert_eq!(
+            new_tip.parent_id(0).unwrap(),
+            published,
+            "the published commit is unchanged"
+        );
+
+        let forced_tip = branch::rewrite_identity(
+            repo,
+            "master",
+            &from,
+            &to,
+            RewriteIdentityOptions { force: true },
+        )
+        .unwrap();
+        let forced_tip = repo.find_commit(forced_tip).unwrap();
+        assert_ne!(forced_tip.parent_id(0).unwrap(), published);
+        assert_eq!(
+            forced_tip.parent(0).unwrap().author().email(),
+            Some("right@example.com")
+        );
+    }
+}
+
+mod upstream {
+    use gitbutler_error::error::{AnyhowContextExt, Code};
+    use gitbutler_repo::branch::{self, UpstreamRef};
+    use gitbutler_testsupport::testing_repository::TestingRepository;
+
+    #[test]
+    fn configured_upstream() {
+        let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+        let repo = &test_repository.repository;
+        let mut config = repo.config().unwrap();
+        config.set_str("branch.master.remote", "origin").unwrap();
+        config
+            .set_str("branch.master.merge", "refs/heads/main")
+            .unwrap();
+
+        assert_eq!(
+            branch::upstream(repo, "master").unwrap(),
+            Some(UpstreamRef {
+                remote: "origin".into(),
+                remote_branch: "main".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn no_upstream() {
+        let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+        let repo = &test_repository.repository;
+        assert_eq!(branch::upstream(repo, "master").unwrap(), None);
+
+        let err = branch::upstream(repo, "missing").unwrap_err();
+        assert_eq!(
+            err.custom_context().map(|ctx| ctx.code),
+            Some(Code::Validation)
+        );
+    }
+
+    #[test]
+    fn set_upstream() {
+        let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+        let repo = &test_repository.repository;
+        repo.remote("origin", "https://example.com/repo.git")
+            .unwrap();
+
+        branch::set_upstream(repo, "master", "origin", "feature").unwrap();
+
+        assert_eq!(
+            branch::upstream(repo, "master").unwrap(),
+            Some(UpstreamRef {
+                remote: "origin".into(),
+                remote_branch: "feature".into(),
+            })
+        );
+        let config = repo.config().unwrap().snapshot().unwrap();
+        assert_eq!(
+            config.get_str("branch.master.merge").unwrap(),
+            "refs/heads/feature"
+        );
+
+        let err = branch::set_upstream(repo, "master", "missing", "feature").unwrap_err();
+        assert_eq!(
+            err.custom_context().map(|ctx| ctx.code),
+            Some(Code::Validation)
+        );
+    }
+}
+
+mod add_signoff {
+    use gitbutler_repo::branch;
+    use gitbutler_repo::commit_message::Identity;
+    use gitbutler_testsupport::testing_repository::TestingRepository;
+
+    /// Commit on top of `HEAD` with `message`, returning the new commit.
+    fn commit(repo: &git2::Repository, message: &str) -> git2::Oid {
+        let signature = git2::Signature::now("Jane", "jane@example.com").unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &head.tree().unwrap(),
+            &[&head],
+        )
+        .unwrap()
+    }
+
+    fn messages(repo: &git2::Repository, tip: git2::Oid, count: usize) -> Vec<String> {
+        let mut commit = repo.find_commit(tip).unwrap();
+        let mut messages = Vec::new();
+        for _ in 0..count {
+            messages.push(commit.message().unwrap().to_owned());
+            commit = commit.parent(0).unwrap();
+        }
+        messages
+    }
+
+    #[test]
+    fn unpushed_commits_are_signed_off_once() {
+        let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+        let repo = &test_repository.repository;
+        let published = commit(repo, "published");
+        commit(repo, "title\n\nbody\n");
+        commit(
+            repo,
+            "with trailer\n\nCo-authored-by: Joe <joe@example.com>\n",
+        );
+        commit(
+            repo,
+            "already signed off\n\nSigned-off-by: Jane <jane@example.com>\n",
+        );
+        repo.remote("origin", "https://example.com/repo.git")
+            .unwrap();
+        repo.reference("refs/remotes/origin/master", published, false, "")
+            .unwrap();
+        repo.find_branch("master", git2::BranchType::Local)
+            .unwrap()
+            .set_upstream(Some("origin/master"))
+            .unwrap();
+
+        let jane = Identity {
+            name: "Jane".into(),
+            email: "jane@example.com".into(),
+        };
+        let new_tip = branch::add_signoff(repo, "master", &jane).unwrap();
+        assert_eq!(repo.head().unwrap().target(), Some(new_tip));
+        let expected = [
+            "already signed off\n\nSigned-off-by: Jane <jane@example.com>\n",
+            "with trailer\n\nCo-authored-by: Joe <joe@example.com>\nSigned-off-by: Jane <jane@example.com>\n",
+            "title\n\nbody\n\nSigned-off-by: Jane <jane@example.com>\n",
+            "published",
+        ];
+        assert_eq!(
+            messages(repo, new_tip, 4),
+            expected,
+            "trailers are added to existing ones, and published commits are left alone"
+        );
+        let mut base = repo.find_commit(new_tip).unwrap();
+        for _ in 0..3 {
+            base = base.parent(0).unwrap();
+        }
+        assert_eq!(base.id(), published);
+
+        assert_eq!(
+            branch::add_signoff(repo, "master", &jane).unwrap(),
+            new_tip,
+            "nothing changes if all commits are signed off already"
+        );
+        assert_eq!(messages(repo, new_tip, 4), expected);
+    }
+}
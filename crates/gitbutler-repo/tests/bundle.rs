@@ -0,0 +1,94 @@
+use gitbutler_error::error::{AnyhowContextExt, Code};
+use gitbutler_repo::bundle::{apply_bundle, create_bundle, AppliedBundle};
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn round_trip_branch_into_fresh_repository() {
+    let source = TestingRepository::open_with_initial_commit(&[("file.txt", "initial")]);
+    let head = source.repository.head().unwrap().peel_to_commit().unwrap();
+    let feature = source.commit_tree(Some(&head), &[("file.txt", "feature")]);
+    source
+        .repository
+        .branch("feature", &feature, false)
+        .unwrap();
+
+    let bundle_dir = tempfile::tempdir().unwrap();
+    let bundle_path = bundle_dir.path().join("feature.bundle");
+    create_bundle(&source.repository, &["feature".into()], &bundle_path).unwrap();
+    assert!(bundle_path.is_file());
+
+    let destination = TestingRepository::open();
+    let applied = apply_bundle(&destination.repository, &bundle_path).unwrap();
+    assert_eq!(applied.updated, ["refs/heads/feature"]);
+    assert!(applied.rejected.is_empty());
+    assert_eq!(
+        destination
+            .repository
+            .refname_to_id("refs/heads/feature")
+            .unwrap(),
+        feature.id()
+    );
+
+    let applied = apply_bundle(&destination.repository, &bundle_path).unwrap();
+    assert_eq!(
+        applied,
+        AppliedBundle::default(),
+        "nothing changes when applying it again"
+    );
+}
+
+#[test]
+fn local_branch_ahead_of_bundle_is_kept() {
+    let source = TestingRepository::open_with_initial_commit(&[("file.txt", "initial")]);
+    let head = source.repository.head().unwrap().peel_to_commit().unwrap();
+    let feature = source.commit_tree(Some(&head), &[("file.txt", "feature")]);
+    source
+        .repository
+        .branch("feature", &feature, false)
+        .unwrap();
+
+    let bundle_dir = tempfile::tempdir().unwrap();
+    let bundle_path = bundle_dir.path().join("feature.bundle");
+    create_bundle(&source.repository, &["feature".into()], &bundle_path).unwrap();
+
+    let destination = TestingRepository::open();
+    apply_bundle(&destination.repository, &bundle_path).unwrap();
+    let feature = destination.repository.find_commit(feature.id()).unwrap();
+    let local = destination.commit_tree(Some(&feature), &[("file.txt", "local work")]);
+    destination
+        .repository
+        .branch("feature", &local, true)
+        .unwrap();
+
+    let applied = apply_bundle(&destination.repository, &bundle_path).unwrap();
+    assert_eq!(
+        applied,
+        AppliedBundle {
+            updated: vec![],
+            rejected: vec!["refs/heads/feature".into()],
+        }
+    );
+    assert_eq!(
+        destination
+            .repository
+            .refname_to_id("refs/heads/feature")
+            .unwrap(),
+        local.id(),
+        "the local commit isn't lost"
+    );
+}
+
+#[test]
+fn missing_refs_are_rejected() {
+    let source = TestingRepository::open_with_initial_commit(&[("file.txt", "initial")]);
+
+    let bundle_dir = tempfile::tempdir().unwrap();
+    let bundle_path = bundle_dir.path().join("missing.bundle");
+    let err =
+        create_bundle(&source.repository, &["does-not-exist".into()], &bundle_path).unwrap_err();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::Validation)
+    );
+    assert!(!bundle_path.exists(), "nothing is written");
+}
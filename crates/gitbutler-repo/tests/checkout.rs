@@ -0,0 +1,74 @@
+use std::path::PathBuf;
+
+use gitbutler_error::error::{AnyhowContextExt, Code};
+use gitbutler_repo::{CheckoutWouldOverwrite, RepositoryExt as _};
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn local_changes_that_would_be_overwritten_are_reported() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file.txt", "initial")]);
+    let repo = &test_repository.repository;
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    let other = test_repository.commit_tree(Some(&head), &[("file.txt", "other")]);
+
+    // Get back to HEAD, then make a local change to the file the checkout wants to write.
+    repo.checkout_tree_builder(&head.tree().unwrap())
+        .force()
+        .checkout()
+        .unwrap();
+    std::fs::write(test_repository.tempdir.path().join("file.txt"), "local").unwrap();
+
+    let err = repo
+        .checkout_tree_builder(&other.tree().unwrap())
+        .checkout()
+        .unwrap_err();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::ProjectCheckoutWouldOverwrite)
+    );
+    assert_eq!(
+        err.downcast_ref::<CheckoutWouldOverwrite>()
+            .map(|err| err.paths.clone()),
+        Some(vec![PathBuf::from("file.txt")])
+    );
+    assert_eq!(
+        std::fs::read_to_string(test_repository.tempdir.path().join("file.txt")).unwrap(),
+        "local",
+        "the local change is untouched"
+    );
+
+    repo.checkout_tree_builder(&other.tree().unwrap())
+        .force()
+        .checkout()
+        .expect("forcing it works");
+}
+
+#[test]
+fn progress_is_reported_until_completion() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file.txt", "initial")]);
+    let repo = &test_repository.repository;
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    let other = test_repository.commit_tree(
+        Some(&head),
+        &[("a.txt", "a"), ("b.txt", "b"), ("dir/c.txt", "c")],
+    );
+
+    let mut events = Vec::new();
+    repo.checkout_tree_builder(&other.tree().unwrap())
+        .force()
+        .progress(|progress| {
+            events.push((
+                progress.path.map(ToOwned::to_owned),
+                progress.completed,
+                progress.total,
+            ))
+        })
+        .checkout()
+        .unwrap();
+
+    let (_, completed, total) = events.last().cloned().expect("progress was reported");
+    assert!(total >= 3, "each new file is a step");
+    assert_eq!(completed, total, "the checkout is reported as done");
+    let paths: Vec<_> = events.into_iter().filter_map(|(path, ..)| path).collect();
+    assert!(paths.contains(&PathBuf::from("dir/c.txt")), "{paths:?}");
+}
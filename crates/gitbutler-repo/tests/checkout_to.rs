@@ -0,0 +1,54 @@
+use gitbutler_repo::RepositoryExt as _;
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn tree_is_written_into_other_directory() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    let repo = &test_repository.repository;
+    let head = repo.head().unwrap().target().unwrap();
+    let index_before = std::fs::read(repo.path().join("index")).unwrap();
+    let nested_tree = {
+        let mut builder = repo.treebuilder(None).unwrap();
+        let blob = repo.blob(b"nested").unwrap();
+        builder
+            .insert("nested", blob, git2::FileMode::Blob.into())
+            .unwrap();
+        builder.write().unwrap()
+    };
+    let preview_tree = {
+        let mut builder = repo.treebuilder(None).unwrap();
+        let blob = repo.blob(b"preview").unwrap();
+        builder
+            .insert("file", blob, git2::FileMode::Blob.into())
+            .unwrap();
+        builder
+            .insert("dir", nested_tree, git2::FileMode::Tree.into())
+            .unwrap();
+        builder.write().unwrap()
+    };
+
+    let scratch = tempfile::tempdir().unwrap();
+    let dir = scratch.path().join("preview");
+    repo.checkout_to(preview_tree, &dir).unwrap();
+
+    assert_eq!(
+        std::fs::read_to_string(dir.join("file")).unwrap(),
+        "preview"
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.join("dir/nested")).unwrap(),
+        "nested"
+    );
+    assert_eq!(repo.head().unwrap().target(), Some(head));
+    assert_eq!(
+        std::fs::read(repo.path().join("index")).unwrap(),
+        index_before,
+        "the index is unchanged"
+    );
+    assert_eq!(
+        std::fs::read_to_string(test_repository.tempdir.path().join("file")).unwrap(),
+        "a",
+        "the worktree is unchanged"
+    );
+    assert!(!test_repository.tempdir.path().join("dir").exists());
+}
@@ -0,0 +1,57 @@
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use gitbutler_repo::RepositoryExt as _;
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+fn write_lock(path: &Path, age: Duration) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    let file = std::fs::File::create(path).unwrap();
+    file.set_modified(SystemTime::now() - age).unwrap();
+}
+
+#[test]
+fn stale_locks_are_removed_and_fresh_ones_kept() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    let repo = &test_repository.repository;
+    let hour = Duration::from_secs(60 * 60);
+    let stale_index = repo.path().join("index.lock");
+    let stale_ref = repo.path().join("refs/heads/feature/main.lock");
+    let fresh = repo.path().join("shallow.lock");
+    write_lock(&stale_index, 2 * hour);
+    write_lock(&stale_ref, 2 * hour);
+    write_lock(&fresh, Duration::ZERO);
+
+    let removed = repo.clear_stale_locks(hour).unwrap();
+    assert_eq!(removed, [stale_index.clone(), stale_ref.clone()]);
+    assert!(!stale_index.exists());
+    assert!(!stale_ref.exists());
+    assert!(fresh.exists(), "the lock may belong to a running process");
+
+    assert!(
+        repo.clear_stale_locks(hour).unwrap().is_empty(),
+        "nothing is left to remove"
+    );
+    repo.index()
+        .unwrap()
+        .write()
+        .expect("the index can be written again");
+}
+
+#[test]
+fn other_files_are_ignored() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    let repo = &test_repository.repository;
+    let old = Duration::from_secs(24 * 60 * 60);
+    let not_a_lock = repo.path().join("lock");
+    let nested = repo.path().join("gitbutler/project.lock");
+    write_lock(&not_a_lock, old);
+    write_lock(&nested, old);
+
+    assert!(repo.clear_stale_locks(Duration::ZERO).unwrap().is_empty());
+    assert!(not_a_lock.exists());
+    assert!(
+        nested.exists(),
+        "only lock files of Git itself are considered"
+    );
+}
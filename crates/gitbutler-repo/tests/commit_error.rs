@@ -0,0 +1,108 @@
+use gitbutler_error::error::{AnyhowContextExt, Code};
+use gitbutler_repo::{
+    hooks::{ErrorData, HookResult},
+    CommitError, RepositoryExt as _,
+};
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+fn code(err: CommitError) -> Option<Code> {
+    anyhow::Error::from(err)
+        .custom_context()
+        .map(|ctx| ctx.code)
+}
+
+#[test]
+fn each_failure_has_its_code() {
+    assert_eq!(code(CommitError::EmptyTree), Some(Code::Validation));
+    assert_eq!(
+        code(CommitError::MissingIdentity),
+        Some(Code::AuthorMissing)
+    );
+    assert_eq!(
+        code(CommitError::HookRejected("lint failed".into())),
+        Some(Code::Validation)
+    );
+    assert_eq!(
+        code(CommitError::Conflict),
+        Some(Code::CommitMergeConflictFailure)
+    );
+    assert_eq!(
+        code(CommitError::Other(anyhow::anyhow!("disk full"))),
+        None,
+        "unknown errors are passed on as is"
+    );
+}
+
+#[test]
+fn classified_by_existing_code() {
+    let err = anyhow::anyhow!("no author").context(Code::AuthorMissing);
+    assert!(matches!(
+        CommitError::from(err),
+        CommitError::MissingIdentity
+    ));
+    let err = anyhow::anyhow!("disk full");
+    assert!(matches!(CommitError::from(err), CommitError::Other(_)));
+}
+
+#[test]
+fn failed_hook_rejects_the_commit() {
+    assert!(HookResult::Success.into_commit_result().is_ok());
+    assert!(HookResult::NotConfigured.into_commit_result().is_ok());
+    let err = HookResult::Failure(ErrorData {
+        error: "lint failed".into(),
+    })
+    .into_commit_result()
+    .unwrap_err();
+    assert!(matches!(err, CommitError::HookRejected(output) if output == "lint failed"));
+}
+
+#[test]
+fn committing_unchanged_paths() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    let err = test_repository
+        .repository
+        .commit_paths(&["file".into()], "nothing")
+        .unwrap_err();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::Validation)
+    );
+    assert_eq!(
+        err.to_string(),
+        "There are no changes to commit in the selected files"
+    );
+}
+
+#[test]
+fn committing_conflicted_paths() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    let repo = &test_repository.repository;
+    let blob = repo.blob(b"b").unwrap();
+    let entry = |stage: u16| git2::IndexEntry {
+        ctime: git2::IndexTime::new(0, 0),
+        mtime: git2::IndexTime::new(0, 0),
+        dev: 0,
+        ino: 0,
+        mode: git2::FileMode::Blob.into(),
+        uid: 0,
+        gid: 0,
+        file_size: 1,
+        id: blob,
+        flags: stage << 12,
+        flags_extended: 0,
+        path: b"file".to_vec(),
+    };
+    let mut index = repo.index().unwrap();
+    index
+        .conflict_add(Some(&entry(1)), Some(&entry(2)), Some(&entry(3)))
+        .unwrap();
+    index.write().unwrap();
+
+    let err = repo
+        .commit_paths(&["file".into()], "conflicted")
+        .unwrap_err();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::CommitMergeConflictFailure)
+    );
+}
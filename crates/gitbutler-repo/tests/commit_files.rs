@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+
+use gitbutler_diff::ChangeType;
+use gitbutler_repo::RepositoryExt as _;
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn normal_commit() {
+    let test_repository = TestingRepository::open();
+    let base = test_repository.commit_tree(
+        None,
+        &[("modified", "a"), ("deleted", "a"), ("untouched", "a")],
+    );
+    let commit = test_repository.commit_tree(
+        Some(&base),
+        &[("modified", "b"), ("added", "b"), ("untouched", "a")],
+    );
+
+    let mut files = test_repository
+        .repository
+        .commit_files(commit.id(), false)
+        .unwrap();
+    files.sort_by(|a, b| a.1.cmp(&b.1));
+    assert_eq!(
+        files,
+        [
+            (ChangeType::Added, PathBuf::from("added")),
+            (ChangeType::Deleted, PathBuf::from("deleted")),
+            (ChangeType::Modified, PathBuf::from("modified")),
+        ]
+    );
+}
+
+#[test]
+fn root_commit_is_compared_to_empty_tree() {
+    let test_repository = TestingRepository::open();
+    let root = test_repository.commit_tree(None, &[("file", "a")]);
+
+    let files = test_repository
+        .repository
+        .commit_files(root.id(), false)
+        .unwrap();
+    assert_eq!(files, [(ChangeType::Added, PathBuf::from("file"))]);
+}
+
+#[test]
+fn merge_commit() {
+    let test_repository = TestingRepository::open();
+    let repo = &test_repository.repository;
+    let base = test_repository.commit_tree(None, &[("base", "a")]);
+    let ours = test_repository.commit_tree(Some(&base), &[("base", "a"), ("ours", "a")]);
+    let theirs = test_repository.commit_tree(Some(&base), &[("base", "a"), ("theirs", "a")]);
+    let merged = test_repository.commit_tree(
+        Some(&ours),
+        &[("base", "a"), ("ours", "a"), ("theirs", "a")],
+    );
+    let signature = git2::Signature::now("Caleb", "caleb@gitbutler.com").unwrap();
+    let merge = repo
+        .commit(
+            None,
+            &signature,
+            &signature,
+            "merge",
+            &merged.tree().unwrap(),
+            &[&ours, &theirs],
+        )
+        .unwrap();
+
+    let files = repo.commit_files(merge, false).unwrap();
+    assert_eq!(
+        files,
+        [(ChangeType::Added, PathBuf::from("theirs"))],
+        "by default, only the first parent is considered"
+    );
+
+    let mut files = repo.commit_files(merge, true).unwrap();
+    files.sort_by(|a, b| a.1.cmp(&b.1));
+    assert_eq!(
+        files,
+        [
+            (ChangeType::Added, PathBuf::from("ours")),
+            (ChangeType::Added, PathBuf::from("theirs")),
+        ]
+    );
+}
@@ -0,0 +1,59 @@
+use gitbutler_repo::RepositoryExt as _;
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn line_counts_match_the_diff() {
+    let test_repository = TestingRepository::open();
+    let base = test_repository.commit_tree(
+        None,
+        &[
+            ("modified", "1\n2\n3\n"),
+            ("deleted", "gone\n"),
+            ("same", "x\n"),
+        ],
+    );
+    let commit = test_repository.commit_tree(
+        Some(&base),
+        &[
+            ("modified", "1\ntwo\n3\n4\n"),
+            ("added", "new\n"),
+            ("same", "x\n"),
+        ],
+    );
+    let repo = &test_repository.repository;
+
+    let insights = repo.commit_insights(commit.id()).unwrap();
+    let diff =
+        gitbutler_diff::trees(repo, &base.tree().unwrap(), &commit.tree().unwrap(), true).unwrap();
+    let count = |prefix: u8| {
+        diff.values()
+            .flat_map(|file| &file.hunks)
+            .flat_map(|hunk| hunk.diff_lines.split(|b| *b == b'\n'))
+            .filter(|line| line.first() == Some(&prefix))
+            .count()
+    };
+    assert_eq!(insights.files_changed, diff.len());
+    assert_eq!(insights.files_changed, 3);
+    assert_eq!(insights.insertions, count(b'+'));
+    assert_eq!(insights.deletions, count(b'-'));
+    assert_eq!((insights.insertions, insights.deletions), (3, 2));
+    assert_eq!(
+        (insights.added_bytes, insights.removed_bytes),
+        (4 + 4, 5),
+        "'modified' grew by 4 bytes and 'added' has 4, while 'deleted' had 5"
+    );
+}
+
+#[test]
+fn root_commit() {
+    let test_repository = TestingRepository::open();
+    let root = test_repository.commit_tree(None, &[("file", "a\nb\n")]);
+
+    let insights = test_repository
+        .repository
+        .commit_insights(root.id())
+        .unwrap();
+    assert_eq!(insights.files_changed, 1);
+    assert_eq!((insights.insertions, insights.deletions), (2, 0));
+    assert_eq!((insights.added_bytes, insights.removed_bytes), (4, 0));
+}
@@ -0,0 +1,65 @@
+use std::path::PathBuf;
+
+use gitbutler_diff::ChangeType;
+use gitbutler_repo::RepositoryExt as _;
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn only_selected_paths_are_committed() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[
+        ("modified", "a"),
+        ("deleted", "a"),
+        ("modified-but-not-selected", "a"),
+    ]);
+    let repo = &test_repository.repository;
+    let workdir = test_repository.tempdir.path();
+    std::fs::write(workdir.join("modified"), "b").unwrap();
+    std::fs::write(workdir.join("added"), "b").unwrap();
+    std::fs::remove_file(workdir.join("deleted")).unwrap();
+    std::fs::write(workdir.join("modified-but-not-selected"), "b").unwrap();
+    let head_before = repo.head().unwrap().peel_to_commit().unwrap().id();
+
+    let commit_id = repo
+        .commit_paths(
+            &["modified".into(), "added".into(), "deleted".into()],
+            "partial commit",
+        )
+        .unwrap();
+
+    assert_eq!(
+        repo.head().unwrap().target(),
+        Some(commit_id),
+        "the current branch is advanced"
+    );
+    let commit = repo.find_commit(commit_id).unwrap();
+    assert_eq!(commit.parent_id(0).unwrap(), head_before);
+    assert_eq!(commit.message(), Some("partial commit"));
+
+    let mut files = repo.commit_files(commit_id, false).unwrap();
+    files.sort_by(|a, b| a.1.cmp(&b.1));
+    assert_eq!(
+        files,
+        [
+            (ChangeType::Added, PathBuf::from("added")),
+            (ChangeType::Deleted, PathBuf::from("deleted")),
+            (ChangeType::Modified, PathBuf::from("modified")),
+        ],
+        "the path that wasn't selected isn't part of the commit"
+    );
+    assert_eq!(
+        std::fs::read_to_string(workdir.join("modified-but-not-selected")).unwrap(),
+        "b",
+        "the worktree remains untouched"
+    );
+
+    let statuses = repo.statuses(None).unwrap();
+    let changed: Vec<_> = statuses
+        .iter()
+        .filter_map(|e| e.path().map(ToOwned::to_owned))
+        .collect();
+    assert_eq!(
+        changed,
+        ["modified-but-not-selected"],
+        "the index was updated for the committed paths"
+    );
+}
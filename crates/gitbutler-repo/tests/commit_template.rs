@@ -0,0 +1,45 @@
+use gitbutler_repo::RepositoryExt as _;
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn configured_template_is_read() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    let repo = &test_repository.repository;
+    assert_eq!(repo.commit_template().unwrap(), None);
+
+    let template_dir = tempfile::tempdir().unwrap();
+    let template = template_dir.path().join("template.txt");
+    std::fs::write(&template, "feat: \n\nIssue: #\n").unwrap();
+    repo.config()
+        .unwrap()
+        .set_str("commit.template", template.to_str().unwrap())
+        .unwrap();
+    assert_eq!(
+        repo.commit_template().unwrap().as_deref(),
+        Some("feat: \n\nIssue: #\n")
+    );
+
+    std::fs::remove_file(&template).unwrap();
+    assert_eq!(
+        repo.commit_template().unwrap(),
+        None,
+        "a missing template is like no template"
+    );
+}
+
+#[test]
+fn relative_path_is_relative_to_the_worktree() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    let repo = &test_repository.repository;
+    std::fs::write(
+        test_repository.tempdir.path().join(".gitmessage"),
+        "template",
+    )
+    .unwrap();
+    repo.config()
+        .unwrap()
+        .set_str("commit.template", ".gitmessage")
+        .unwrap();
+
+    assert_eq!(repo.commit_template().unwrap().as_deref(), Some("template"));
+}
@@ -0,0 +1,62 @@
+use gitbutler_repo::Config;
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+/// Append `config` to the local configuration of `test_repository` and reopen it, so it's read again.
+fn reopen_with_config(test_repository: &TestingRepository, config: &str) -> git2::Repository {
+    let repo = &test_repository.repository;
+    let local_config = repo.path().join("config");
+    let mut content = std::fs::read_to_string(&local_config).unwrap();
+    content.push_str(config);
+    std::fs::write(local_config, content).unwrap();
+    git2::Repository::open(repo.path()).unwrap()
+}
+
+#[test]
+fn value_from_included_file() {
+    let test_repository = TestingRepository::open();
+    let included = test_repository.tempdir.path().join("included.config");
+    std::fs::write(&included, "[gitbutler]\n\tincluded = yes\n").unwrap();
+    let repo = reopen_with_config(
+        &test_repository,
+        &format!("[include]\n\tpath = {}\n", included.display()),
+    );
+
+    let config = Config::from(&repo);
+    assert_eq!(
+        config.get_string("gitbutler.included").unwrap().as_deref(),
+        Some("yes")
+    );
+    assert_eq!(config.get_string("gitbutler.missing").unwrap(), None);
+}
+
+#[test]
+fn value_from_conditionally_included_file() {
+    let test_repository = TestingRepository::open();
+    let included = test_repository.tempdir.path().join("included.config");
+    std::fs::write(&included, "[gitbutler]\n\tincluded = yes\n").unwrap();
+    let other = test_repository.tempdir.path().join("other.config");
+    std::fs::write(&other, "[gitbutler]\n\tother = yes\n").unwrap();
+    // Like `gitdir:~/work/`, which applies to all repositories in that directory.
+    let parent_dir = std::fs::canonicalize(test_repository.tempdir.path()).unwrap();
+    let repo = reopen_with_config(
+        &test_repository,
+        &format!(
+            "[includeIf \"gitdir:{}/\"]\n\tpath = {}\n[includeIf \"gitdir:/somewhere/else/\"]\n\tpath = {}\n",
+            parent_dir.display(),
+            included.display(),
+            other.display()
+        ),
+    );
+
+    let config = Config::from(&repo);
+    assert_eq!(
+        config.get_string("gitbutler.included").unwrap().as_deref(),
+        Some("yes"),
+        "the condition matches this repository"
+    );
+    assert_eq!(
+        config.get_string("gitbutler.other").unwrap(),
+        None,
+        "the condition matches another directory"
+    );
+}
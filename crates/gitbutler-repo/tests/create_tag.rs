@@ -0,0 +1,45 @@
+use gitbutler_error::error::{AnyhowContextExt, Code};
+use gitbutler_repo::RepositoryExt as _;
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn lightweight_and_annotated() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    let repo = &test_repository.repository;
+    let head_id = repo.head().unwrap().target().unwrap();
+
+    let id = repo.create_tag("light", head_id, None, false).unwrap();
+    assert_eq!(id, head_id, "lightweight tags point to the commit directly");
+
+    let id = repo
+        .create_tag("annotated", head_id, Some("release"), false)
+        .unwrap();
+    let tag = repo.find_tag(id).unwrap();
+    assert_eq!(tag.target_id(), head_id);
+    assert_eq!(tag.message(), Some("release"));
+}
+
+#[test]
+fn existing_tag_is_refused_unless_forced() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    let repo = &test_repository.repository;
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    let other = test_repository.commit_tree(Some(&head), &[("file", "b")]);
+    repo.create_tag("v1", head.id(), None, false).unwrap();
+
+    let err = repo.create_tag("v1", other.id(), None, false).unwrap_err();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::ProjectRefExists)
+    );
+    let tag_target = || {
+        repo.find_reference("refs/tags/v1")
+            .unwrap()
+            .target()
+            .unwrap()
+    };
+    assert_eq!(tag_target(), head.id());
+
+    repo.create_tag("v1", other.id(), None, true).unwrap();
+    assert_eq!(tag_target(), other.id());
+}
@@ -159,3 +159,81 @@ mod github {
         }
     }
 }
+
+mod profiles {
+    use gitbutler_repo::credentials::{get_profiles, CredentialProfile, HttpsCredential};
+
+    use super::*;
+
+    #[test]
+    fn remotes_resolve_to_their_profile() {
+        let (repo, _tmp) = test_repository();
+        repo.remote("work", "git@work.example.com:team/app.git")
+            .unwrap();
+        repo.remote("personal", "https://git.example.org/me/dotfiles.git")
+            .unwrap();
+        repo.remote("other", "https://git.example.org/someone/else.git")
+            .unwrap();
+        let project = projects::Project {
+            path: repo.workdir().unwrap().to_path_buf(),
+            preferred_key: projects::AuthKey::Local {
+                private_key_path: PathBuf::from("/tmp/id_rsa"),
+            },
+            ..Default::default()
+        };
+        let ctx = CommandContext::open(&project, AppSettings::default()).unwrap();
+
+        let work_key = Credential::Ssh(SshCredential::Keyfile {
+            key_path: PathBuf::from("/tmp/work_key"),
+            passphrase: None,
+        });
+        let personal_token = Credential::Https(HttpsCredential::GitHubToken("personal".into()));
+        get_profiles().register(CredentialProfile {
+            pattern: "work.example.com".into(),
+            credential: work_key.clone(),
+        });
+        get_profiles().register(CredentialProfile {
+            pattern: "git.example.org/me/*".into(),
+            credential: personal_token.clone(),
+        });
+        let flow = |remote_name: &str| {
+            help(&ctx, remote_name)
+                .unwrap()
+                .into_iter()
+                .map(|(remote, credentials)| {
+                    (remote.url().as_ref().unwrap().to_string(), credentials)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let work = flow("work");
+        let personal = flow("personal");
+        let other = flow("other");
+        get_profiles().remove("work.example.com").unwrap();
+        get_profiles().remove("git.example.org/me/*").unwrap();
+
+        assert_eq!(
+            work,
+            [("git@work.example.com:team/app.git".into(), vec![work_key])]
+        );
+        assert_eq!(
+            personal,
+            [(
+                "https://git.example.org/me/dotfiles.git".into(),
+                vec![personal_token]
+            )],
+            "the remote URL is kept as it fits the credential"
+        );
+        assert_eq!(
+            other,
+            [(
+                "git@git.example.org:someone/else.git".into(),
+                vec![Credential::Ssh(SshCredential::Keyfile {
+                    key_path: PathBuf::from("/tmp/id_rsa"),
+                    passphrase: None,
+                })]
+            )],
+            "without a matching profile, the preferred key is used"
+        );
+    }
+}
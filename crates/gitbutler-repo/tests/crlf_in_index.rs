@@ -0,0 +1,37 @@
+use std::path::PathBuf;
+
+use gitbutler_repo::RepositoryExt as _;
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn files_stored_with_crlf_are_reported() {
+    let test_repository = TestingRepository::open();
+    let repo = &test_repository.repository;
+    assert!(
+        repo.crlf_in_index().unwrap().is_empty(),
+        "an empty index has nothing to report"
+    );
+
+    // This also adds all files to the index.
+    test_repository.commit_tree(
+        None,
+        &[
+            ("crlf.txt", "one\r\ntwo\r\n"),
+            ("dir/mixed.txt", "one\ntwo\r\n"),
+            ("lf.txt", "one\ntwo\n"),
+            ("binary.bin", "\0one\r\ntwo\r\n"),
+            ("marked-binary.dat", "one\r\ntwo\r\n"),
+            ("no-text.dat", "one\r\ntwo\r\n"),
+            (
+                ".gitattributes",
+                "marked-binary.dat binary\nno-text.dat -text\n",
+            ),
+        ],
+    );
+
+    assert_eq!(
+        repo.crlf_in_index().unwrap(),
+        [PathBuf::from("crlf.txt"), PathBuf::from("dir/mixed.txt")],
+        "binary files and those that aren't text according to `.gitattributes` are skipped"
+    );
+}
@@ -0,0 +1,35 @@
+use gitbutler_repo::logging::RepositoryExt as _;
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn commits_reset_away_are_dangling() {
+    let test_repository = TestingRepository::open();
+    let repo = &test_repository.repository;
+    let base = test_repository.commit_tree(None, &[("file", "a")]);
+    let lost = test_repository.commit_tree(Some(&base), &[("file", "b")]);
+    let lost_tip = test_repository.commit_tree(Some(&lost), &[("file", "c")]);
+    repo.reference("refs/heads/master", lost_tip.id(), true, "commit")
+        .unwrap();
+    repo.set_head("refs/heads/master").unwrap();
+    assert!(
+        repo.dangling_commits().unwrap().is_empty(),
+        "everything is reachable from the branch"
+    );
+
+    repo.reset(base.as_object(), git2::ResetType::Soft, None)
+        .unwrap();
+
+    let mut dangling: Vec<_> = repo
+        .dangling_commits()
+        .unwrap()
+        .into_iter()
+        .map(|summary| summary.id)
+        .collect();
+    dangling.sort();
+    let mut expected = vec![lost.id(), lost_tip.id()];
+    expected.sort();
+    assert_eq!(
+        dangling, expected,
+        "the reflog leads to the tip, and its unreachable ancestors are included"
+    );
+}
@@ -0,0 +1,75 @@
+use gitbutler_error::error::{AnyhowContextExt, Code};
+use gitbutler_repo::colors::{parse_color, Color, ColorValue, DiffColors};
+use gitbutler_repo::RepositoryExt as _;
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn configured_colors_are_read() {
+    let test_repository = TestingRepository::open();
+    let repo = &test_repository.repository;
+    let mut config = repo.config().unwrap();
+    config.set_str("color.diff.new", "bold #00FF00").unwrap();
+    config.set_str("color.diff.old", "brightred black").unwrap();
+    config.set_str("color.diff.plain", "245").unwrap();
+
+    let colors = repo.diff_colors().unwrap();
+    assert_eq!(
+        colors.added,
+        Color {
+            foreground: Some(ColorValue::Rgb(0, 0xff, 0)),
+            background: None,
+            attributes: vec!["bold".into()],
+        }
+    );
+    assert_eq!(
+        colors.removed,
+        Color {
+            foreground: Some(ColorValue::Named("brightred".into())),
+            background: Some(ColorValue::Named("black".into())),
+            attributes: vec![],
+        },
+        "the second color is the background"
+    );
+    assert_eq!(
+        colors.context.foreground,
+        Some(ColorValue::Ansi(245)),
+        "the older name of `color.diff.context` is used as well"
+    );
+    assert_eq!(
+        colors.meta,
+        DiffColors::default().meta,
+        "unset colors use Git's defaults"
+    );
+}
+
+#[test]
+fn invalid_colors_are_refused() {
+    let test_repository = TestingRepository::open();
+    let repo = &test_repository.repository;
+    repo.config()
+        .unwrap()
+        .set_str("color.diff.meta", "red green blue")
+        .unwrap();
+
+    let err = repo.diff_colors().unwrap_err();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::Validation)
+    );
+}
+
+#[test]
+fn parse() {
+    assert_eq!(parse_color("").unwrap(), Color::default());
+    assert_eq!(
+        parse_color("nobold no-ul #f80").unwrap(),
+        Color {
+            foreground: Some(ColorValue::Rgb(0xff, 0x88, 0)),
+            background: None,
+            attributes: vec!["nobold".into(), "no-ul".into()],
+        }
+    );
+    assert!(parse_color("purple").is_err());
+    assert!(parse_color("#12345").is_err());
+    assert!(parse_color("256").is_err());
+}
@@ -0,0 +1,81 @@
+use std::path::{Path, PathBuf};
+
+use gitbutler_diff::ChangeType;
+use gitbutler_repo::discard::{discard, discard_hunk, DiscardOptions};
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn modified_and_untracked_files() {
+    let test_repository =
+        TestingRepository::open_with_initial_commit(&[("modified", "a"), ("untouched", "a")]);
+    let workdir = test_repository.tempdir.path();
+    std::fs::write(workdir.join("modified"), "b").unwrap();
+    std::fs::write(workdir.join("untouched"), "b").unwrap();
+    std::fs::write(workdir.join("new"), "b").unwrap();
+
+    discard(
+        &test_repository.repository,
+        &[PathBuf::from("modified"), PathBuf::from("new")],
+        DiscardOptions::default(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        std::fs::read_to_string(workdir.join("modified")).unwrap(),
+        "a"
+    );
+    assert!(!workdir.join("new").exists(), "untracked files are deleted");
+    assert_eq!(
+        std::fs::read_to_string(workdir.join("untouched")).unwrap(),
+        "b",
+        "other paths are left alone"
+    );
+}
+
+#[test]
+fn single_hunk() {
+    let lines = |changed: &[u32]| {
+        (1..=20)
+            .map(|n| match changed.contains(&n) {
+                true => format!("changed {n}\n"),
+                false => format!("{n}\n"),
+            })
+            .collect::<String>()
+    };
+    let original = lines(&[]);
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", &original)]);
+    let workdir = test_repository.tempdir.path();
+    std::fs::write(workdir.join("file"), lines(&[2, 19])).unwrap();
+
+    let repo = &test_repository.repository;
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    let diff = gitbutler_diff::workdir(repo, head.id()).unwrap();
+    let hunks = &diff[Path::new("file")].hunks;
+    assert_eq!(hunks.len(), 2);
+    assert_eq!(hunks[1].change_type, ChangeType::Modified);
+
+    discard_hunk(
+        repo,
+        Path::new("file"),
+        &hunks[1],
+        DiscardOptions::default(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        std::fs::read_to_string(workdir.join("file")).unwrap(),
+        lines(&[2]),
+        "only the second hunk was discarded"
+    );
+    let err = discard_hunk(
+        repo,
+        Path::new("file"),
+        &hunks[1],
+        DiscardOptions::default(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "The hunk to discard isn't among the changes of 'file'"
+    );
+}
@@ -0,0 +1,95 @@
+use gitbutler_error::error::{AnyhowContextExt, Code};
+use gitbutler_repo::format_patch::{format_patch, FormatPatchOptions};
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn two_commit_series() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a\n")]);
+    let base = test_repository
+        .repository
+        .head()
+        .unwrap()
+        .peel_to_commit()
+        .unwrap();
+    let first =
+        test_repository.commit_tree_with_message(Some(&base), "Change to b", &[("file", "b\n")]);
+    let second = test_repository.commit_tree_with_message(
+        Some(&first),
+        "Add another file\n\nWith a description.",
+        &[("file", "b\n"), ("another", "c\n")],
+    );
+
+    let out = tempfile::tempdir().unwrap();
+    let paths = format_patch(
+        &test_repository.repository,
+        base.id()..second.id(),
+        out.path(),
+        FormatPatchOptions::default(),
+    )
+    .unwrap();
+
+    let names: Vec<_> = paths
+        .iter()
+        .map(|path| path.strip_prefix(out.path()).unwrap().to_str().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        ["0001-Change-to-b.patch", "0002-Add-another-file.patch"]
+    );
+    let first_patch = std::fs::read_to_string(&paths[0]).unwrap();
+    assert!(first_patch.starts_with(&format!("From {} ", first.id())));
+    assert!(first_patch.contains("\nFrom: Caleb <caleb@gitbutler.com>\n"));
+    assert!(first_patch.contains("\nDate: "));
+    assert!(first_patch.contains("\nSubject: [PATCH 1/2] Change to b\n"));
+    assert!(first_patch.contains("\n-a\n+b\n"));
+    let second_patch = std::fs::read_to_string(&paths[1]).unwrap();
+    assert!(
+        second_patch.contains("\nSubject: [PATCH 2/2] Add another file\n\nWith a description.\n")
+    );
+}
+
+#[test]
+fn cover_letter() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a\n")]);
+    let base = test_repository
+        .repository
+        .head()
+        .unwrap()
+        .peel_to_commit()
+        .unwrap();
+    let tip = test_repository.commit_tree_with_message(Some(&base), "Change", &[("file", "b\n")]);
+
+    let out = tempfile::tempdir().unwrap();
+    let paths = format_patch(
+        &test_repository.repository,
+        base.id()..tip.id(),
+        out.path(),
+        FormatPatchOptions { cover_letter: true },
+    )
+    .unwrap();
+
+    assert_eq!(paths.len(), 2);
+    assert!(paths[0].ends_with("0000-cover-letter.patch"));
+    let cover_letter = std::fs::read_to_string(&paths[0]).unwrap();
+    assert!(cover_letter.contains("\nSubject: [PATCH 0/1] *** SUBJECT HERE ***\n"));
+    assert!(paths[1].ends_with("0001-Change.patch"));
+}
+
+#[test]
+fn empty_range() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a\n")]);
+    let head = test_repository.repository.head().unwrap().target().unwrap();
+
+    let out = tempfile::tempdir().unwrap();
+    let err = format_patch(
+        &test_repository.repository,
+        head..head,
+        out.path(),
+        FormatPatchOptions::default(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::Validation)
+    );
+}
@@ -0,0 +1,155 @@
+use std::sync::OnceLock;
+
+use gitbutler_error::error::{AnyhowContextExt, Code};
+use gitbutler_repo::RepositoryExt as _;
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+/// Make sure only the repository-local configuration can provide an identity.
+///
+/// The environment is shared by all tests, which run in parallel, so it's only changed once
+/// by the first test, while all others wait for it.
+fn isolate_from_user_configuration() {
+    static HOME: OnceLock<tempfile::TempDir> = OnceLock::new();
+    HOME.get_or_init(|| {
+        let home = tempfile::tempdir().unwrap();
+        for name in [
+            "GIT_AUTHOR_NAME",
+            "GIT_AUTHOR_EMAIL",
+            "GIT_COMMITTER_NAME",
+            "GIT_COMMITTER_EMAIL",
+        ] {
+            std::env::remove_var(name);
+        }
+        std::env::set_var("GIT_CONFIG_NOSYSTEM", "1");
+        std::env::set_var("GIT_CONFIG_GLOBAL", home.path().join("gitconfig"));
+        std::env::set_var("HOME", home.path());
+        std::env::set_var("XDG_CONFIG_HOME", home.path());
+        home
+    });
+}
+
+#[test]
+fn configured_identity() {
+    isolate_from_user_configuration();
+    let test_repository = TestingRepository::open();
+
+    assert!(test_repository.repository.has_identity());
+    assert!(test_repository.repository.signatures().is_ok());
+}
+
+#[test]
+fn missing_identity() {
+    isolate_from_user_configuration();
+    let test_repository = TestingRepository::open();
+    let mut config = test_repository
+        .repository
+        .config()
+        .unwrap()
+        .open_level(git2::ConfigLevel::Local)
+        .unwrap();
+    config.remove("user.name").unwrap();
+    config.remove("user.email").unwrap();
+
+    assert!(!test_repository.repository.has_identity());
+    let err = test_repository.repository.signatures().unwrap_err();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::AuthorMissing)
+    );
+}
+
+#[test]
+fn empty_email_is_no_identity() {
+    isolate_from_user_configuration();
+    let test_repository = TestingRepository::open();
+    test_repository
+        .repository
+        .config()
+        .unwrap()
+        .open_level(git2::ConfigLevel::Local)
+        .unwrap()
+        .set_str("user.email", "")
+        .unwrap();
+
+    assert!(!test_repository.repository.has_identity());
+    assert!(test_repository.repository.signatures().is_err());
+}
+
+mod identity_source {
+    use gitbutler_repo::{commit_message::Identity, IdentitySource, RepositoryExt as _};
+    use gitbutler_testsupport::testing_repository::TestingRepository;
+
+    use super::isolate_from_user_configuration;
+
+    /// `libgit2` determines the location of its configuration files only once, so point it to `home`
+    /// explicitly, where the global configuration is `.gitconfig`.
+    fn isolate_libgit2_configuration(home: &std::path::Path) {
+        for level in [
+            git2::ConfigLevel::Global,
+            git2::ConfigLevel::XDG,
+            git2::ConfigLevel::System,
+        ] {
+            unsafe { git2::opts::set_search_path(level, home) }.unwrap();
+        }
+    }
+
+    fn remove_local_identity(repo: &git2::Repository) {
+        let mut config = repo
+            .config()
+            .unwrap()
+            .open_level(git2::ConfigLevel::Local)
+            .unwrap();
+        config.remove("user.name").unwrap();
+        config.remove("user.email").unwrap();
+    }
+
+    fn identity(name: &str, email: &str) -> Identity {
+        Identity {
+            name: name.into(),
+            email: email.into(),
+        }
+    }
+
+    /// All levels are checked by a single test, as the search path of `libgit2` is global to
+    /// the process and would otherwise be changed by tests running in parallel.
+    #[test]
+    fn levels() {
+        isolate_from_user_configuration();
+        let home = tempfile::tempdir().unwrap();
+        isolate_libgit2_configuration(home.path());
+
+        let test_repository = TestingRepository::open();
+        remove_local_identity(&test_repository.repository);
+        // `libgit2` only picks up configuration files that exist when the repository is opened.
+        let open = || git2::Repository::open(test_repository.tempdir.path()).unwrap();
+        assert_eq!(
+            open().identity_source().unwrap(),
+            IdentitySource::None,
+            "nothing is configured"
+        );
+
+        std::fs::write(
+            home.path().join(".gitconfig"),
+            "[user]\n\tname = Global\n\temail = global@example.com\n",
+        )
+        .unwrap();
+        let repo = open();
+        assert_eq!(
+            repo.identity_source().unwrap(),
+            IdentitySource::Global(identity("Global", "global@example.com"))
+        );
+
+        let mut config = repo
+            .config()
+            .unwrap()
+            .open_level(git2::ConfigLevel::Local)
+            .unwrap();
+        config.set_str("user.name", "Local").unwrap();
+        config.set_str("user.email", "local@example.com").unwrap();
+        assert_eq!(
+            repo.identity_source().unwrap(),
+            IdentitySource::Local(identity("Local", "local@example.com")),
+            "the local identity overrides the global one"
+        );
+    }
+}
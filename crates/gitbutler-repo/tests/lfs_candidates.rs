@@ -0,0 +1,59 @@
+use std::path::PathBuf;
+
+use gitbutler_repo::RepositoryExt as _;
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn large_binary_files_not_yet_in_lfs() {
+    let test_repository = TestingRepository::open();
+    let repo = &test_repository.repository;
+    assert!(
+        repo.lfs_candidates(0).unwrap().is_empty(),
+        "an empty index has nothing to report"
+    );
+
+    let large_binary = format!("\0{}", "x".repeat(1024));
+    // This also adds all files to the index.
+    test_repository.commit_tree(
+        None,
+        &[
+            ("assets/large.bin", large_binary.as_str()),
+            ("small.bin", "\0x"),
+            ("large.txt", &"text\n".repeat(1024)),
+            ("marked-binary.dat", &"data\n".repeat(1024)),
+            // Stored as is, as the filter isn't run here, but tracked by LFS nonetheless.
+            ("tracked/large.bin", large_binary.as_str()),
+            (
+                ".gitattributes",
+                "marked-binary.dat binary\ntracked/*.bin filter=lfs diff=lfs merge=lfs -text\n",
+            ),
+        ],
+    );
+
+    assert_eq!(
+        repo.lfs_candidates(1000).unwrap(),
+        [
+            (PathBuf::from("assets/large.bin"), 1025),
+            (PathBuf::from("marked-binary.dat"), 5 * 1024),
+        ],
+        "small files, text files and those tracked by LFS are skipped"
+    );
+    assert_eq!(
+        repo.lfs_candidates(1025).unwrap(),
+        [(PathBuf::from("marked-binary.dat"), 5 * 1024)],
+        "files must be larger than the threshold"
+    );
+}
+
+#[test]
+fn only_the_beginning_of_the_content_is_sniffed() {
+    let test_repository = TestingRepository::open();
+    let repo = &test_repository.repository;
+    let late_binary = format!("{}\0", "text\n".repeat(2000));
+    test_repository.commit_tree(None, &[("late.bin", late_binary.as_str())]);
+
+    assert!(
+        repo.lfs_candidates(1000).unwrap().is_empty(),
+        "like Git, a NUL byte after the first 8000 bytes doesn't make a file binary"
+    );
+}
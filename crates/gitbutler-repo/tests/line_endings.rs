@@ -0,0 +1,50 @@
+use gitbutler_repo::RepositoryExt as _;
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn autocrlf_round_trip() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("other", "a")]);
+    let repo = &test_repository.repository;
+    let workdir = test_repository.tempdir.path();
+    repo.config()
+        .unwrap()
+        .set_bool("core.autocrlf", true)
+        .unwrap();
+    std::fs::write(workdir.join("file.txt"), "a\r\nb\r\n").unwrap();
+
+    let commit_id = repo.commit_paths(&["file.txt".into()], "add file").unwrap();
+
+    let tree = repo.find_commit(commit_id).unwrap().tree().unwrap();
+    let blob = repo
+        .find_blob(tree.get_name("file.txt").unwrap().id())
+        .unwrap();
+    assert_eq!(blob.content(), b"a\nb\n", "the blob is stored with LF");
+
+    std::fs::remove_file(workdir.join("file.txt")).unwrap();
+    repo.checkout_tree_builder(&tree)
+        .force()
+        .checkout()
+        .unwrap();
+    assert_eq!(
+        std::fs::read(workdir.join("file.txt")).unwrap(),
+        b"a\r\nb\r\n",
+        "the worktree file is checked out with CRLF"
+    );
+}
+
+#[test]
+fn gitattributes_eol() {
+    let test_repository =
+        TestingRepository::open_with_initial_commit(&[(".gitattributes", "*.txt text eol=crlf\n")]);
+    let repo = &test_repository.repository;
+    let workdir = test_repository.tempdir.path();
+    std::fs::write(workdir.join("file.txt"), "a\r\n").unwrap();
+
+    let commit_id = repo.commit_paths(&["file.txt".into()], "add file").unwrap();
+
+    let tree = repo.find_commit(commit_id).unwrap().tree().unwrap();
+    let blob = repo
+        .find_blob(tree.get_name("file.txt").unwrap().id())
+        .unwrap();
+    assert_eq!(blob.content(), b"a\n", "the blob is stored with LF");
+}
@@ -0,0 +1,46 @@
+use gitbutler_repo::commit_message::Identity;
+use gitbutler_repo::logging::{LogUntil, RepositoryExt as _};
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+fn identity(name: &str, email: &str) -> Identity {
+    Identity {
+        name: name.into(),
+        email: email.into(),
+    }
+}
+
+#[test]
+fn co_authors_and_sign_off_from_trailers() {
+    let test_repository = TestingRepository::open();
+    let base = test_repository.commit_tree_with_message(None, "base", &[("file", "a")]);
+    let commit = test_repository.commit_tree_with_message(
+        Some(&base),
+        "pair on it\n\nA body.\n\nCo-authored-by: Alice <alice@example.com>\nco-authored-by: Bob <bob@example.com>\nSigned-off-by: Caleb <caleb@gitbutler.com>\n",
+        &[("file", "b")],
+    );
+
+    let summaries = test_repository
+        .repository
+        .log_summaries(commit.id(), LogUntil::End, false)
+        .unwrap();
+    assert_eq!(summaries.len(), 2);
+
+    let summary = &summaries[0];
+    assert_eq!(summary.id, commit.id());
+    assert_eq!(summary.title, "pair on it");
+    assert_eq!(summary.author, identity("Caleb", "caleb@gitbutler.com"));
+    assert_eq!(
+        summary.co_authors,
+        [
+            identity("Alice", "alice@example.com"),
+            identity("Bob", "bob@example.com")
+        ],
+        "trailer tokens are case-insensitive"
+    );
+    assert!(summary.signed_off);
+
+    let summary = &summaries[1];
+    assert_eq!(summary.id, base.id());
+    assert!(summary.co_authors.is_empty());
+    assert!(!summary.signed_off);
+}
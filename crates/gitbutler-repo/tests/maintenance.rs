@@ -0,0 +1,62 @@
+use std::sync::atomic::AtomicBool;
+
+use gitbutler_repo::maintenance::{maintenance, MaintenanceKind};
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn commit_graph_write() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "content")]);
+    let repo = &test_repository.repository;
+    let commit_graph = repo.path().join("objects/info/commit-graph");
+    assert!(!commit_graph.exists());
+
+    maintenance(
+        repo,
+        MaintenanceKind::CommitGraph,
+        &mut |_progress| {},
+        &AtomicBool::default(),
+    )
+    .unwrap();
+    assert!(commit_graph.is_file());
+}
+
+#[test]
+fn incremental_repack() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "content")]);
+    let repo = &test_repository.repository;
+    maintenance(
+        repo,
+        MaintenanceKind::IncrementalRepack,
+        &mut |_progress| {},
+        &AtomicBool::default(),
+    )
+    .unwrap();
+
+    let has_pack = std::fs::read_dir(repo.path().join("objects/pack"))
+        .unwrap()
+        .any(|entry| {
+            entry
+                .unwrap()
+                .path()
+                .extension()
+                .is_some_and(|ext| ext == "pack")
+        });
+    assert!(has_pack, "loose objects were packed");
+}
+
+#[test]
+fn interrupted() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "content")]);
+    let repo = &test_repository.repository;
+    maintenance(
+        repo,
+        MaintenanceKind::CommitGraph,
+        &mut |_progress| {},
+        &AtomicBool::new(true),
+    )
+    .unwrap_err();
+    assert!(
+        !repo.path().join("objects/info/commit-graph").exists(),
+        "nothing was written"
+    );
+}
@@ -0,0 +1,65 @@
+use gitbutler_error::error::{AnyhowContextExt, Code};
+use gitbutler_repo::RepositoryExt as _;
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn two_parents() {
+    let test_repository = TestingRepository::open();
+    let repo = &test_repository.repository;
+    let base = test_repository.commit_tree(None, &[("file", "a")]);
+    let ours = test_repository.commit_tree(Some(&base), &[("file", "a"), ("ours", "b")]);
+    let theirs = test_repository.commit_tree(Some(&base), &[("file", "a"), ("theirs", "c")]);
+    let merged = test_repository.commit_tree(
+        Some(&ours),
+        &[("file", "a"), ("ours", "b"), ("theirs", "c")],
+    );
+
+    let merge = repo
+        .create_merge_commit(merged.tree_id(), &[ours.id(), theirs.id()], "merge")
+        .unwrap();
+
+    let merge = repo.find_commit(merge).unwrap();
+    assert_eq!(
+        merge.parent_ids().collect::<Vec<_>>(),
+        [ours.id(), theirs.id()]
+    );
+    assert_eq!(merge.tree_id(), merged.tree_id());
+    assert_eq!(merge.message(), Some("merge"));
+}
+
+#[test]
+fn without_parents_is_refused() {
+    let test_repository = TestingRepository::open();
+    let base = test_repository.commit_tree(None, &[("file", "a")]);
+
+    let err = test_repository
+        .repository
+        .create_merge_commit(base.tree_id(), &[], "merge")
+        .unwrap_err();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::Validation)
+    );
+}
+
+#[test]
+fn missing_objects_are_refused() {
+    let test_repository = TestingRepository::open();
+    let repo = &test_repository.repository;
+    let base = test_repository.commit_tree(None, &[("file", "a")]);
+
+    for (tree, parent) in [
+        (git2::Oid::zero(), base.id()),
+        (base.tree_id(), git2::Oid::zero()),
+        // A tree isn't a commit.
+        (base.tree_id(), base.tree_id()),
+    ] {
+        let err = repo
+            .create_merge_commit(tree, &[base.id(), parent], "merge")
+            .unwrap_err();
+        assert_eq!(
+            err.custom_context().map(|ctx| ctx.code),
+            Some(Code::Validation)
+        );
+    }
+}
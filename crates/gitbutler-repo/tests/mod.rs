@@ -1,4 +1,45 @@
+mod alternates;
+mod branch;
+mod bundle;
+mod checkout;
+mod checkout_to;
+mod clear_stale_locks;
+mod commit_error;
+mod commit_files;
+mod commit_insights;
+mod commit_paths;
+mod commit_template;
+mod config;
+mod create_tag;
 mod create_wd_tree;
+mod crlf_in_index;
 mod credentials;
+mod dangling_commits;
+mod diff_colors;
+mod discard;
+mod format_patch;
+mod lfs_candidates;
+mod line_endings;
+mod log_summaries;
+mod maintenance;
 mod merge_base_octopussy;
+mod merge_commit;
+mod mutation;
+mod notes;
+mod on_disk_size;
+mod operation;
+mod patch_id;
+mod path_status;
+mod read_blob_stream;
 mod rebase;
+mod refs_matching;
+mod remote_tracking_branches;
+mod require_clean;
+mod secrets;
+mod sign_buffer;
+mod sparse_checkout;
+mod staging;
+mod stash_show;
+mod submodules;
+mod update_refs;
+mod write_tree;
@@ -0,0 +1,67 @@
+use std::sync::{Arc, Mutex};
+
+use gitbutler_repo::{
+    mutation::{on_mutation, remove_observer, MutationKind, ObserverId},
+    RefUpdate, RepositoryExt as _,
+};
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+fn record(repo: &git2::Repository) -> (ObserverId, Arc<Mutex<Vec<MutationKind>>>) {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let id = on_mutation(
+        repo,
+        Box::new({
+            let seen = Arc::clone(&seen);
+            move |kind| seen.lock().unwrap().push(kind)
+        }),
+    );
+    (id, seen)
+}
+
+#[test]
+fn commit_is_observed() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    let repo = &test_repository.repository;
+    let (_id, seen) = record(repo);
+    std::fs::write(test_repository.tempdir.path().join("file"), "b").unwrap();
+
+    repo.commit_paths(&["file".into()], "change").unwrap();
+    assert_eq!(*seen.lock().unwrap(), [MutationKind::Commit]);
+
+    let other_instance = git2::Repository::open(repo.path()).unwrap();
+    let head = other_instance.head().unwrap().target().unwrap();
+    other_instance
+        .update_refs(vec![RefUpdate {
+            name: "refs/heads/new".into(),
+            expected: None,
+            new: Some(head),
+        }])
+        .unwrap();
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [MutationKind::Commit, MutationKind::RefUpdate],
+        "changes through other instances of the same repository are observed as well"
+    );
+}
+
+#[test]
+fn removed_observers_are_not_called() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    let repo = &test_repository.repository;
+    let (id, seen) = record(repo);
+    let unrelated = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    std::fs::write(unrelated.tempdir.path().join("file"), "b").unwrap();
+    unrelated
+        .repository
+        .commit_paths(&["file".into()], "change")
+        .unwrap();
+    assert!(
+        seen.lock().unwrap().is_empty(),
+        "only changes of the observed repository are reported"
+    );
+
+    remove_observer(id);
+    std::fs::write(test_repository.tempdir.path().join("file"), "b").unwrap();
+    repo.commit_paths(&["file".into()], "change").unwrap();
+    assert!(seen.lock().unwrap().is_empty());
+}
@@ -0,0 +1,44 @@
+use gitbutler_repo::RepositoryExt as _;
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn write_and_read_back() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    let repo = &test_repository.repository;
+    let commit = repo.head().unwrap().target().unwrap();
+    assert_eq!(repo.read_note(commit, None).unwrap(), None);
+
+    repo.write_note(commit, None, "reviewed").unwrap();
+    assert_eq!(
+        repo.read_note(commit, None).unwrap().as_deref(),
+        Some("reviewed")
+    );
+    assert_eq!(
+        repo.read_note(commit, Some("refs/notes/commits"))
+            .unwrap()
+            .as_deref(),
+        Some("reviewed"),
+        "that's the default notes ref"
+    );
+
+    repo.write_note(commit, None, "approved").unwrap();
+    assert_eq!(
+        repo.read_note(commit, None).unwrap().as_deref(),
+        Some("approved"),
+        "notes are replaced"
+    );
+
+    repo.write_note(commit, Some("refs/notes/ci"), "passed")
+        .unwrap();
+    assert_eq!(
+        repo.read_note(commit, Some("refs/notes/ci"))
+            .unwrap()
+            .as_deref(),
+        Some("passed")
+    );
+    assert_eq!(
+        repo.read_note(commit, None).unwrap().as_deref(),
+        Some("approved"),
+        "each notes ref is separate"
+    );
+}
@@ -0,0 +1,34 @@
+use gitbutler_repo::RepositoryExt as _;
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn packed_loose_and_worktree_sizes() {
+    let test_repository =
+        TestingRepository::open_with_initial_commit(&[("file", "content"), ("dir/other", "12")]);
+    let repo = &test_repository.repository;
+
+    let size = repo.on_disk_size().unwrap();
+    assert_eq!(size.packed_bytes, 0, "there are no packs yet");
+    assert!(size.loose_bytes > 0, "the initial commit is stored loosely");
+    assert_eq!(
+        size.worktree_bytes,
+        ("content".len() + "12".len()) as u64,
+        "the `.git` directory doesn't count towards the worktree"
+    );
+
+    let mut packbuilder = repo.packbuilder().unwrap();
+    packbuilder
+        .insert_commit(repo.head().unwrap().target().unwrap())
+        .unwrap();
+    packbuilder
+        .write(&repo.path().join("objects").join("pack"), 0o644)
+        .unwrap();
+
+    let size_with_pack = repo.on_disk_size().unwrap();
+    assert!(size_with_pack.packed_bytes > 0);
+    assert_eq!(
+        size_with_pack.loose_bytes, size.loose_bytes,
+        "loose objects are only removed by maintenance"
+    );
+    assert_eq!(size_with_pack.worktree_bytes, size.worktree_bytes);
+}
@@ -0,0 +1,58 @@
+use gitbutler_repo::operation::{operation_progress, OperationKind, OperationProgress};
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn nothing_in_progress() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    assert_eq!(
+        operation_progress(&test_repository.repository).unwrap(),
+        None
+    );
+}
+
+#[test]
+fn paused_rebase() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    let repo = &test_repository.repository;
+    let stopped_at = repo.head().unwrap().target().unwrap();
+    // The state `git rebase` leaves behind when stopping at a conflict.
+    let rebase_dir = repo.path().join("rebase-merge");
+    std::fs::create_dir(&rebase_dir).unwrap();
+    std::fs::write(rebase_dir.join("interactive"), "").unwrap();
+    std::fs::write(rebase_dir.join("msgnum"), "3\n").unwrap();
+    std::fs::write(rebase_dir.join("end"), "7\n").unwrap();
+    std::fs::write(
+        rebase_dir.join("stopped-sha"),
+        format!("{}\n", &stopped_at.to_string()[..7]),
+    )
+    .unwrap();
+
+    assert_eq!(
+        operation_progress(repo).unwrap(),
+        Some(OperationProgress {
+            kind: OperationKind::Rebase,
+            step: 3,
+            total: 7,
+            stopped_at: Some(stopped_at),
+        })
+    );
+}
+
+#[test]
+fn paused_merge() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    let repo = &test_repository.repository;
+    let base = repo.head().unwrap().peel_to_commit().unwrap();
+    let theirs = test_repository.commit_tree(Some(&base), &[("file", "b")]);
+    std::fs::write(repo.path().join("MERGE_HEAD"), format!("{}\n", theirs.id())).unwrap();
+
+    assert_eq!(
+        operation_progress(repo).unwrap(),
+        Some(OperationProgress {
+            kind: OperationKind::Merge,
+            step: 1,
+            total: 1,
+            stopped_at: Some(theirs.id()),
+        })
+    );
+}
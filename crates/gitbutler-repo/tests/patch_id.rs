@@ -0,0 +1,85 @@
+use gitbutler_repo::RepositoryExt as _;
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn cherry_picks_share_the_patch_id() {
+    let test_repository = TestingRepository::open();
+    let base = test_repository.commit_tree(None, &[("file", "a\nb\nc\n"), ("other", "x\n")]);
+    let commit =
+        test_repository.commit_tree(Some(&base), &[("file", "a\nB\nc\n"), ("other", "x\n")]);
+
+    let unrelated_base =
+        test_repository.commit_tree(Some(&base), &[("file", "a\nb\nc\n"), ("other", "y\n")]);
+    let cherry_pick = test_repository.commit_tree(
+        Some(&unrelated_base),
+        &[("file", "a\nB\nc\n"), ("other", "y\n")],
+    );
+    let repo = &test_repository.repository;
+    assert_ne!(commit.id(), cherry_pick.id());
+
+    let patch_id = repo.patch_id(commit.id()).unwrap();
+    assert_eq!(
+        patch_id,
+        repo.patch_id(cherry_pick.id()).unwrap(),
+        "the same change has the same patch-id, no matter where it's applied"
+    );
+    assert_ne!(
+        patch_id,
+        repo.patch_id(unrelated_base.id()).unwrap(),
+        "different changes have different patch-ids"
+    );
+}
+
+mod is_upstream {
+    use gitbutler_repo::RepositoryExt as _;
+    use gitbutler_testsupport::testing_repository::TestingRepository;
+
+    #[test]
+    fn cherry_picked_upstream() {
+        let test_repository = TestingRepository::open();
+        let base = test_repository.commit_tree(None, &[("file", "a\nb\nc\n"), ("other", "x\n")]);
+        let commit =
+            test_repository.commit_tree(Some(&base), &[("file", "a\nB\nc\n"), ("other", "x\n")]);
+
+        let upstream =
+            test_repository.commit_tree(Some(&base), &[("file", "a\nb\nc\n"), ("other", "y\n")]);
+        let cherry_pick = test_repository
+            .commit_tree(Some(&upstream), &[("file", "a\nB\nc\n"), ("other", "y\n")]);
+        let upstream_tip = test_repository.commit_tree(
+            Some(&cherry_pick),
+            &[("file", "a\nB\nc\n"), ("other", "y\n"), ("new", "z\n")],
+        );
+
+        let repo = &test_repository.repository;
+        assert!(repo.is_upstream(commit.id(), upstream_tip.id()).unwrap());
+    }
+
+    #[test]
+    fn reachable_from_upstream() {
+        let test_repository = TestingRepository::open();
+        let base = test_repository.commit_tree(None, &[("file", "a\n")]);
+        let commit = test_repository.commit_tree(Some(&base), &[("file", "b\n")]);
+        let upstream_tip = test_repository.commit_tree(Some(&commit), &[("file", "c\n")]);
+
+        let repo = &test_repository.repository;
+        assert!(repo.is_upstream(commit.id(), upstream_tip.id()).unwrap());
+        assert!(repo.is_upstream(commit.id(), commit.id()).unwrap());
+    }
+
+    #[test]
+    fn new_commit() {
+        let test_repository = TestingRepository::open();
+        let base = test_repository.commit_tree(None, &[("file", "a\nb\nc\n"), ("other", "x\n")]);
+        let commit =
+            test_repository.commit_tree(Some(&base), &[("file", "a\nB\nc\n"), ("other", "x\n")]);
+        let upstream_tip =
+            test_repository.commit_tree(Some(&base), &[("file", "a\nb\nc\n"), ("other", "y\n")]);
+
+        let repo = &test_repository.repository;
+        assert!(!repo.is_upstream(commit.id(), upstream_tip.id()).unwrap());
+        assert!(
+            !repo.is_upstream(upstream_tip.id(), commit.id()).unwrap(),
+            "it works in both directions"
+        );
+    }
+}
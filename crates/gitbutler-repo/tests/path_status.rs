@@ -0,0 +1,64 @@
+use std::path::Path;
+
+use gitbutler_diff::ChangeType;
+use gitbutler_repo::{PathState, RepositoryExt as _};
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn each_state() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[
+        (".gitignore", "*.log\n"),
+        ("unchanged", "a"),
+        ("modified", "a"),
+        ("deleted", "a"),
+    ]);
+    let repo = &test_repository.repository;
+    let workdir = test_repository.tempdir.path();
+    std::fs::write(workdir.join("modified"), "b").unwrap();
+    std::fs::remove_file(workdir.join("deleted")).unwrap();
+    std::fs::write(workdir.join("new"), "b").unwrap();
+    std::fs::write(workdir.join("debug.log"), "b").unwrap();
+    std::fs::write(workdir.join("added"), "b").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("added")).unwrap();
+    index.write().unwrap();
+
+    for (path, expected) in [
+        ("unchanged", PathState::Tracked { status: None }),
+        (
+            "modified",
+            PathState::Tracked {
+                status: Some(ChangeType::Modified),
+            },
+        ),
+        (
+            "deleted",
+            PathState::Tracked {
+                status: Some(ChangeType::Deleted),
+            },
+        ),
+        (
+            "added",
+            PathState::Tracked {
+                status: Some(ChangeType::Added),
+            },
+        ),
+        ("new", PathState::Untracked),
+        ("debug.log", PathState::Ignored),
+    ] {
+        assert_eq!(
+            repo.path_status(Path::new(path)).unwrap(),
+            expected,
+            "{path}"
+        );
+    }
+}
+
+#[test]
+fn missing_path_is_an_error() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    assert!(test_repository
+        .repository
+        .path_status(Path::new("does-not-exist"))
+        .is_err());
+}
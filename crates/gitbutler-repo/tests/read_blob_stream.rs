@@ -0,0 +1,91 @@
+use std::ops::ControlFlow;
+
+use gitbutler_repo::{RepositoryExt as _, BLOB_STREAM_CHUNK_SIZE};
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn all_chunks() {
+    let test_repository = TestingRepository::open();
+    let repo = &test_repository.repository;
+    let content: Vec<u8> = (0..BLOB_STREAM_CHUNK_SIZE * 2 + 10)
+        .map(|idx| (idx % 251) as u8)
+        .collect();
+    let id = repo.blob(&content).unwrap();
+
+    let mut chunk_sizes = Vec::new();
+    let mut streamed = Vec::new();
+    repo.read_blob_stream(id, |chunk| {
+        chunk_sizes.push(chunk.len());
+        streamed.extend_from_slice(chunk);
+        ControlFlow::Continue(())
+    })
+    .unwrap();
+    assert_eq!(
+        chunk_sizes,
+        [BLOB_STREAM_CHUNK_SIZE, BLOB_STREAM_CHUNK_SIZE, 10]
+    );
+    assert!(streamed == content, "the content is passed in order");
+}
+
+#[test]
+fn early_stop_after_first_chunk() {
+    let test_repository = TestingRepository::open();
+    let repo = &test_repository.repository;
+    // Content that doesn't compress, so cutting the loose object in half keeps about half of it.
+    let mut state = 0x2545_f491_u32;
+    let content: Vec<u8> = (0..BLOB_STREAM_CHUNK_SIZE * 4)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect();
+    let id = repo.blob(&content).unwrap();
+    let hex = id.to_string();
+    let loose_object = repo.path().join("objects").join(&hex[..2]).join(&hex[2..]);
+    let compressed = std::fs::read(&loose_object).unwrap();
+    std::fs::remove_file(&loose_object).unwrap();
+    std::fs::write(&loose_object, &compressed[..compressed.len() / 2]).unwrap();
+
+    let mut streamed = Vec::new();
+    let res = repo.read_blob_stream(id, |chunk| {
+        streamed.extend_from_slice(chunk);
+        ControlFlow::Continue(())
+    });
+    assert!(
+        res.is_err() || streamed.len() < content.len(),
+        "the truncated object can't be read as a whole"
+    );
+
+    let mut chunks = Vec::new();
+    repo.read_blob_stream(id, |chunk| {
+        chunks.push(chunk.to_vec());
+        ControlFlow::Break(())
+    })
+    .unwrap();
+    assert_eq!(chunks.len(), 1);
+    assert!(
+        chunks[0] == content[..BLOB_STREAM_CHUNK_SIZE],
+        "only the first chunk was read, so the missing end of the object doesn't matter"
+    );
+}
+
+#[test]
+fn empty_blob_and_non_blob() {
+    let test_repository = TestingRepository::open();
+    let repo = &test_repository.repository;
+    let id = repo.blob(b"").unwrap();
+    let mut calls = 0;
+    repo.read_blob_stream(id, |_chunk| {
+        calls += 1;
+        ControlFlow::Continue(())
+    })
+    .unwrap();
+    assert_eq!(calls, 0);
+
+    let tree_id = repo.head().unwrap().peel_to_tree().unwrap().id();
+    assert!(repo
+        .read_blob_stream(tree_id, |_chunk| ControlFlow::Continue(()))
+        .is_err());
+}
@@ -414,3 +414,346 @@ mod gitbutler_merge_commits {
         );
     }
 }
+
+mod split_commit {
+    use gitbutler_commit::commit_ext::CommitExt as _;
+    use gitbutler_error::error::{AnyhowContextExt, Code};
+    use gitbutler_repo::rebase::split_commit;
+    use gitbutler_testsupport::testing_repository::{
+        assert_commit_tree_matches, TestingRepository,
+    };
+
+    #[test]
+    fn two_file_commit_into_single_file_commits() {
+        let test_repository = TestingRepository::open();
+        let repo = &test_repository.repository;
+
+        let base = test_repository.commit_tree(None, &[("foo.txt", "a"), ("bar.txt", "a")]);
+        let to_split = test_repository.commit_tree_with_message(
+            Some(&base),
+            "change both",
+            &[("foo.txt", "b"), ("bar.txt", "b")],
+        );
+        let descendant = test_repository.commit_tree(
+            Some(&to_split),
+            &[("foo.txt", "b"), ("bar.txt", "b"), ("baz.txt", "c")],
+        );
+        let first_tree = test_repository
+            .commit_tree(Some(&base), &[("foo.txt", "b"), ("bar.txt", "a")])
+            .tree_id();
+
+        let split = split_commit(repo, to_split.id(), first_tree, descendant.id()).unwrap();
+
+        let first = repo.find_commit(split.first).unwrap();
+        assert_eq!(first.parent_ids().collect::<Vec<_>>(), [base.id()]);
+        assert_eq!(first.message(), Some("change both"));
+        assert_commit_tree_matches(repo, &first, &[("foo.txt", b"b"), ("bar.txt", b"a")]);
+
+        let second = repo.find_commit(split.second).unwrap();
+        assert_eq!(second.parent_ids().collect::<Vec<_>>(), [first.id()]);
+        assert_eq!(second.tree_id(), to_split.tree_id());
+        assert_eq!(
+            second.change_id(),
+            to_split.change_id(),
+            "the second commit is the continuation of the original one"
+        );
+
+        let new_tip = repo.find_commit(split.new_tip).unwrap();
+        assert_eq!(new_tip.parent_ids().collect::<Vec<_>>(), [second.id()]);
+        assert_commit_tree_matches(
+            repo,
+            &new_tip,
+            &[("foo.txt", b"b"), ("bar.txt", b"b"), ("baz.txt", b"c")],
+        );
+    }
+
+    #[test]
+    fn commit_at_tip() {
+        let test_repository = TestingRepository::open();
+        let base = test_repository.commit_tree(None, &[("foo.txt", "a")]);
+        let to_split = test_repository.commit_tree(Some(&base), &[("foo.txt", "b")]);
+
+        let split = split_commit(
+            &test_repository.repository,
+            to_split.id(),
+            base.tree_id(),
+            to_split.id(),
+        )
+        .unwrap();
+        assert_eq!(split.new_tip, split.second);
+    }
+
+    #[test]
+    fn commit_outside_of_branch_is_refused() {
+        let test_repository = TestingRepository::open();
+        let base = test_repository.commit_tree(None, &[("foo.txt", "a")]);
+        let to_split = test_repository.commit_tree(Some(&base), &[("foo.txt", "b")]);
+        let other = test_repository.commit_tree(Some(&base), &[("foo.txt", "c")]);
+
+        let err = split_commit(
+            &test_repository.repository,
+            to_split.id(),
+            base.tree_id(),
+            other.id(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.custom_context().map(|ctx| ctx.code),
+            Some(Code::Validation)
+        );
+    }
+}
+
+mod reword_commit {
+    use gitbutler_error::error::{AnyhowContextExt, Code};
+    use gitbutler_repo::rebase::reword_commit;
+    use gitbutler_testsupport::testing_repository::TestingRepository;
+
+    #[test]
+    fn middle_commit_keeps_descendant_trees() {
+        let test_repository = TestingRepository::open();
+        let repo = &test_repository.repository;
+
+        let base = test_repository.commit_tree(None, &[("foo.txt", "a")]);
+        let to_reword =
+            test_repository.commit_tree_with_message(Some(&base), "old", &[("foo.txt", "b")]);
+        let descendant = test_repository.commit_tree_with_message(
+            Some(&to_reword),
+            "descendant",
+            &[("foo.txt", "b"), ("bar.txt", "c")],
+        );
+
+        let new_tip = reword_commit(repo, to_reword.id(), "new", descendant.id()).unwrap();
+
+        let new_tip = repo.find_commit(new_tip).unwrap();
+        assert_ne!(new_tip.id(), descendant.id());
+        assert_eq!(new_tip.message(), Some("descendant"));
+        assert_eq!(new_tip.tree_id(), descendant.tree_id());
+
+        let reworded = new_tip.parent(0).unwrap();
+        assert_eq!(reworded.message(), Some("new"));
+        assert_eq!(reworded.tree_id(), to_reword.tree_id());
+        assert_eq!(reworded.author().email(), to_reword.author().email());
+        assert_eq!(reworded.author().when(), to_reword.author().when());
+        assert_eq!(reworded.parent_ids().collect::<Vec<_>>(), [base.id()]);
+    }
+
+    #[test]
+    fn commit_at_tip() {
+        let test_repository = TestingRepository::open();
+        let base = test_repository.commit_tree(None, &[("foo.txt", "a")]);
+        let to_reword = test_repository.commit_tree(Some(&base), &[("foo.txt", "b")]);
+
+        let new_tip = reword_commit(
+            &test_repository.repository,
+            to_reword.id(),
+            "new",
+            to_reword.id(),
+        )
+        .unwrap();
+        let new_tip = test_repository.repository.find_commit(new_tip).unwrap();
+        assert_eq!(new_tip.message(), Some("new"));
+        assert_eq!(new_tip.tree_id(), to_reword.tree_id());
+    }
+
+    #[test]
+    fn empty_message_is_refused() {
+        let test_repository = TestingRepository::open();
+        let base = test_repository.commit_tree(None, &[("foo.txt", "a")]);
+
+        let err =
+            reword_commit(&test_repository.repository, base.id(), " \n", base.id()).unwrap_err();
+        assert_eq!(
+            err.custom_context().map(|ctx| ctx.code),
+            Some(Code::Validation)
+        );
+    }
+}
+
+mod reorder_commits {
+    use gitbutler_error::error::{AnyhowContextExt, Code};
+    use gitbutler_repo::rebase::{reorder_commits, ReorderOutcome};
+    use gitbutler_testsupport::testing_repository::{
+        assert_commit_tree_matches, TestingRepository,
+    };
+
+    #[test]
+    fn two_independent_commits() {
+        let test_repository = TestingRepository::open();
+        let repo = &test_repository.repository;
+        let base = test_repository.commit_tree(None, &[("a", "1"), ("b", "1")]);
+        let first = test_repository.commit_tree_with_message(
+            Some(&base),
+            "first",
+            &[("a", "2"), ("b", "1")],
+        );
+        let second = test_repository.commit_tree_with_message(
+            Some(&first),
+            "second",
+            &[("a", "2"), ("b", "2")],
+        );
+
+        let outcome =
+            reorder_commits(repo, base.id(), second.id(), &[first.id(), second.id()]).unwrap();
+
+        let ReorderOutcome::Reordered(new_tip) = outcome else {
+            panic!("reordering independent commits can't conflict: {outcome:?}");
+        };
+        let new_tip = repo.find_commit(new_tip).unwrap();
+        assert_eq!(new_tip.message(), Some("first"));
+        assert_eq!(
+            new_tip.tree_id(),
+            second.tree_id(),
+            "the result stays the same"
+        );
+        let new_parent = new_tip.parent(0).unwrap();
+        assert_eq!(new_parent.message(), Some("second"));
+        assert_commit_tree_matches(repo, &new_parent, &[("a", b"1"), ("b", b"2")]);
+        assert_eq!(new_parent.parent_ids().collect::<Vec<_>>(), [base.id()]);
+    }
+
+    #[test]
+    fn same_order_keeps_commits() {
+        let test_repository = TestingRepository::open();
+        let base = test_repository.commit_tree(None, &[("a", "1")]);
+        let first = test_repository.commit_tree(Some(&base), &[("a", "2")]);
+        let second = test_repository.commit_tree(Some(&first), &[("a", "3")]);
+
+        let outcome = reorder_commits(
+            &test_repository.repository,
+            base.id(),
+            second.id(),
+            &[second.id(), first.id()],
+        )
+        .unwrap();
+        assert_eq!(outcome, ReorderOutcome::Reordered(second.id()));
+    }
+
+    #[test]
+    fn dependent_commits_conflict() {
+        let test_repository = TestingRepository::open();
+        let base = test_repository.commit_tree(None, &[("a", "1")]);
+        let first = test_repository.commit_tree(Some(&base), &[("a", "2")]);
+        let second = test_repository.commit_tree(Some(&first), &[("a", "3")]);
+
+        let outcome = reorder_commits(
+            &test_repository.repository,
+            base.id(),
+            second.id(),
+            &[first.id(), second.id()],
+        )
+        .unwrap();
+        assert_eq!(
+            outcome,
+            ReorderOutcome::Conflicted {
+                commit: second.id(),
+                paths: vec!["a".into()],
+            }
+        );
+    }
+
+    #[test]
+    fn new_order_must_be_a_permutation() {
+        let test_repository = TestingRepository::open();
+        let base = test_repository.commit_tree(None, &[("a", "1")]);
+        let first = test_repository.commit_tree(Some(&base), &[("a", "2")]);
+        let second = test_repository.commit_tree(Some(&first), &[("a", "3")]);
+
+        for new_order in [
+            vec![second.id()],
+            vec![second.id(), second.id()],
+            vec![second.id(), first.id(), base.id()],
+        ] {
+            let err = reorder_commits(
+                &test_repository.repository,
+                base.id(),
+                second.id(),
+                &new_order,
+            )
+            .unwrap_err();
+            assert_eq!(
+                err.custom_context().map(|ctx| ctx.code),
+                Some(Code::Validation)
+            );
+        }
+    }
+}
+
+mod can_integrate {
+    use gitbutler_repo::rebase::{can_integrate, IntegrationReport};
+    use gitbutler_testsupport::testing_repository::TestingRepository;
+
+    #[test]
+    fn clean() {
+        let test_repository = TestingRepository::open();
+        let base = test_repository.commit_tree(None, &[("a", "1"), ("b", "1")]);
+        let left = test_repository.commit_tree(Some(&base), &[("a", "2"), ("b", "1")]);
+        let right = test_repository.commit_tree(Some(&base), &[("a", "1"), ("b", "2")]);
+
+        assert_eq!(
+            can_integrate(&test_repository.repository, left.id(), right.id()).unwrap(),
+            IntegrationReport::Clean
+        );
+    }
+
+    #[test]
+    fn conflicted() {
+        let test_repository = TestingRepository::open();
+        let base = test_repository.commit_tree(None, &[("a", "1"), ("b", "1"), ("c", "1")]);
+        let left = test_repository.commit_tree(Some(&base), &[("a", "2"), ("b", "2"), ("c", "2")]);
+        let right = test_repository.commit_tree(Some(&base), &[("a", "3"), ("b", "1"), ("c", "3")]);
+
+        assert_eq!(
+            can_integrate(&test_repository.repository, left.id(), right.id()).unwrap(),
+            IntegrationReport::Conflicted(vec!["a".into(), "c".into()])
+        );
+    }
+
+    #[test]
+    fn already_integrated() {
+        let test_repository = TestingRepository::open();
+        let base = test_repository.commit_tree(None, &[("a", "1")]);
+        let descendant = test_repository.commit_tree(Some(&base), &[("a", "2")]);
+
+        for (a, b) in [(base.id(), descendant.id()), (descendant.id(), base.id())] {
+            assert_eq!(
+                can_integrate(&test_repository.repository, a, b).unwrap(),
+                IntegrationReport::AlreadyIntegrated
+            );
+        }
+    }
+}
+
+mod preflight_rebase {
+    use gitbutler_repo::rebase::preflight_rebase;
+    use gitbutler_testsupport::testing_repository::TestingRepository;
+
+    #[test]
+    fn conflict_in_second_commit() {
+        let test_repository = TestingRepository::open();
+        let base = test_repository.commit_tree(None, &[("foo.txt", "a")]);
+        let onto = test_repository.commit_tree(Some(&base), &[("foo.txt", "b")]);
+        let first = test_repository.commit_tree(Some(&base), &[("foo.txt", "a"), ("bar.txt", "x")]);
+        let second =
+            test_repository.commit_tree(Some(&first), &[("foo.txt", "c"), ("bar.txt", "x")]);
+
+        assert_eq!(
+            preflight_rebase(&test_repository.repository, second.id(), onto.id()).unwrap(),
+            vec![std::path::PathBuf::from("foo.txt")]
+        );
+    }
+
+    #[test]
+    fn clean() {
+        let test_repository = TestingRepository::open();
+        let base = test_repository.commit_tree(None, &[("foo.txt", "a"), ("bar.txt", "a")]);
+        let onto = test_repository.commit_tree(Some(&base), &[("foo.txt", "b"), ("bar.txt", "a")]);
+        let tip = test_repository.commit_tree(Some(&base), &[("foo.txt", "a"), ("bar.txt", "b")]);
+
+        assert!(
+            preflight_rebase(&test_repository.repository, tip.id(), onto.id())
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,41 @@
+use gitbutler_error::error::{AnyhowContextExt, Code};
+use gitbutler_repo::RepositoryExt as _;
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn namespace_with_several_refs() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    let repo = &test_repository.repository;
+    let head = repo.head().unwrap().target().unwrap();
+    for name in [
+        "refs/gitbutler/b",
+        "refs/gitbutler/a",
+        "refs/gitbutler/nested/c",
+        "refs/heads/other",
+    ] {
+        repo.reference(name, head, false, "").unwrap();
+    }
+
+    assert_eq!(
+        repo.refs_matching("refs/gitbutler/*").unwrap(),
+        [
+            ("refs/gitbutler/a".to_owned(), head),
+            ("refs/gitbutler/b".to_owned(), head),
+            ("refs/gitbutler/nested/c".to_owned(), head),
+        ]
+    );
+    assert!(repo.refs_matching("refs/tags/*").unwrap().is_empty());
+}
+
+#[test]
+fn invalid_glob_is_refused() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    for glob in ["", "refs/heads/a..b", "refs/with space/*"] {
+        let err = test_repository.repository.refs_matching(glob).unwrap_err();
+        assert_eq!(
+            err.custom_context().map(|ctx| ctx.code),
+            Some(Code::Validation),
+            "{glob:?}"
+        );
+    }
+}
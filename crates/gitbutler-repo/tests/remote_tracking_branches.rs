@@ -0,0 +1,68 @@
+use std::time::{Duration, SystemTime};
+
+use gitbutler_reference::RemoteRefname;
+use gitbutler_repo::RepositoryExt as _;
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn timestamp_of_fetch() {
+    let upstream = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    let upstream_head = upstream.repository.head().unwrap().target().unwrap();
+    upstream
+        .repository
+        .reference("refs/heads/feature", upstream_head, false, "")
+        .unwrap();
+
+    let test_repository = TestingRepository::open();
+    let repo = &test_repository.repository;
+    assert!(repo.remote_tracking_branches().unwrap().is_empty());
+
+    let mut remote = repo
+        .remote("origin", upstream.tempdir.path().to_str().unwrap())
+        .unwrap();
+    let before_fetch = SystemTime::now() - Duration::from_secs(1);
+    remote.fetch::<&str>(&[], None, None).unwrap();
+    repo.reference_symbolic(
+        "refs/remotes/origin/HEAD",
+        "refs/remotes/origin/master",
+        false,
+        "",
+    )
+    .unwrap();
+
+    let branches = repo.remote_tracking_branches().unwrap();
+    assert_eq!(
+        branches
+            .iter()
+            .map(|branch| (branch.name.clone(), branch.id))
+            .collect::<Vec<_>>(),
+        [
+            (RemoteRefname::new("origin", "feature"), upstream_head),
+            (RemoteRefname::new("origin", "master"), upstream_head),
+        ],
+        "symbolic refs are skipped"
+    );
+    for branch in branches {
+        let last_updated = branch.last_updated.expect("the fetch writes a reflog");
+        assert!(
+            last_updated >= before_fetch,
+            "{last_updated:?} is at least the time of the fetch"
+        );
+    }
+}
+
+#[test]
+fn no_reflog() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    let repo = &test_repository.repository;
+    let head = repo.head().unwrap().target().unwrap();
+    repo.reference("refs/remotes/origin/main", head, false, "")
+        .unwrap();
+    repo.reflog_delete("refs/remotes/origin/main").unwrap();
+
+    let branches = repo.remote_tracking_branches().unwrap();
+    assert_eq!(branches.len(), 1);
+    assert_eq!(branches[0].name, RemoteRefname::new("origin", "main"));
+    assert_eq!(branches[0].id, head);
+    assert_eq!(branches[0].last_updated, None);
+}
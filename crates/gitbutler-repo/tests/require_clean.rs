@@ -0,0 +1,40 @@
+use std::path::{Path, PathBuf};
+
+use gitbutler_error::error::{AnyhowContextExt, Code};
+use gitbutler_repo::{DirtyWorktree, RepositoryExt as _};
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn clean_worktree() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    std::fs::write(test_repository.tempdir.path().join("untracked"), "new").unwrap();
+
+    assert!(
+        test_repository.repository.require_clean().is_ok(),
+        "untracked files don't make the worktree dirty"
+    );
+}
+
+#[test]
+fn dirty_worktree_lists_changed_paths() {
+    let test_repository =
+        TestingRepository::open_with_initial_commit(&[("modified", "a"), ("staged", "a")]);
+    let repo = &test_repository.repository;
+    let workdir = test_repository.tempdir.path();
+    std::fs::write(workdir.join("modified"), "b").unwrap();
+    std::fs::write(workdir.join("staged"), "b").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("staged")).unwrap();
+    index.write().unwrap();
+
+    let err = repo.require_clean().unwrap_err();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::ProjectDirtyWorktree)
+    );
+    let dirty = err.downcast_ref::<DirtyWorktree>().unwrap();
+    assert_eq!(
+        dirty.paths,
+        [PathBuf::from("modified"), PathBuf::from("staged")]
+    );
+}
@@ -0,0 +1,118 @@
+//! Signing invokes the programs configured in `gpg.program` and `gpg.ssh.program`, which are mocked
+//! with shell scripts here.
+#![cfg(unix)]
+
+use std::os::unix::fs::PermissionsExt as _;
+use std::path::{Path, PathBuf};
+
+use gitbutler_error::error::{AnyhowContextExt, Code};
+use gitbutler_repo::RepositoryExt as _;
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+/// Write an executable script with `body` into `dir` and return its path.
+fn mock_program(dir: &Path, body: &str) -> PathBuf {
+    let path = dir.join("mock-signer");
+    std::fs::write(&path, format!("#!/bin/sh\n{body}\n")).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+#[test]
+fn configured_gpg_program_is_invoked() {
+    let test_repository = TestingRepository::open();
+    let repo = &test_repository.repository;
+    let args = test_repository.tempdir.path().join("args");
+    let program = mock_program(
+        test_repository.tempdir.path(),
+        &format!(
+            "echo \"$@\" > '{}'\ncat > /dev/null\nprintf 'MOCK SIGNATURE'",
+            args.display()
+        ),
+    );
+    let mut config = repo.config().unwrap();
+    config.set_str("user.signingkey", "ABCDEF").unwrap();
+    config
+        .set_str("gpg.program", program.to_str().unwrap())
+        .unwrap();
+
+    let signature = repo.sign_buffer(b"commit").unwrap();
+    assert_eq!(signature, "MOCK SIGNATURE");
+    assert_eq!(
+        std::fs::read_to_string(args).unwrap(),
+        "--status-fd=2 -bsau ABCDEF -\n",
+        "it's invoked with the same arguments Git uses"
+    );
+}
+
+#[test]
+fn failing_gpg_program_is_reported() {
+    let test_repository = TestingRepository::open();
+    let repo = &test_repository.repository;
+    let program = mock_program(
+        test_repository.tempdir.path(),
+        "cat > /dev/null\necho 'gpg: signing failed: No secret key' >&2\nexit 2",
+    );
+    let mut config = repo.config().unwrap();
+    config.set_str("user.signingkey", "ABCDEF").unwrap();
+    config
+        .set_str("gpg.program", program.to_str().unwrap())
+        .unwrap();
+
+    let err = repo.sign_buffer(b"commit").unwrap_err();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::CommitSigningFailed)
+    );
+    assert!(
+        format!("{err:#}").contains("gpg: signing failed: No secret key"),
+        "the output of the program is shown: {err:#}"
+    );
+}
+
+#[test]
+fn failing_ssh_program_is_reported() {
+    let test_repository = TestingRepository::open();
+    let repo = &test_repository.repository;
+    let program = mock_program(
+        test_repository.tempdir.path(),
+        "echo \"Couldn't load public key\" >&2\nexit 255",
+    );
+    let mut config = repo.config().unwrap();
+    config
+        .set_str("user.signingkey", "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5")
+        .unwrap();
+    config.set_str("gpg.format", "ssh").unwrap();
+    config
+        .set_str("gpg.ssh.program", program.to_str().unwrap())
+        .unwrap();
+
+    let err = repo.sign_buffer(b"commit").unwrap_err();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::CommitSigningFailed)
+    );
+    assert!(
+        format!("{err:#}").contains("Couldn't load public key"),
+        "{err:#}"
+    );
+}
+
+#[test]
+fn program_that_cannot_be_executed_is_reported() {
+    let test_repository = TestingRepository::open();
+    let repo = &test_repository.repository;
+    let program = mock_program(test_repository.tempdir.path(), "exit 0");
+    std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o644)).unwrap();
+    let mut config = repo.config().unwrap();
+    config.set_str("user.signingkey", "ABCDEF").unwrap();
+    config
+        .set_str("gpg.program", program.to_str().unwrap())
+        .unwrap();
+
+    let err = repo.sign_buffer(b"commit").unwrap_err();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::CommitSigningFailed),
+        "not only missing programs are reported as signing failures: {err:#}"
+    );
+}
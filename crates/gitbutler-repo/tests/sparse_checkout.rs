@@ -0,0 +1,42 @@
+use std::path::Path;
+
+use gitbutler_error::error::{AnyhowContextExt, Code};
+use gitbutler_repo::sparse_checkout::{set_sparse_checkout, sparse_checkout_patterns};
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn cone_mode_removes_files_outside_of_cone_from_worktree_only() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[
+        ("root", "root"),
+        ("included/file", "included"),
+        ("excluded/file", "excluded"),
+    ]);
+    let repo = &test_repository.repository;
+    let workdir = test_repository.tempdir.path();
+    assert!(sparse_checkout_patterns(repo).unwrap().is_empty());
+
+    set_sparse_checkout(repo, &["included".into()], true).unwrap();
+
+    assert!(workdir.join("root").is_file(), "root files are always kept");
+    assert!(workdir.join("included/file").is_file());
+    assert!(!workdir.join("excluded/file").exists());
+    let mut index = repo.index().unwrap();
+    index.read(true).unwrap();
+    assert!(
+        index.get_path(Path::new("excluded/file"), 0).is_some(),
+        "the file is still tracked"
+    );
+
+    assert_eq!(sparse_checkout_patterns(repo).unwrap(), ["included"]);
+}
+
+#[test]
+fn cone_mode_rejects_globs() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("root", "root")]);
+    let err =
+        set_sparse_checkout(&test_repository.repository, &["*.txt".into()], true).unwrap_err();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::Validation)
+    );
+}
@@ -0,0 +1,98 @@
+use std::path::Path;
+
+use gitbutler_error::error::{AnyhowContextExt, Code};
+use gitbutler_repo::staging::{index_entries, remove_index_entry, stage_blob, IndexEntry};
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn stage_blob_by_id_and_read_it_back() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    let repo = &test_repository.repository;
+    let blob = repo.blob(b"only staged").unwrap();
+
+    stage_blob(
+        repo,
+        Path::new("dir/new"),
+        blob,
+        git2::FileMode::BlobExecutable,
+    )
+    .unwrap();
+
+    let entries = index_entries(repo).unwrap();
+    assert_eq!(
+        entries
+            .iter()
+            .find(|entry| entry.path == Path::new("dir/new")),
+        Some(&IndexEntry {
+            path: "dir/new".into(),
+            id: blob,
+            mode: git2::FileMode::BlobExecutable,
+            stage: 0,
+        })
+    );
+    assert!(
+        !test_repository.tempdir.path().join("dir/new").exists(),
+        "the worktree isn't touched"
+    );
+    let reopened = git2::Repository::open(repo.path()).unwrap();
+    assert!(
+        reopened
+            .index()
+            .unwrap()
+            .get_path(Path::new("dir/new"), 0)
+            .is_some(),
+        "the index was written"
+    );
+}
+
+#[test]
+fn remove_entry_keeps_worktree() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    let repo = &test_repository.repository;
+
+    remove_index_entry(repo, Path::new("file")).unwrap();
+    assert!(index_entries(repo)
+        .unwrap()
+        .iter()
+        .all(|entry| entry.path != Path::new("file")));
+    assert!(test_repository.tempdir.path().join("file").exists());
+
+    let err = remove_index_entry(repo, Path::new("file")).unwrap_err();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::Validation)
+    );
+}
+
+#[test]
+fn non_blobs_are_refused() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    let repo = &test_repository.repository;
+    let tree = repo.head().unwrap().peel_to_tree().unwrap().id();
+
+    for (id, mode) in [
+        (tree, git2::FileMode::Blob),
+        (repo.blob(b"content").unwrap(), git2::FileMode::Tree),
+    ] {
+        let err = stage_blob(repo, Path::new("new"), id, mode).unwrap_err();
+        assert_eq!(
+            err.custom_context().map(|ctx| ctx.code),
+            Some(Code::Validation)
+        );
+    }
+}
+
+#[test]
+fn locked_index_is_respected() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    let repo = &test_repository.repository;
+    let blob = repo.blob(b"content").unwrap();
+    std::fs::write(repo.path().join("index.lock"), "").unwrap();
+
+    let err = stage_blob(repo, Path::new("new"), blob, git2::FileMode::Blob).unwrap_err();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::ProjectGitLocked),
+        "{err:#}"
+    );
+}
@@ -0,0 +1,64 @@
+use gitbutler_diff::ChangeType;
+use gitbutler_error::error::{AnyhowContextExt, Code};
+use gitbutler_repo::RepositoryExt as _;
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn tracked_changes_and_untracked_files() {
+    let test_repository =
+        TestingRepository::open_with_initial_commit(&[("modified", "a\n"), ("untouched", "a\n")]);
+    let workdir = test_repository.tempdir.path();
+    std::fs::write(workdir.join("modified"), "b\n").unwrap();
+    std::fs::write(workdir.join("untracked"), "new\n").unwrap();
+    let mut repo = git2::Repository::open(workdir).unwrap();
+    let signature = git2::Signature::now("test", "test@example.com").unwrap();
+    repo.stash_save(
+        &signature,
+        "with untracked",
+        Some(git2::StashFlags::INCLUDE_UNTRACKED),
+    )
+    .unwrap();
+    assert!(
+        !workdir.join("untracked").exists(),
+        "the untracked file is in the stash"
+    );
+
+    let files = test_repository.repository.stash_show(0).unwrap();
+
+    let summary: Vec<_> = files
+        .iter()
+        .map(|file| {
+            (
+                file.path.to_str().unwrap(),
+                file.hunks[0].change_type,
+                file.hunks[0].diff_lines.to_string(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (
+                "modified",
+                ChangeType::Modified,
+                "@@ -1 +1 @@\n-a\n+b\n".to_string()
+            ),
+            (
+                "untracked",
+                ChangeType::Added,
+                "@@ -0,0 +1 @@\n+new\n".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn missing_entry() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a\n")]);
+
+    let err = test_repository.repository.stash_show(0).unwrap_err();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::Validation)
+    );
+}
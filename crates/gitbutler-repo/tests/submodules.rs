@@ -0,0 +1,79 @@
+use std::path::PathBuf;
+
+use gitbutler_repo::submodules::{submodule_issues, SubmoduleIssue};
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+/// Add a gitlink to `commit` at `path` to the index, like `git add` of a nested repository does.
+fn add_gitlink(repo: &git2::Repository, path: &str, commit: git2::Oid) {
+    let mut index = repo.index().unwrap();
+    index
+        .add(&git2::IndexEntry {
+            ctime: git2::IndexTime::new(0, 0),
+            mtime: git2::IndexTime::new(0, 0),
+            dev: 0,
+            ino: 0,
+            mode: 0o160000,
+            uid: 0,
+            gid: 0,
+            file_size: 0,
+            id: commit,
+            flags: path.len() as u16,
+            flags_extended: 0,
+            path: path.into(),
+        })
+        .unwrap();
+    index.write().unwrap();
+}
+
+#[test]
+fn gitlink_without_gitmodules_entry() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    let repo = &test_repository.repository;
+    let head = repo.head().unwrap().target().unwrap();
+    add_gitlink(repo, "vendor/lib", head);
+
+    assert_eq!(
+        submodule_issues(repo).unwrap(),
+        [SubmoduleIssue::MissingEntry {
+            path: PathBuf::from("vendor/lib")
+        }]
+    );
+}
+
+#[test]
+fn uninitialized_and_missing_url() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    let repo = &test_repository.repository;
+    std::fs::write(
+        test_repository.tempdir.path().join(".gitmodules"),
+        "[submodule \"lib\"]\n\tpath = lib\n\turl = https://example.com/lib.git\n\
+         [submodule \"broken\"]\n\tpath = broken\n",
+    )
+    .unwrap();
+    let head = repo.head().unwrap().target().unwrap();
+    add_gitlink(repo, "broken", head);
+    add_gitlink(repo, "lib", head);
+
+    assert_eq!(
+        submodule_issues(repo).unwrap(),
+        [
+            SubmoduleIssue::MissingUrl {
+                name: "broken".into(),
+                path: PathBuf::from("broken")
+            },
+            SubmoduleIssue::Uninitialized {
+                name: "lib".into(),
+                path: PathBuf::from("lib")
+            },
+        ]
+    );
+}
+
+#[test]
+fn no_submodules() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+
+    assert!(submodule_issues(&test_repository.repository)
+        .unwrap()
+        .is_empty());
+}
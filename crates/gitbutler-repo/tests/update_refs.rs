@@ -0,0 +1,72 @@
+use gitbutler_error::error::{AnyhowContextExt, Code};
+use gitbutler_repo::{RefUpdate, RepositoryExt as _};
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn atomic_update_of_two_refs() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    let repo = &test_repository.repository;
+    let base = repo.head().unwrap().peel_to_commit().unwrap();
+    let next = test_repository.commit_tree(Some(&base), &[("file", "b")]);
+    repo.reference("refs/heads/to-delete", base.id(), false, "")
+        .unwrap();
+
+    repo.update_refs(vec![
+        RefUpdate {
+            name: "refs/heads/master".into(),
+            expected: Some(base.id()),
+            new: Some(next.id()),
+        },
+        RefUpdate {
+            name: "refs/heads/created".into(),
+            expected: None,
+            new: Some(base.id()),
+        },
+        RefUpdate {
+            name: "refs/heads/to-delete".into(),
+            expected: Some(base.id()),
+            new: None,
+        },
+    ])
+    .unwrap();
+
+    assert_eq!(repo.refname_to_id("refs/heads/master").unwrap(), next.id());
+    assert_eq!(repo.refname_to_id("refs/heads/created").unwrap(), base.id());
+    assert!(repo.find_reference("refs/heads/to-delete").is_err());
+}
+
+#[test]
+fn stale_expectation_rejects_all_updates() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    let repo = &test_repository.repository;
+    let base = repo.head().unwrap().peel_to_commit().unwrap();
+    let next = test_repository.commit_tree(Some(&base), &[("file", "b")]);
+    repo.reference("refs/heads/other", next.id(), false, "")
+        .unwrap();
+
+    let err = repo
+        .update_refs(vec![
+            RefUpdate {
+                name: "refs/heads/master".into(),
+                expected: Some(base.id()),
+                new: Some(next.id()),
+            },
+            RefUpdate {
+                name: "refs/heads/other".into(),
+                expected: Some(base.id()),
+                new: Some(base.id()),
+            },
+        ])
+        .unwrap_err();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::Validation)
+    );
+
+    assert_eq!(
+        repo.refname_to_id("refs/heads/master").unwrap(),
+        base.id(),
+        "the valid update wasn't applied either"
+    );
+    assert_eq!(repo.refname_to_id("refs/heads/other").unwrap(), next.id());
+}
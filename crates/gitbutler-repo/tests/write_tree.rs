@@ -0,0 +1,79 @@
+use std::path::Path;
+
+use gitbutler_error::error::{AnyhowContextExt, Code};
+use gitbutler_repo::RepositoryExt as _;
+use gitbutler_testsupport::testing_repository::TestingRepository;
+
+#[test]
+fn staged_and_unstaged_changes() {
+    let test_repository =
+        TestingRepository::open_with_initial_commit(&[("staged", "a"), ("unstaged", "a")]);
+    let repo = &test_repository.repository;
+    let workdir = test_repository.tempdir.path();
+    let head_tree = repo.head().unwrap().peel_to_tree().unwrap().id();
+    assert_eq!(repo.write_tree().unwrap(), head_tree, "nothing changed yet");
+
+    std::fs::write(workdir.join("staged"), "b").unwrap();
+    std::fs::write(workdir.join("unstaged"), "b").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("staged")).unwrap();
+    index.write().unwrap();
+
+    let index_tree = repo.find_tree(repo.write_tree().unwrap()).unwrap();
+    assert_ne!(index_tree.id(), head_tree);
+    let content = |tree: &git2::Tree, path: &str| {
+        let blob = tree.get_path(Path::new(path)).unwrap().id();
+        repo.find_blob(blob).unwrap().content().to_owned()
+    };
+    assert_eq!(content(&index_tree, "staged"), b"b");
+    assert_eq!(
+        content(&index_tree, "unstaged"),
+        b"a",
+        "unstaged changes aren't included"
+    );
+
+    let workdir_tree = repo.find_tree(repo.write_workdir_tree().unwrap()).unwrap();
+    assert_eq!(content(&workdir_tree, "staged"), b"b");
+    assert_eq!(content(&workdir_tree, "unstaged"), b"b");
+    assert!(
+        repo.index()
+            .unwrap()
+            .get_path(Path::new("unstaged"), 0)
+            .is_some_and(
+                |entry| entry.id == index_tree.get_path(Path::new("unstaged")).unwrap().id()
+            ),
+        "the index is left alone"
+    );
+}
+
+#[test]
+fn conflicts_are_rejected() {
+    let test_repository = TestingRepository::open_with_initial_commit(&[("file", "a")]);
+    let repo = &test_repository.repository;
+    let blob = repo.blob(b"b").unwrap();
+    let entry = |stage: u16| git2::IndexEntry {
+        ctime: git2::IndexTime::new(0, 0),
+        mtime: git2::IndexTime::new(0, 0),
+        dev: 0,
+        ino: 0,
+        mode: git2::FileMode::Blob.into(),
+        uid: 0,
+        gid: 0,
+        file_size: 1,
+        id: blob,
+        flags: stage << 12,
+        flags_extended: 0,
+        path: b"file".to_vec(),
+    };
+    let mut index = repo.index().unwrap();
+    index
+        .conflict_add(Some(&entry(1)), Some(&entry(2)), Some(&entry(3)))
+        .unwrap();
+    index.write().unwrap();
+
+    let err = repo.write_tree().unwrap_err();
+    assert_eq!(
+        err.custom_context().map(|ctx| ctx.code),
+        Some(Code::CommitMergeConflictFailure)
+    );
+}
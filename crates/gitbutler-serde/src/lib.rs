@@ -58,6 +58,19 @@ where
     v.seconds().serialize(s)
 }
 
+/// Serialize the `(old, new)` modes of a file as octal strings, like `100755`, as Git would display them.
+pub fn as_octal_mode_change<S>(
+    v: &Option<(git2::FileMode, git2::FileMode)>,
+    s: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    let to_octal = |mode: git2::FileMode| format!("{:o}", i32::from(mode));
+    v.map(|(old, new)| (to_octal(old), to_octal(new)))
+        .serialize(s)
+}
+
 pub mod oid_opt {
     use serde::{Deserialize, Deserializer, Serialize};
 
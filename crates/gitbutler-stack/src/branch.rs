@@ -0,0 +1,70 @@
+//! Access to the target branch, the branch on the remote that all stacks are integrated into,
+//! for callers that only have a repository at hand.
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use gitbutler_reference::RemoteRefname;
+
+use crate::{Target, VirtualBranchesHandle};
+
+/// The target branch as persisted in the GitButler state of a repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetBranch {
+    /// The name of the remote the target branch is fetched from, like `origin`.
+    pub remote: String,
+    /// The name of the branch on the remote, like `main`.
+    pub name: String,
+    /// The commit of the target branch seen most recently, updated when the workspace is
+    /// integrated with the remote.
+    pub sha: git2::Oid,
+}
+
+impl From<&Target> for TargetBranch {
+    fn from(target: &Target) -> Self {
+        TargetBranch {
+            remote: target.branch.remote().to_owned(),
+            name: target.branch.branch().to_owned(),
+            sha: target.sha,
+        }
+    }
+}
+
+/// Return the target branch of `repo`, or `None` if none was set yet.
+pub fn target(repo: &git2::Repository) -> Result<Option<TargetBranch>> {
+    Ok(VirtualBranchesHandle::new(gb_dir(repo))
+        .maybe_get_default_target()?
+        .as_ref()
+        .map(TargetBranch::from))
+}
+
+/// Make `target` the target branch of `repo`, using the URL `target.remote` currently has.
+/// The remote to push to is kept if one was configured for the previous target.
+///
+/// Fails if `target.remote` doesn't exist in `repo`.
+pub fn set_target(repo: &git2::Repository, target: &TargetBranch) -> Result<()> {
+    let remote = repo
+        .find_remote(&target.remote)
+        .with_context(|| format!("Remote '{}' doesn't exist", target.remote))?;
+    let remote_url = remote
+        .url()
+        .with_context(|| format!("Remote '{}' has no URL or it isn't UTF-8", target.remote))?
+        .to_owned();
+
+    let gb_dir = gb_dir(repo);
+    std::fs::create_dir_all(&gb_dir)?;
+    let handle = VirtualBranchesHandle::new(gb_dir);
+    let push_remote_name = handle
+        .maybe_get_default_target()?
+        .and_then(|previous| previous.push_remote_name);
+    handle.set_default_target(Target {
+        branch: RemoteRefname::new(&target.remote, &target.name),
+        remote_url,
+        sha: target.sha,
+        push_remote_name,
+    })
+}
+
+/// The state is kept next to the Git data, see `Project::gb_dir()`.
+fn gb_dir(repo: &git2::Repository) -> PathBuf {
+    repo.path().join("gitbutler")
+}
@@ -1,4 +1,5 @@
 #![warn(clippy::indexing_slicing)]
+pub mod branch;
 mod file_ownership;
 mod ownership;
 mod stack;
@@ -7,7 +8,9 @@ mod state;
 mod target;
 
 pub use file_ownership::OwnershipClaim;
-pub use ownership::{reconcile_claims, BranchOwnershipClaims, ClaimOutcome};
+pub use ownership::{
+    ownership_conflicts, reconcile_claims, BranchOwnershipClaims, ClaimOutcome, OwnershipConflict,
+};
 pub use stack::{Stack, StackId};
 pub use state::{VirtualBranches as VirtualBranchesState, VirtualBranchesHandle};
 pub use target::Target;
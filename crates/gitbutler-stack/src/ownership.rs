@@ -1,10 +1,11 @@
-use std::{collections::HashSet, fmt, str::FromStr};
+use std::{collections::HashSet, fmt, path::PathBuf, str::FromStr};
 
 use anyhow::Result;
+use gitbutler_diff::{DiffByPathMap, GitHunk};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize, Serializer};
 
-use crate::{file_ownership::OwnershipClaim, Stack};
+use crate::{file_ownership::OwnershipClaim, Stack, StackId};
 
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct BranchOwnershipClaims {
@@ -147,3 +148,55 @@ pub fn reconcile_claims(
 
     Ok(claim_outcomes)
 }
+
+/// Two stacks that both claim the lines `start..end` of the file at `file_path`, as returned by [`ownership_conflicts()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnershipConflict {
+    pub file_path: PathBuf,
+    /// The contending stacks, in the order they were passed.
+    pub stacks: (StackId, StackId),
+    /// The first line claimed by both stacks.
+    pub start: u32,
+    /// One past the last line claimed by both stacks.
+    pub end: u32,
+}
+
+/// Return all ranges of lines claimed by more than one of `stacks`, limited to the ones that are
+/// still part of the uncommitted changes in `diffs`, as claims of lines that changed back don't matter.
+pub fn ownership_conflicts(stacks: &[Stack], diffs: &DiffByPathMap) -> Vec<OwnershipConflict> {
+    let mut conflicts = Vec::new();
+    for (idx, a) in stacks.iter().enumerate() {
+        for b in stacks.iter().skip(idx + 1) {
+            for a_claim in &a.ownership.claims {
+                let Some(diff) = diffs.get(&a_claim.file_path) else {
+                    continue;
+                };
+                let b_hunks = b
+                    .ownership
+                    .claims
+                    .iter()
+                    .filter(|b_claim| b_claim.file_path == a_claim.file_path)
+                    .flat_map(|b_claim| &b_claim.hunks);
+                for (a_hunk, b_hunk) in a_claim.hunks.iter().cartesian_product(b_hunks) {
+                    let (start, end) = (a_hunk.start.max(b_hunk.start), a_hunk.end.min(b_hunk.end));
+                    if start < end && diff.hunks.iter().any(|hunk| overlaps(hunk, start, end)) {
+                        conflicts.push(OwnershipConflict {
+                            file_path: a_claim.file_path.clone(),
+                            stacks: (a.id, b.id),
+                            start,
+                            end,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    conflicts
+}
+
+/// Return `true` if the lines `start..end` of the new version of the file are part of `hunk`.
+fn overlaps(hunk: &GitHunk, start: u32, end: u32) -> bool {
+    // Deletions still occupy the line they are anchored at.
+    let hunk_end = hunk.new_start + hunk.new_lines.max(1);
+    hunk.new_start < end && start < hunk_end
+}
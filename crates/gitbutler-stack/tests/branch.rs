@@ -0,0 +1,34 @@
+use gitbutler_stack::branch::{self, TargetBranch};
+
+#[test]
+fn target_is_none_until_set() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let repo = git2::Repository::init(tmp.path())?;
+    assert_eq!(branch::target(&repo)?, None);
+
+    repo.remote("origin", "https://example.com/repo.git")?;
+    let sha = repo.treebuilder(None)?.write()?;
+    let target = TargetBranch {
+        remote: "origin".into(),
+        name: "main".into(),
+        sha,
+    };
+    branch::set_target(&repo, &target)?;
+    assert_eq!(branch::target(&repo)?, Some(target));
+    Ok(())
+}
+
+#[test]
+fn target_with_unknown_remote_is_refused() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let repo = git2::Repository::init(tmp.path())?;
+    let target = TargetBranch {
+        remote: "origin".into(),
+        name: "main".into(),
+        sha: git2::Oid::zero(),
+    };
+    let err = branch::set_target(&repo, &target).unwrap_err();
+    assert_eq!(err.to_string(), "Remote 'origin' doesn't exist");
+    assert_eq!(branch::target(&repo)?, None);
+    Ok(())
+}
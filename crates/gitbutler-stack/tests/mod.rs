@@ -1,3 +1,4 @@
+mod branch;
 mod file_ownership;
 mod ownership;
 
@@ -5,7 +6,7 @@ use anyhow::Result;
 use but_core::Reference;
 use gitbutler_command_context::CommandContext;
 use gitbutler_repo::logging::{LogUntil, RepositoryExt as _};
-use gitbutler_repo_actions::RepoActionsExt;
+use gitbutler_repo_actions::{PushOutcome, RepoActionsExt};
 use gitbutler_stack::stack_context::CommandContextExt;
 use gitbutler_stack::{CommitOrChangeId, StackBranch, VirtualBranchesHandle};
 use gitbutler_stack::{PatchReferenceUpdate, TargetUpdate};
@@ -595,6 +596,35 @@ fn update_name_after_push() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn push_without_changes_is_up_to_date() -> Result<()> {
+    let (ctx, _temp_dir) = command_ctx("multiple-commits")?;
+    let test_ctx = test_ctx(&ctx)?;
+
+    let state = VirtualBranchesHandle::new(ctx.project().gb_dir());
+    let mut target = state.get_default_target()?;
+    target.push_remote_name = Some("origin".into());
+    state.set_default_target(target)?;
+
+    let push_details = test_ctx.stack.push_details(&ctx, "a-branch-2".into())?;
+    let push = || {
+        ctx.push(
+            push_details.head,
+            &push_details.remote_refname,
+            false,
+            None,
+            Some(Some(test_ctx.stack.id)),
+        )
+    };
+    assert!(matches!(push()?, PushOutcome::Pushed(_)));
+    assert_eq!(
+        push()?,
+        PushOutcome::UpToDate,
+        "the remote already has the commit"
+    );
+    Ok(())
+}
+
 #[test]
 fn list_series_default_head() -> Result<()> {
     let (ctx, _temp_dir) = command_ctx("multiple-commits")?;
@@ -1,7 +1,10 @@
 use std::{path::PathBuf, vec};
 
 use gitbutler_diff::Hunk;
-use gitbutler_stack::{reconcile_claims, BranchOwnershipClaims, OwnershipClaim, Stack};
+use gitbutler_stack::{
+    ownership_conflicts, reconcile_claims, BranchOwnershipClaims, OwnershipClaim,
+    OwnershipConflict, Stack,
+};
 
 #[test]
 fn reconcile_ownership_simple() {
@@ -300,3 +303,56 @@ fn equal() {
         assert_eq!(a == b, expected, "{:#?} == {:#?}", a, b);
     }
 }
+
+#[test]
+fn overlapping_claims_of_changed_lines_conflict() {
+    let stack_with_claims = |name: &str, claims: &str| {
+        #[allow(deprecated)] // this is a test
+        let mut stack = Stack::new(
+            name.to_string(),
+            None,
+            None,
+            None,
+            git2::Oid::zero(),
+            git2::Oid::zero(),
+            usize::default(),
+            None,
+            true,
+        );
+        stack.ownership = claims.parse().unwrap();
+        stack
+    };
+    let a = stack_with_claims("a", "foo:1-10\nunchanged:1-5");
+    let b = stack_with_claims("b", "foo:5-12,20-25\nunchanged:1-5");
+    let c = stack_with_claims("c", "foo:30-40");
+    let hunk = |new_start, new_lines| gitbutler_diff::GitHunk {
+        old_start: new_start,
+        old_lines: new_lines,
+        new_start,
+        new_lines,
+        diff_lines: Default::default(),
+        binary: false,
+        change_type: gitbutler_diff::ChangeType::Modified,
+    };
+    let diffs = [(
+        PathBuf::from("foo"),
+        gitbutler_diff::FileDiff {
+            path: PathBuf::from("foo"),
+            hunks: vec![hunk(1, 24)],
+            ..Default::default()
+        },
+    )]
+    .into_iter()
+    .collect();
+
+    assert_eq!(
+        ownership_conflicts(&[a.clone(), b.clone(), c], &diffs),
+        [OwnershipConflict {
+            file_path: PathBuf::from("foo"),
+            stacks: (a.id, b.id),
+            start: 5,
+            end: 10,
+        }],
+        "files without changes and claims of different lines don't conflict"
+    );
+}
@@ -73,7 +73,7 @@ impl App {
     ) -> Result<()> {
         let project = self.projects().get(project_id)?;
         let ctx = CommandContext::open(&project, settings)?;
-        ctx.fetch(remote_name, askpass)
+        ctx.fetch(remote_name, vec![], askpass)
     }
 
     pub fn git_index_size(&self, project_id: ProjectId, settings: AppSettings) -> Result<usize> {
@@ -3,8 +3,10 @@ use gitbutler_branch_actions::stack::CreateSeriesRequest;
 use gitbutler_command_context::CommandContext;
 use gitbutler_project as projects;
 use gitbutler_project::ProjectId;
+use gitbutler_repo_actions::PushOutcome;
 use gitbutler_stack::StackId;
 use gitbutler_user::User;
+use serde::Serialize;
 use tauri::State;
 use tracing::instrument;
 
@@ -104,6 +106,22 @@ pub fn update_series_pr_number(
     Ok(())
 }
 
+/// The result of pushing one series with [`push_stack()`].
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase", tag = "status")]
+pub enum SeriesPushResult {
+    /// The series was pushed, and the remote printed `remote_messages` while receiving it.
+    #[serde(rename_all = "camelCase")]
+    Pushed {
+        name: String,
+        remote_messages: Vec<String>,
+    },
+    /// The remote already had the series, so nothing was pushed.
+    UpToDate { name: String },
+    /// Pushing the series failed.
+    Error { name: String, error: Error },
+}
+
 #[tauri::command(async)]
 #[instrument(skip(projects, windows, settings), err(Debug))]
 pub fn push_stack(
@@ -113,12 +131,26 @@ pub fn push_stack(
     project_id: ProjectId,
     branch_id: StackId,
     with_force: bool,
-) -> Result<(), Error> {
+) -> Result<Vec<SeriesPushResult>, Error> {
     let project = projects.get(project_id)?;
     let ctx = CommandContext::open(&project, settings.get()?.clone())?;
-    gitbutler_branch_actions::stack::push_stack(&ctx, branch_id, with_force)?;
+    let outcome = gitbutler_branch_actions::stack::push_stack(&ctx, branch_id, with_force)?;
+    // Some branches may have been pushed, which is visible once the branches are refreshed.
     emit_vbranches(&windows, project_id, ctx.app_settings());
-    Ok(())
+    Ok(outcome
+        .into_iter()
+        .map(|result| match result {
+            Ok((name, PushOutcome::Pushed(remote_messages))) => SeriesPushResult::Pushed {
+                name,
+                remote_messages,
+            },
+            Ok((name, PushOutcome::UpToDate)) => SeriesPushResult::UpToDate { name },
+            Err((name, err)) => SeriesPushResult::Error {
+                name,
+                error: err.into(),
+            },
+        })
+        .collect())
 }
 
 #[tauri::command(async)]
@@ -1 +1,8 @@
 export type FileStatus = 'A' | 'M' | 'D';
+
+/** The old and new mode of a file whose executable bit changed, as octal strings like `100755`. */
+export type ModeChange = [string, string];
+
+export function modeChangeText([oldMode, newMode]: ModeChange): string {
+	return `mode changed ${oldMode} → ${newMode}`;
+}